@@ -1,9 +1,13 @@
 #![no_std]
 use soroban_sdk::{
-    contract, contractimpl, contracterror, contracttype, token, Address, Env,
+    contract, contractclient, contracterror, contractimpl, contracttype, symbol_short, token,
+    Address, BytesN, Env, IntoVal, String, Symbol, Val, Vec,
 };
 
 /// Custom error types for the contract
+/// 
+/// Contract specs allow at most 50 error variants and this enum is full, so
+/// new failure cases map onto the closest existing variant.
 #[contracterror]
 #[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
 #[repr(u32)]
@@ -24,6 +28,67 @@ pub enum Error {
     DivisionError = 14,
     Underflow = 15,
     GoalOverflow = 16,
+    WithdrawRequestRequired = 17,
+    WithdrawNotRequested = 18,
+    WithdrawAlreadyRequested = 19,
+    CooldownNotElapsed = 20,
+    InsufficientReserve = 21,
+    InvalidThresholds = 22,
+    NoDrawdown = 23,
+    InstallmentNotDue = 24,
+    OracleNotConfigured = 25,
+    PriceUnavailable = 26,
+    PriceStale = 27,
+    NoIou = 28,
+    NotRenewable = 29,
+    InterestNotRealized = 30,
+    TokenPaused = 31,
+    InvalidToken = 32,
+    TokenNotSupported = 33,
+    InvalidSplit = 34,
+    AmountExceedsBalance = 35,
+    GoalMatured = 36,
+    LabelTooLong = 37,
+    Paused = 38,
+    InvalidRateTiers = 39,
+    InvalidBatch = 40,
+    GracePeriodExpired = 41,
+    DepositBelowMinimum = 42,
+    DepositAboveMaximum = 43,
+    GoalExists = 44,
+    InsufficientBalance = 45,
+    TransferFailed = 46,
+    GoalLimitReached = 47,
+    UnlockInPast = 48,
+    CooldownActive = 49,
+    Reentrancy = 50,
+}
+
+/// Identifies which arithmetic step failed, reported by the `diagnostics`
+/// feature's `diagnose_goal` query
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum ErrorContext {
+    /// No step failed
+    None = 0,
+    /// `current_time - last_compound_time`
+    ElapsedTime = 1,
+    /// `principal + accrued_interest`
+    TotalBalance = 2,
+    /// `balance * interest_rate`
+    InterestRateMul = 3,
+    /// `balance * interest_rate * time_elapsed`
+    InterestTimeMul = 4,
+    /// Interest division by `SECONDS_PER_YEAR * BASIS_POINTS`
+    InterestDiv = 5,
+    /// `balance + pending_interest`
+    BalanceWithInterest = 6,
+    /// `balance * penalty_rate`
+    PenaltyMul = 7,
+    /// `balance - penalty`
+    PenaltySub = 8,
+    /// `base_penalty * remaining_lock / lock_duration`
+    PenaltyDecay = 9,
 }
 
 /// Represents a single savings goal with time-lock mechanism
@@ -48,6 +113,66 @@ pub struct SavingsGoal {
     pub last_compound_time: u64,
     /// Whether this goal is active
     pub is_active: bool,
+    /// Informational reference to an external position (e.g. an NFT)
+    pub linked_asset: LinkedAsset,
+    /// Whether the goal re-locks for another term at maturity via `process_renewal`
+    pub auto_renew: bool,
+    /// Ledger sequence the goal was created in (0 for goals created before
+    /// this was tracked)
+    pub created_ledger: u32,
+    /// Number of compounds that credited interest (0 for goals created before
+    /// this was tracked)
+    pub compound_count: u32,
+    /// Interest prepaid at creation and held back for this goal (0 if the
+    /// goal isn't prepaid)
+    pub prepaid_interest: i128,
+    /// Optional display name chosen by the owner (at most 32 bytes)
+    pub label: Option<String>,
+    /// Accrual mode; goals created before this was added compound linearly
+    pub compound_mode: CompoundMode,
+    /// Address allowed to claim the goal after an inactivity window past unlock
+    pub beneficiary: Option<Address>,
+    /// Token the goal is denominated in; all of its transfers use this token
+    pub token: Address,
+    /// Amount the owner plans to add each `recurring_interval` (0: no plan)
+    pub recurring_amount: i128,
+    /// Seconds between planned contributions (0: no plan)
+    pub recurring_interval: u64,
+    /// Top-ups made via `deposit` in the current term
+    pub deposit_count: u32,
+    /// Whether interest keeps accruing past unlock time; `None` follows the
+    /// contract-wide setting
+    pub accrue_after_unlock: Option<bool>,
+    /// Balance the owner is saving toward (0: no target)
+    pub target_amount: i128,
+    /// Whether `poke` has announced the current term's maturity
+    pub maturity_notified: bool,
+    /// Whether interest is paid in the configured reward token rather than
+    /// `token` (set for goals opened while a reward token was configured)
+    pub interest_in_reward: bool,
+    /// Whether the owner has frozen interest accrual on this goal
+    pub frozen: bool,
+    /// Seconds after `start_time` before interest starts accruing (0: from
+    /// the start)
+    pub accrual_start_offset: u64,
+    /// Timestamp the goal was closed by a payout (withdrawal, emergency
+    /// withdrawal, cancellation or dust close); 0 while active
+    pub withdrawn_at: u64,
+    /// Timestamp the goal was created; unlike `start_time` it never moves
+    pub created_at: u64,
+    /// Whether the goal was ever merged into, re-locked or renewed; such
+    /// goals can no longer be cancelled
+    pub restarted: bool,
+}
+
+/// Reference to an external position a goal is saving toward
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum LinkedAsset {
+    /// No position linked
+    None,
+    /// (contract, token_id) of the linked position
+    Position(Address, i128),
 }
 
 /// Storage keys for the contract
@@ -57,14 +182,389 @@ pub enum StorageKey {
     Token,
     /// Admin address
     Admin,
-    /// Counter for goal IDs
+    /// Counter for goal IDs, shared by all owners (see `store_new_goal` for
+    /// why there is no per-user counter)
     GoalCounter,
     /// Mapping: (owner, goal_id) -> SavingsGoal
     Goal(Address, u64),
     /// User's goal count
     UserGoalCount(Address),
+    /// Mapping: user -> IDs of all goals they have owned, in creation order
+    UserGoalIds(Address),
     /// Emergency withdrawal penalty in basis points (e.g., 1000 = 10%)
     EmergencyPenalty,
+    /// Seconds between a withdrawal request and its claim (0 = instant)
+    WithdrawCooldown,
+    /// Mapping: (owner, goal_id) -> timestamp of a pending withdrawal request
+    WithdrawRequest(Address, u64),
+    /// Number of distinct addresses that have created at least one goal
+    UniqueUsers,
+    /// Tokens provisioned by the admin to fund interest payouts
+    Reserve,
+    /// Minimum annual rate in basis points guaranteed over a goal's lock term
+    GuaranteedMinRate,
+    /// Whether withdrawals fail when the reserve can't cover the guarantee
+    GuaranteeStrict,
+    /// Sum of principal across active goals
+    TotalPrincipal,
+    /// Sum of accrued (compounded) interest across active goals
+    TotalAccruedInterest,
+    /// Sum of (principal + accrued_interest) * interest_rate across active goals
+    TotalWeightedRate,
+    /// Number of active goals
+    ActiveGoalCount,
+    /// Admin-set thresholds used by `get_health`
+    HealthThresholds,
+    /// Mapping: (owner, goal_id) -> Drawdown schedule for a matured goal
+    Drawdown(Address, u64),
+    /// Mapping: goal_id -> owner, for paging over the global ID range
+    GoalOwner(u64),
+    /// Price oracle used for display quotes in a pegged unit
+    PriceOracle,
+    /// Maximum age in seconds of an oracle price before it's considered stale
+    OracleMaxAge,
+    /// Mapping: owner -> interest owed from principal-only withdrawals
+    Iou(Address),
+    /// Sum of all outstanding IOUs
+    TotalIou,
+    /// How `deposit` treats the unlock time of the goal being topped up
+    TopUpMode,
+    /// Mapping: token -> whether goals in that token are paused
+    TokenPaused(Address),
+    /// Mapping: supported token -> its cached `decimals()`
+    AllowedToken(Address),
+    /// Mapping: non-default token -> admin-funded interest reserve in that token
+    TokenReserve(Address),
+    /// Smallest amount accepted by `create_goal` or `deposit` (unset: any)
+    MinDeposit,
+    /// Largest amount accepted by `create_goal` or `deposit` (unset: any)
+    MaxDeposit,
+    /// Address receiving the operator share of emergency penalties (unset: admin)
+    Treasury,
+    /// Mapping: owner -> number of their goals that are still active
+    UserActiveGoalCount(Address),
+    /// Most active goals one address may hold at once (unset: unlimited)
+    MaxGoalsPerUser,
+    /// Mapping: (owner, goal_id) -> last `MAX_HISTORY_ENTRIES` actions
+    GoalHistory(Address, u64),
+    /// Mapping: owner -> timestamp of their last emergency withdrawal
+    LastEmergencyWithdraw(Address),
+    /// Seconds after an emergency withdrawal during which that address can't
+    /// open new goals (0 disables)
+    EmergencyCooldown,
+    /// Set while a guarded entry point is running
+    ReentrancyLock,
+    /// Mapping: owner -> total interest paid out to them on normal withdrawal
+    LifetimeInterest(Address),
+    /// Balance below which an abandoned goal may be force-closed (unset: 0)
+    DustThreshold,
+    /// Whether interest accrues past unlock time for goals without their own
+    /// setting (unset: true)
+    AccrueAfterUnlock,
+    /// Symbol prefixed to every event topic to tell deployments apart
+    EventNamespace,
+    /// Share of each emergency penalty (basis points) recycled into the reserve
+    PenaltyReserveShare,
+    /// Recycled penalty revenue held until `rebalance_reserve` moves it
+    PenaltyRevenue,
+    /// Total interest prepaid for active prepaid goals, kept out of the reserve
+    PrepaidReserve,
+    /// How the emergency penalty is computed (`PenaltyMode`)
+    PenaltyMode,
+    /// Sum of principal * unlock_time over active goals
+    TotalWeightedUnlock,
+    /// Address proposed as the next admin, pending its acceptance
+    PendingAdmin,
+    /// Whether new goals and deposits are halted contract-wide
+    Paused,
+    /// Seconds past unlock time before a beneficiary can claim a goal
+    BeneficiaryWindow,
+    /// Duration-tiered rate caps, sorted by ascending `min_duration`
+    RateTiers,
+}
+
+/// Storage keys added once `StorageKey` reached the contract spec's limit of
+/// 50 variants
+/// 
+/// Both enums encode as their variant name, so names here must not repeat
+/// any in `StorageKey`.
+#[contracttype]
+pub enum ExtendedKey {
+    /// Deployment-specific rate cap in basis points (unset: `MAX_INTEREST_RATE`)
+    MaxRate,
+    /// Rate applied to new goals created with a rate of 0 (unset: 0)
+    DefaultRate,
+    /// Most total principal the contract may hold across active goals
+    /// (unset: no cap)
+    GlobalCap,
+    /// Fee in basis points taken from each new goal's deposit (unset: 0)
+    CreationFeeBps,
+    /// Token new goals are paid interest in, instead of their own (unset:
+    /// interest in the goal's token)
+    RewardToken,
+    /// Whether goal creation is limited to whitelisted owners (unset: open)
+    WhitelistEnabled,
+    /// Address approved to create goals while the whitelist is enabled
+    Whitelisted(Address),
+    /// Most goals one address may create per ledger (unset: unlimited)
+    CreationRateLimit,
+    /// Ledger sequence of an address's latest goal creation and how many
+    /// goals it created in that ledger
+    CreationsInLedger(Address),
+    /// Where the operator share of emergency penalties goes (unset: treasury)
+    PenaltyDestination,
+    /// Recipient of burned penalties (unset: they stay in the contract)
+    BurnAddress,
+    /// Share of the withdrawn balance in basis points an emergency
+    /// withdrawal always returns (unset: 0)
+    MinReturnBps,
+    /// Set once an address creates its first goal; counted in `UniqueUsers`
+    HasSaved(Address),
+}
+
+/// How a goal's interest accrues between compounds
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CompoundMode {
+    /// Simple interest on the balance over the whole elapsed window
+    Linear,
+    /// Interest compounded once per `COMPOUND_PERIOD` within the window
+    Periodic,
+}
+
+/// How the emergency penalty applies over a goal's lock
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PenaltyMode {
+    /// The full penalty applies until unlock time
+    Flat,
+    /// The penalty falls linearly from the full rate at the start of the lock
+    /// to zero at unlock time
+    LinearDecay,
+}
+
+/// Effect of a top-up on a goal's unlock time
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TopUpMode {
+    /// Keep the original unlock time
+    KeepUnlock,
+    /// Move the unlock time to the principal-weighted average of the old
+    /// remaining lock and a fresh full term for the new funds
+    ExtendBlended,
+}
+
+/// Where the operator share of emergency penalties goes
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PenaltyDestination {
+    /// Paid to the treasury (default)
+    Treasury,
+    /// Credited to the interest reserve of the goal's token
+    Reserve,
+    /// Sent to the burn address, or left in the contract untracked
+    Burn,
+}
+
+/// Lifecycle stage used to filter `list_goals_by_status`
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum GoalStatus {
+    /// Active and before its unlock time
+    Locked,
+    /// Active and past its unlock time, not yet withdrawn
+    Matured,
+    /// No longer active: withdrawn, cancelled or merged away
+    Withdrawn,
+}
+
+/// Maximum rate allowed for goals locked at least `min_duration`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RateTier {
+    /// Minimum lock duration in seconds for this tier
+    pub min_duration: u64,
+    /// Maximum annual rate in basis points for this tier
+    pub max_rate: u32,
+}
+
+/// Complete contract configuration applied by `initialize_full`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FullConfig {
+    /// Default savings token
+    pub token: Address,
+    /// Address with administrative privileges
+    pub admin: Address,
+    /// Emergency withdrawal penalty in basis points
+    pub emergency_penalty: u32,
+    /// Share of each emergency penalty recycled into the reserve (basis points)
+    pub penalty_reserve_share_bps: u32,
+    /// Seconds between `request_withdraw` and `claim_withdraw` (0 disables)
+    pub withdraw_cooldown: u64,
+    /// Minimum annual rate in basis points topped up from the reserve
+    pub guaranteed_min_rate: u32,
+    /// Whether withdrawals fail when the reserve can't cover the guarantee
+    pub guarantee_strict: bool,
+    /// Thresholds used by `get_health`
+    pub health_thresholds: HealthThresholds,
+    /// Effect of a top-up on a goal's unlock time
+    pub top_up_mode: TopUpMode,
+    /// Symbol prefixed to every event topic
+    pub event_namespace: Symbol,
+    /// Additional supported tokens besides the default one
+    pub extra_tokens: Vec<Address>,
+    /// Duration-tiered rate caps (empty for no tiers)
+    pub rate_tiers: Vec<RateTier>,
+    /// Smallest accepted deposit amount (`None` for no floor)
+    pub min_deposit: Option<i128>,
+    /// Largest accepted deposit amount (`None` for no ceiling)
+    pub max_deposit: Option<i128>,
+    /// Destination of penalty revenue (`None` to pay the admin)
+    pub treasury: Option<Address>,
+    /// Most active goals per address (`None` for unlimited)
+    pub max_goals_per_user: Option<u32>,
+}
+
+/// Settings changed together by `update_config`; `None` leaves a setting as
+/// it is
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConfigUpdate {
+    /// Emergency withdrawal penalty in basis points
+    pub emergency_penalty: Option<u32>,
+    /// Smallest accepted deposit amount
+    pub min_deposit: Option<i128>,
+    /// Largest accepted deposit amount
+    pub max_deposit: Option<i128>,
+    /// Deployment-specific rate cap in basis points
+    pub max_rate: Option<u32>,
+}
+
+/// Snapshot of the contract's effective settings returned by `get_config`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Config {
+    /// Default savings token
+    pub token: Address,
+    /// Address with administrative privileges
+    pub admin: Address,
+    /// Address receiving penalty revenue (the admin unless a treasury is set)
+    pub treasury: Address,
+    /// Emergency withdrawal penalty in basis points
+    pub emergency_penalty: u32,
+    /// Smallest accepted deposit amount (`None` for no floor)
+    pub min_deposit: Option<i128>,
+    /// Largest accepted deposit amount (`None` for no ceiling)
+    pub max_deposit: Option<i128>,
+    /// Highest rate new goals may use, in basis points
+    pub max_rate: u32,
+    /// Rate applied when a goal is created with rate 0, in basis points
+    pub default_rate: u32,
+    /// Cap on the total principal of active goals (`None` for no cap)
+    pub global_cap: Option<i128>,
+    /// Fee taken from each new goal's deposit, in basis points
+    pub creation_fee_bps: u32,
+    /// Most active goals per address (`None` for unlimited)
+    pub max_goals_per_user: Option<u32>,
+    /// Seconds between `request_withdraw` and `claim_withdraw` (0 disables)
+    pub withdraw_cooldown: u64,
+    /// Whether the contract is paused
+    pub paused: bool,
+}
+
+/// Price reported by the oracle
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PriceData {
+    /// Price of one token unit in the pegged unit, scaled by `10^decimals()`
+    pub price: i128,
+    /// Timestamp the price was published at
+    pub timestamp: u64,
+}
+
+/// Interface expected from the configured price oracle
+#[contractclient(name = "PriceOracleClient")]
+pub trait PriceOracle {
+    /// Latest price for `asset`, if any
+    fn lastprice(env: Env, asset: Address) -> Option<PriceData>;
+    /// Number of decimals prices are scaled by
+    fn decimals(env: Env) -> u32;
+}
+
+/// Kind of action recorded in a goal's history
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum GoalAction {
+    /// Goal opened with its initial principal
+    Created,
+    /// Top-up via `deposit`
+    Deposit,
+    /// Interest credited to the goal
+    Compound,
+    /// Funds paid out of the goal, once per recipient
+    Withdrawal,
+}
+
+/// One entry of a goal's bounded activity history
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HistoryEntry {
+    /// What happened to the goal
+    pub action: GoalAction,
+    /// Token amount the action moved
+    pub amount: i128,
+    /// Ledger timestamp at which the action was recorded
+    pub timestamp: u64,
+}
+
+/// Installment schedule for releasing a matured goal over time
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Drawdown {
+    /// Amount released per installment
+    pub installment: i128,
+    /// Seconds between installments
+    pub interval: u64,
+    /// Earliest timestamp at which the next installment can be claimed
+    pub next_installment_time: u64,
+}
+
+/// Overall status reported by `get_health`
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum HealthStatus {
+    Healthy,
+    Warning,
+    Critical,
+}
+
+/// Thresholds below which `get_health` reports `Warning` or `Critical`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HealthThresholds {
+    /// Solvency ratio in basis points below which status is `Warning`
+    pub warning_solvency_bps: u32,
+    /// Solvency ratio in basis points below which status is `Critical`
+    pub critical_solvency_bps: u32,
+    /// Reserve runway in seconds below which status is `Warning`
+    pub warning_runway: u64,
+    /// Reserve runway in seconds below which status is `Critical`
+    pub critical_runway: u64,
+}
+
+/// Composite health indicator returned by `get_health`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HealthReport {
+    /// Contract token balance / obligations in basis points (`u32::MAX` with no obligations)
+    pub solvency_bps: u32,
+    /// Seconds of projected interest the reserve covers (`u64::MAX` if nothing accrues)
+    pub runway: u64,
+    /// Number of active goals
+    pub active_goals: u64,
+    /// Status derived from the configured thresholds
+    pub status: HealthStatus,
 }
 
 /// Minimum lock duration: 1 day in seconds
@@ -82,482 +582,10406 @@ const BASIS_POINTS: i128 = 10000;
 /// Seconds in a year for interest calculation
 const SECONDS_PER_YEAR: i128 = 31536000;
 
-#[contract]
-pub struct TimeLockedSavings;
+/// Interface version reported by `version`; bump whenever the storage
+/// layout or the semantics of a public function change
+const CONTRACT_VERSION: u32 = 1;
 
-#[contractimpl]
-impl TimeLockedSavings {
-    /// Initialize the contract with token address and admin
-    /// 
-    /// # Security:
-    /// - Can only be called once (initialization pattern)
-    /// - Sets up admin privileges for contract management
-    /// 
-    /// # Parameters:
-    /// - `token`: Address of the token to be used for savings
-    /// - `admin`: Address with administrative privileges
-    /// - `emergency_penalty`: Penalty in basis points for early withdrawal (e.g., 1000 = 10%)
-    pub fn initialize(
-        env: Env,
-        token: Address,
-        admin: Address,
-        emergency_penalty: u32,
-    ) -> Result<(), Error> {
-        // Security: Prevent re-initialization
-        if env.storage().instance().has(&StorageKey::Token) {
-            return Err(Error::AlreadyInitialized);
-        }
+/// Event namespace used until the admin configures one
+const DEFAULT_EVENT_NAMESPACE: Symbol = symbol_short!("savings");
 
-        // Security: Validate penalty rate
-        if emergency_penalty > 5000 {
-            // Max 50%
-            return Err(Error::PenaltyTooHigh);
-        }
+/// Length of one compounding period in `CompoundMode::Periodic` (1 day)
+const COMPOUND_PERIOD: u64 = 86400;
 
-        // Store contract configuration
-        env.storage().instance().set(&StorageKey::Token, &token);
-        env.storage().instance().set(&StorageKey::Admin, &admin);
-        env.storage()
-            .instance()
-            .set(&StorageKey::EmergencyPenalty, &emergency_penalty);
-        env.storage().instance().set(&StorageKey::GoalCounter, &0u64);
+/// Maximum periods compounded per call; any time beyond accrues linearly
+const MAX_COMPOUND_PERIODS: u64 = 3660;
 
-        Ok(())
-    }
+/// Fixed-point scale for periodic compounding so per-period interest isn't
+/// truncated to whole token units
+const COMPOUND_SCALE: i128 = 1_000_000;
 
-    /// Create a new savings goal with time-lock
-    /// 
-    /// # Security:
-    /// - Validates all inputs before state changes
-    /// - Uses authorization to ensure only owner can create goals
-    /// - Atomic operation - either fully succeeds or reverts
-    /// - Protects against overflow in calculations
-    /// 
-    /// # Parameters:
-    /// - `owner`: Address of the goal owner (must authorize)
-    /// - `amount`: Amount to deposit
-    /// - `lock_duration`: How long funds are locked (in seconds)
-    /// - `interest_rate`: Annual interest rate in basis points
-    pub fn create_goal(
-        env: Env,
-        owner: Address,
-        amount: i128,
-        lock_duration: u64,
-        interest_rate: u32,
-    ) -> Result<u64, Error> {
-        // Security: Require authorization from the owner
-        owner.require_auth();
+/// Default seconds past unlock time before a beneficiary can claim (180 days)
+const DEFAULT_BENEFICIARY_WINDOW: u64 = 15552000;
 
-        // Security: Validate inputs
-        if amount <= 0 {
-            return Err(Error::InvalidAmount);
-        }
+/// Maximum length of a goal label in bytes
+const MAX_LABEL_LEN: u32 = 32;
 
-        if lock_duration < MIN_LOCK_DURATION || lock_duration > MAX_LOCK_DURATION {
-            return Err(Error::InvalidDuration);
-        }
+/// Maximum number of recipients in a `withdraw_split` payout
+const MAX_SPLIT_RECIPIENTS: u32 = 10;
 
-        if interest_rate > MAX_INTEREST_RATE {
-            return Err(Error::RateTooHigh);
-        }
+/// Window after a goal's start during which `cancel_goal` may undo it: 1 hour
+const CANCEL_GRACE_PERIOD: u64 = 3600;
 
-        // Get current timestamp
-        let current_time = env.ledger().timestamp();
+/// Approximate seconds per ledger, used to convert lock time into TTL ledgers
+const LEDGER_SECONDS: u64 = 5;
 
-        // Security: Check for overflow when calculating unlock time
-        let unlock_time = current_time
-            .checked_add(lock_duration)
-            .ok_or(Error::Overflow)?;
+/// TTL kept on goal entries beyond their unlock time: ~30 days of ledgers
+const GOAL_TTL_BUFFER: u32 = 518400;
 
-        // Transfer tokens from user to contract
-        // Security: This will fail if user has insufficient balance
-        let token_address: Address = env
-            .storage()
-            .instance()
-            .get(&StorageKey::Token)
-            .ok_or(Error::NotInitialized)?;
-        let token = token::Client::new(&env, &token_address);
-        token.transfer(&owner, &env.current_contract_address(), &amount);
+/// Number of most recent actions kept in each goal's history
+const MAX_HISTORY_ENTRIES: u32 = 20;
 
-        // Generate unique goal ID
-        let goal_id: u64 = env
-            .storage()
-            .instance()
-            .get(&StorageKey::GoalCounter)
-            .unwrap_or(0);
+/// Maximum number of goals opened by a single `create_goals_batch` call
+const MAX_BATCH_GOALS: u32 = 10;
 
-        // Security: Check for goal ID overflow
-        let next_goal_id = goal_id
-            .checked_add(1)
-            .ok_or(Error::GoalOverflow)?;
-
-        // Create the savings goal
-        let goal = SavingsGoal {
-            owner: owner.clone(),
-            principal: amount,
-            interest_rate,
-            start_time: current_time,
-            lock_duration,
-            unlock_time,
-            accrued_interest: 0,
-            last_compound_time: current_time,
-            is_active: true,
-        };
+/// Maximum creation fee in basis points (5%)
+const MAX_CREATION_FEE_BPS: u32 = 500;
 
-        // Store the goal
-        env.storage()
-            .persistent()
-            .set(&StorageKey::Goal(owner.clone(), goal_id), &goal);
+/// Maximum number of goals compounded by a single `compound_all` call
+const MAX_COMPOUND_ALL: u32 = 25;
 
-        // Update counters
-        env.storage()
-            .instance()
-            .set(&StorageKey::GoalCounter, &next_goal_id);
+/// Time past unlock after which a dust goal counts as abandoned: 180 days
+const DUST_GRACE_PERIOD: u64 = 15552000;
 
-        let user_count: u64 = env
-            .storage()
-            .persistent()
-            .get(&StorageKey::UserGoalCount(owner.clone()))
-            .unwrap_or(0);
-        env.storage()
-            .persistent()
-            .set(&StorageKey::UserGoalCount(owner), &(user_count + 1));
+/// Maximum number of goal IDs scanned by a single paged query
+const MAX_PAGE_LIMIT: u32 = 100;
 
-        Ok(goal_id)
-    }
+/// Default reserve runway below which health is `Warning`: 30 days
+const DEFAULT_WARNING_RUNWAY: u64 = 2592000;
 
-    /// Compound interest for a specific goal
-    /// 
-    /// # Security:
-    /// - Only calculates interest, doesn't modify principal
-    /// - Uses safe math to prevent overflow
-    /// - Can be called by anyone (public utility function)
-    /// 
-    /// # Parameters:
-    /// - `owner`: Address of the goal owner
-    /// - `goal_id`: ID of the goal to compound
-    pub fn compound_interest(env: Env, owner: Address, goal_id: u64) -> Result<(), Error> {
-        let mut goal: SavingsGoal = env
-            .storage()
-            .persistent()
-            .get(&StorageKey::Goal(owner.clone(), goal_id))
-            .ok_or(Error::GoalNotFound)?;
+/// Default reserve runway below which health is `Critical`: 7 days
+const DEFAULT_CRITICAL_RUNWAY: u64 = 604800;
 
-        // Security: Check if goal is active
-        if !goal.is_active {
-            return Err(Error::GoalInactive);
-        }
+/// Simple interest on `balance` at `rate` basis points per year over `elapsed` seconds
+/// 
+/// Computes `balance * rate * elapsed / (SECONDS_PER_YEAR * BASIS_POINTS)` with
+/// checked arithmetic. The time step is split around the divisor so it never
+/// forms the full triple product: with `balance * rate = q * D + r`, the result
+/// is `q * elapsed + r * elapsed / D`, which is exact and only overflows when
+/// the interest itself doesn't fit in an `i128`.
+/// 
+/// Rounding policy: the division truncates toward zero, in the contract's
+/// favor, so each compounding forfeits less than one unit of interest. The
+/// time it covers is still consumed, so a goal compounded more often than
+/// it earns a whole unit accrues nothing for that time.
+fn calculate_interest(balance: i128, rate: u32, elapsed: u64) -> Result<i128, Error> {
+    calculate_interest_traced(balance, rate, elapsed).map_err(|(error, _)| error)
+}
 
-        let current_time = env.ledger().timestamp();
+/// `calculate_interest`, also reporting which step failed
+fn calculate_interest_traced(
+    balance: i128,
+    rate: u32,
+    elapsed: u64,
+) -> Result<i128, (Error, ErrorContext)> {
+    let divisor = SECONDS_PER_YEAR * BASIS_POINTS;
+    let yearly = balance
+        .checked_mul(rate as i128)
+        .ok_or((Error::Overflow, ErrorContext::InterestRateMul))?;
+    let quotient = yearly
+        .checked_div(divisor)
+        .ok_or((Error::DivisionError, ErrorContext::InterestDiv))?;
+    let remainder = yearly
+        .checked_rem(divisor)
+        .ok_or((Error::DivisionError, ErrorContext::InterestDiv))?;
 
-        // Calculate time elapsed since last compound
-        let time_elapsed = current_time
-            .checked_sub(goal.last_compound_time)
-            .ok_or(Error::TimeError)?;
+    // `remainder < divisor`, so `remainder * elapsed` fits for any `u64` time
+    let whole = quotient
+        .checked_mul(elapsed as i128)
+        .ok_or((Error::Overflow, ErrorContext::InterestTimeMul))?;
+    let fraction = remainder
+        .checked_mul(elapsed as i128)
+        .ok_or((Error::Overflow, ErrorContext::InterestTimeMul))?
+        .checked_div(divisor)
+        .ok_or((Error::DivisionError, ErrorContext::InterestDiv))?;
 
-        if time_elapsed == 0 {
-            return Ok(()); // No time passed, nothing to compound
-        }
+    whole
+        .checked_add(fraction)
+        .ok_or((Error::Overflow, ErrorContext::InterestTimeMul))
+}
 
-        // Calculate interest: (principal + accrued) * rate * time / (SECONDS_PER_YEAR * BASIS_POINTS)
-        // Security: Use checked arithmetic to prevent overflow
-        let total_balance = goal
-            .principal
-            .checked_add(goal.accrued_interest)
-            .ok_or(Error::Overflow)?;
+/// Interest on `balance` at `rate` compounded every `COMPOUND_PERIOD` over
+/// `elapsed` seconds
+/// 
+/// Iterates over at most `MAX_COMPOUND_PERIODS` whole periods to bound cost;
+/// the leftover time accrues linearly on the compounded balance.
+fn calculate_compound_interest(balance: i128, rate: u32, elapsed: u64) -> Result<i128, Error> {
+    let periods = (elapsed / COMPOUND_PERIOD).min(MAX_COMPOUND_PERIODS);
+    let remainder = elapsed - periods * COMPOUND_PERIOD;
 
-        let interest = total_balance
-            .checked_mul(goal.interest_rate as i128)
-            .ok_or(Error::Overflow)?
-            .checked_mul(time_elapsed as i128)
-            .ok_or(Error::Overflow)?
-            .checked_div(SECONDS_PER_YEAR * BASIS_POINTS)
-            .ok_or(Error::DivisionError)?;
+    let mut scaled = balance.checked_mul(COMPOUND_SCALE).ok_or(Error::Overflow)?;
+    for _ in 0..periods {
+        let interest = calculate_interest(scaled, rate, COMPOUND_PERIOD)?;
+        scaled = scaled.checked_add(interest).ok_or(Error::Overflow)?;
+    }
+    let interest = calculate_interest(scaled, rate, remainder)?;
+    scaled = scaled.checked_add(interest).ok_or(Error::Overflow)?;
 
-        // Update accrued interest
-        goal.accrued_interest = goal
-            .accrued_interest
-            .checked_add(interest)
-            .ok_or(Error::Overflow)?;
+    (scaled / COMPOUND_SCALE)
+        .checked_sub(balance)
+        .ok_or(Error::Underflow)
+}
 
-        goal.last_compound_time = current_time;
+/// Interest a goal earns on `balance` over `elapsed` seconds in its compound mode
+/// 
+/// Zero-rate goals are pure time-locks and skip the math entirely, so
+/// `compound_interest` only advances their `last_compound_time` and
+/// `get_current_balance` is exactly their principal plus accrued interest.
+fn goal_interest(goal: &SavingsGoal, balance: i128, elapsed: u64) -> Result<i128, Error> {
+    if goal.interest_rate == 0 {
+        return Ok(0);
+    }
 
-        // Save updated goal
-        env.storage()
-            .persistent()
-            .set(&StorageKey::Goal(owner, goal_id), &goal);
+    match goal.compound_mode {
+        CompoundMode::Linear => calculate_interest(balance, goal.interest_rate, elapsed),
+        CompoundMode::Periodic => {
+            calculate_compound_interest(balance, goal.interest_rate, elapsed)
+        }
+    }
+}
 
-        Ok(())
+/// Add `delta` to an i128 aggregate in instance storage
+/// 
+/// Security: Aggregates can never go negative.
+fn adjust_total(env: &Env, key: &StorageKey, delta: i128) -> Result<(), Error> {
+    if delta == 0 {
+        return Ok(());
     }
 
-    /// Withdraw funds from a matured goal
-    /// 
-    /// # Security:
-    /// - Requires owner authorization
-    /// - Checks unlock time before allowing withdrawal
-    /// - Compounds interest before withdrawal
-    /// - Marks goal as inactive to prevent double withdrawal
-    /// - Uses checked arithmetic
-    /// 
-    /// # Parameters:
-    /// - `owner`: Address of the goal owner
-    /// - `goal_id`: ID of the goal to withdraw from
-    pub fn withdraw(env: Env, owner: Address, goal_id: u64) -> Result<i128, Error> {
-        // Security: Require authorization
-        owner.require_auth();
+    let total: i128 = env.storage().instance().get(key).unwrap_or(0);
+    let new_total = total.checked_add(delta).ok_or(Error::Overflow)?;
+    if new_total < 0 {
+        return Err(Error::Underflow);
+    }
 
-        // Compound interest before withdrawal
-        Self::compound_interest(env.clone(), owner.clone(), goal_id)?;
+    env.storage().instance().set(key, &new_total);
 
-        let mut goal: SavingsGoal = env
-            .storage()
-            .persistent()
-            .get(&StorageKey::Goal(owner.clone(), goal_id))
-            .ok_or(Error::GoalNotFound)?;
+    Ok(())
+}
 
-        // Security: Check if goal is active
-        if !goal.is_active {
-            return Err(Error::AlreadyWithdrawn);
-        }
+/// A goal's contribution to the aggregates:
+/// (principal, accrued, weighted rate, principal-weighted unlock time, active)
+fn goal_contribution(goal: &SavingsGoal) -> Result<(i128, i128, i128, i128, u64), Error> {
+    if !goal.is_active {
+        return Ok((0, 0, 0, 0, 0));
+    }
 
-        let current_time = env.ledger().timestamp();
+    let weighted_rate = goal
+        .principal
+        .checked_add(goal.accrued_interest)
+        .ok_or(Error::Overflow)?
+        .checked_mul(goal.interest_rate as i128)
+        .ok_or(Error::Overflow)?;
+    let weighted_unlock = goal
+        .principal
+        .checked_mul(goal.unlock_time as i128)
+        .ok_or(Error::Overflow)?;
 
-        // Security: Ensure lock period has passed
-        if current_time < goal.unlock_time {
-            return Err(Error::StillLocked);
-        }
+    Ok((goal.principal, goal.accrued_interest, weighted_rate, weighted_unlock, 1))
+}
 
-        // Calculate total withdrawal amount
-        let total_amount = goal
-            .principal
-            .checked_add(goal.accrued_interest)
-            .ok_or(Error::Overflow)?;
+/// Write a goal and extend its storage TTL
+/// 
+/// Every goal write goes through here so active goals can't be archived
+/// before they mature: the entry (with its `GoalOwner` index and history) is
+/// kept alive until unlock time plus `GOAL_TTL_BUFFER`, capped at the
+/// network's maximum TTL, and the instance storage with it. The TTL is only
+/// extended once it has fallen below half that target, so repeated writes
+/// stay cheap.
+fn save_goal(env: &Env, key: &StorageKey, goal: &SavingsGoal) {
+    let storage = env.storage().persistent();
+    storage.set(key, goal);
 
-        // Security: Mark goal as inactive before transfer to prevent reentrancy
-        goal.is_active = false;
-        env.storage()
-            .persistent()
-            .set(&StorageKey::Goal(owner.clone(), goal_id), &goal);
+    let lock_ledgers = goal.unlock_time.saturating_sub(env.ledger().timestamp()) / LEDGER_SECONDS;
+    let extend_to = (lock_ledgers.min(u32::MAX as u64) as u32)
+        .saturating_add(GOAL_TTL_BUFFER)
+        .min(env.storage().max_ttl());
+    let threshold = extend_to / 2;
 
-        // Transfer funds to owner
-        let token_address: Address = env
-            .storage()
-            .instance()
-            .get(&StorageKey::Token)
-            .ok_or(Error::NotInitialized)?;
-        let token = token::Client::new(&env, &token_address);
-        token.transfer(&env.current_contract_address(), &owner, &total_amount);
+    storage.extend_ttl(key, threshold, extend_to);
+    if let StorageKey::Goal(owner, goal_id) = key {
+        let owner_key = StorageKey::GoalOwner(*goal_id);
+        let history_key = StorageKey::GoalHistory(owner.clone(), *goal_id);
+        for related in [owner_key, history_key] {
+            if storage.has(&related) {
+                storage.extend_ttl(&related, threshold, extend_to);
+            }
+        }
+    }
+    env.storage().instance().extend_ttl(threshold, extend_to);
+}
 
-        Ok(total_amount)
+/// Keep the protocol-wide aggregates in sync with a goal write
+/// 
+/// Must be called whenever a goal's principal, interest, unlock time or
+/// active flag changes; `before` is `None` for newly created goals. Rate
+/// changes go through `renegotiate_rate` or `reduce_rate` instead.
+fn track_goal_change(
+    env: &Env,
+    before: Option<&SavingsGoal>,
+    after: &SavingsGoal,
+) -> Result<(), Error> {
+    if let Some(before) = before {
+        ensure_rate_unchanged(before, after);
     }
 
-    /// Emergency withdrawal with penalty before unlock time
-    /// 
-    /// # Security:
-    /// - Requires owner authorization
-    /// - Applies penalty to discourage misuse
+    sync_goal_aggregates(env, before, after)
+}
+
+/// Invariant for every goal write: a goal's `interest_rate` is fixed once
+/// created, and only `renegotiate_rate` (or `reduce_rate`, which can only
+/// lower it) may change it
+/// 
+/// Panics rather than returning an error, since a violation is a bug in the
+/// contract rather than bad input.
+fn ensure_rate_unchanged(before: &SavingsGoal, after: &SavingsGoal) {
+    assert!(
+        before.interest_rate == after.interest_rate,
+        "interest rate changed outside renegotiate_rate"
+    );
+}
+
+/// Set a new rate on a goal the owner has consented to change (e.g. when
+/// re-locking it for a new term), re-validating it like a new goal's rate
+/// against the goal's lock duration and updating the aggregates
+/// 
+/// `before` is the goal as last stored; other pending changes to `goal` are
+/// tracked along with the rate.
+fn renegotiate_rate(
+    env: &Env,
+    before: &SavingsGoal,
+    goal: &mut SavingsGoal,
+    new_rate: u32,
+) -> Result<(), Error> {
+    ensure_rate_allowed(env, goal.lock_duration, new_rate)?;
+    goal.interest_rate = new_rate;
+
+    sync_goal_aggregates(env, Some(before), goal)
+}
+
+/// Lower a goal's rate without the owner's consent, failing with
+/// `RateTooHigh` unless `new_rate` is strictly below the current one, and
+/// update the aggregates like `renegotiate_rate`
+/// 
+/// A cut never needs the rate policy re-checked, since it only reduces what
+/// the goal earns.
+fn reduce_rate(
+    env: &Env,
+    before: &SavingsGoal,
+    goal: &mut SavingsGoal,
+    new_rate: u32,
+) -> Result<(), Error> {
+    if new_rate >= goal.interest_rate {
+        return Err(Error::RateTooHigh);
+    }
+    goal.interest_rate = new_rate;
+
+    sync_goal_aggregates(env, Some(before), goal)
+}
+
+/// Apply the difference between two versions of a goal to the aggregates
+fn sync_goal_aggregates(
+    env: &Env,
+    before: Option<&SavingsGoal>,
+    after: &SavingsGoal,
+) -> Result<(), Error> {
+    let (old_principal, old_accrued, old_weighted, old_unlock, old_active) = match before {
+        Some(goal) => goal_contribution(goal)?,
+        None => (0, 0, 0, 0, 0),
+    };
+    let (new_principal, new_accrued, new_weighted, new_unlock, new_active) =
+        goal_contribution(after)?;
+
+    adjust_total(env, &StorageKey::TotalPrincipal, new_principal - old_principal)?;
+    adjust_total(env, &StorageKey::TotalAccruedInterest, new_accrued - old_accrued)?;
+    adjust_total(env, &StorageKey::TotalWeightedRate, new_weighted - old_weighted)?;
+    adjust_total(env, &StorageKey::TotalWeightedUnlock, new_unlock - old_unlock)?;
+
+    if new_active != old_active {
+        let active_goals: u64 = env
+            .storage()
+            .instance()
+            .get(&StorageKey::ActiveGoalCount)
+            .unwrap_or(0);
+        let active_goals = (active_goals + new_active)
+            .checked_sub(old_active)
+            .ok_or(Error::Underflow)?;
+        env.storage()
+            .instance()
+            .set(&StorageKey::ActiveGoalCount, &active_goals);
+
+        adjust_user_active_goals(env, &after.owner, new_active > old_active)?;
+    }
+
+    Ok(())
+}
+
+/// Count one goal of `owner` becoming active (`activated`) or inactive
+fn adjust_user_active_goals(env: &Env, owner: &Address, activated: bool) -> Result<(), Error> {
+    let key = StorageKey::UserActiveGoalCount(owner.clone());
+    let count: u32 = env.storage().persistent().get(&key).unwrap_or(0);
+    let count = if activated {
+        count.checked_add(1).ok_or(Error::Overflow)?
+    } else {
+        count.checked_sub(1).ok_or(Error::Underflow)?
+    };
+    env.storage().persistent().set(&key, &count);
+
+    Ok(())
+}
+
+/// Remove `amount` from a goal's balance, taking accrued interest first and
+/// then principal
+fn deduct_from_balance(goal: &mut SavingsGoal, amount: i128) -> Result<(), Error> {
+    let from_interest = amount.min(goal.accrued_interest);
+    let from_principal = amount
+        .checked_sub(from_interest)
+        .ok_or(Error::Underflow)?;
+
+    if from_principal > goal.principal {
+        return Err(Error::InvalidAmount);
+    }
+
+    goal.accrued_interest -= from_interest;
+    goal.principal -= from_principal;
+
+    Ok(())
+}
+
+/// Average annual rate in basis points the reserve can fund for one year
+/// across all active balances, capped at `MAX_INTEREST_RATE`
+fn sustainable_rate(env: &Env) -> Result<u32, Error> {
+    let total_principal: i128 = env
+        .storage()
+        .instance()
+        .get(&StorageKey::TotalPrincipal)
+        .unwrap_or(0);
+    let total_accrued: i128 = env
+        .storage()
+        .instance()
+        .get(&StorageKey::TotalAccruedInterest)
+        .unwrap_or(0);
+    let reserve: i128 = env
+        .storage()
+        .instance()
+        .get(&StorageKey::Reserve)
+        .unwrap_or(0);
+
+    let tvl = total_principal
+        .checked_add(total_accrued)
+        .ok_or(Error::Overflow)?;
+    if tvl == 0 {
+        return Ok(MAX_INTEREST_RATE);
+    }
+
+    let rate = reserve
+        .checked_mul(BASIS_POINTS)
+        .map_or(MAX_INTEREST_RATE as i128, |scaled| scaled / tvl);
+
+    Ok(rate.min(MAX_INTEREST_RATE as i128) as u32)
+}
+
+/// Global goal IDs covered by a page starting at `start_id`, bounded by the
+/// goal counter and `MAX_PAGE_LIMIT`
+fn page_range(env: &Env, start_id: u64, limit: u32) -> core::ops::Range<u64> {
+    let goal_count: u64 = env
+        .storage()
+        .instance()
+        .get(&StorageKey::GoalCounter)
+        .unwrap_or(0);
+    let end = start_id
+        .saturating_add(limit.min(MAX_PAGE_LIMIT) as u64)
+        .min(goal_count);
+
+    start_id..end.max(start_id)
+}
+
+/// Look up a goal by its global ID, returning `None` for missing IDs
+fn load_indexed_goal(env: &Env, goal_id: u64) -> Option<(Address, SavingsGoal)> {
+    let owner: Address = env
+        .storage()
+        .persistent()
+        .get(&StorageKey::GoalOwner(goal_id))?;
+    let goal: SavingsGoal = env
+        .storage()
+        .persistent()
+        .get(&StorageKey::Goal(owner.clone(), goal_id))?;
+
+    Some((owner, goal))
+}
+
+/// Seconds since the last compounding that earn interest: all of them, or
+/// only those up to unlock time when the goal stops accruing at maturity,
+/// none while the goal is frozen, and none before its accrual cliff at
+/// `start_time + accrual_start_offset`
+fn accruing_seconds(env: &Env, goal: &SavingsGoal) -> Result<u64, Error> {
+    if goal.frozen {
+        return Ok(0);
+    }
+
+    let accrues = goal.accrue_after_unlock.unwrap_or_else(|| {
+        env.storage()
+            .instance()
+            .get(&StorageKey::AccrueAfterUnlock)
+            .unwrap_or(true)
+    });
+    let current_time = env.ledger().timestamp();
+    let end = if accrues {
+        current_time
+    } else {
+        current_time.min(goal.unlock_time.max(goal.last_compound_time))
+    };
+    let elapsed = end
+        .checked_sub(goal.last_compound_time)
+        .ok_or(Error::TimeError)?;
+
+    let cliff = goal.start_time.saturating_add(goal.accrual_start_offset);
+    Ok(elapsed.min(end.saturating_sub(cliff)))
+}
+
+/// Interest earned by `goal` since its last compounding, not yet in storage
+fn pending_interest(env: &Env, goal: &SavingsGoal) -> Result<i128, Error> {
+    let time_elapsed = accruing_seconds(env, goal)?;
+    let total_balance = goal
+        .principal
+        .checked_add(goal.accrued_interest)
+        .ok_or(Error::Overflow)?;
+
+    goal_interest(goal, total_balance, time_elapsed)
+}
+
+/// Invariant for every payout path: all interest must already be realized
+/// into `accrued_interest` by compounding up to now, so payouts only ever add
+/// `principal + accrued_interest` and never compute pending interest
+/// themselves (which would count it twice)
+fn ensure_interest_realized(env: &Env, goal: &SavingsGoal) -> Result<(), Error> {
+    if goal.last_compound_time != env.ledger().timestamp() {
+        return Err(Error::InterestNotRealized);
+    }
+
+    Ok(())
+}
+
+/// Publish an event with the deployment's namespace prepended to `topics`
+/// 
+/// Topics are `(namespace, name, ...)`; with Soroban's four-topic limit each
+/// event has room for the name plus two more topics.
+/// 
+/// Event names are part of the public interface and must stay stable:
+/// - `created` `(owner, goal_id)`: `(principal, interest_rate, unlock_time)`
+/// - `withdrawn` `(owner, goal_id)`: `(amount, penalty_applied, recipient)`,
+///   one per recipient for split payouts
+/// - `partial` `(owner, goal_id)`: `(amount, remaining_balance)`
+/// - `extended` `(owner, goal_id)`: new unlock time
+/// - `cancelled` `(owner, goal_id)`: refunded principal
+/// - `transfer` `(from, goal_id)`: new owner
+/// - `compound` `(owner, goal_id)`: `(interest, accrued_interest)`, only when
+///   interest was credited
+/// - `rate_warn` `(owner, goal_id)`: `(interest_rate, sustainable_rate)`
+/// - `rebalance`: moved amount
+/// - `admin` `(old_admin, new_admin)`: `()`
+/// - `upgraded`: new WASM hash
+fn publish_event<T, D>(env: &Env, topics: T, data: D)
+where
+    T: IntoVal<Env, Vec<Val>>,
+    D: IntoVal<Env, Val>,
+{
+    let namespace: Symbol = env
+        .storage()
+        .instance()
+        .get(&StorageKey::EventNamespace)
+        .unwrap_or(DEFAULT_EVENT_NAMESPACE);
+
+    let mut prefixed: Vec<Val> = Vec::from_array(env, [namespace.into_val(env)]);
+    prefixed.append(&topics.into_val(env));
+
+    env.events().publish(prefixed, data);
+}
+
+/// Query a token's decimals, rejecting tokens whose `decimals()` call fails
+/// and the contract's own address
+fn fetch_token_decimals(env: &Env, token: &Address) -> Result<u32, Error> {
+    if *token == env.current_contract_address() {
+        return Err(Error::InvalidToken);
+    }
+
+    match token::Client::new(env, token).try_decimals() {
+        Ok(Ok(decimals)) => Ok(decimals),
+        _ => Err(Error::InvalidToken),
+    }
+}
+
+/// Reject the contract's own address as an admin or payout recipient, where
+/// transfers to itself would strand penalty revenue and lock out
+/// administration
+fn ensure_not_self(env: &Env, address: &Address) -> Result<(), Error> {
+    if *address == env.current_contract_address() {
+        return Err(Error::Unauthorized);
+    }
+
+    Ok(())
+}
+
+/// Reject operations on goals in a token paused by the admin
+fn ensure_token_active(env: &Env, token: &Address) -> Result<(), Error> {
+    let paused: bool = env
+        .storage()
+        .instance()
+        .get(&StorageKey::TokenPaused(token.clone()))
+        .unwrap_or(false);
+
+    if paused {
+        return Err(Error::TokenPaused);
+    }
+
+    Ok(())
+}
+
+/// Reject new funds while the admin has paused the contract; exits stay open
+fn ensure_not_paused(env: &Env) -> Result<(), Error> {
+    let paused: bool = env
+        .storage()
+        .instance()
+        .get(&StorageKey::Paused)
+        .unwrap_or(false);
+
+    if paused {
+        return Err(Error::Paused);
+    }
+
+    Ok(())
+}
+
+/// Remove the pending withdrawal request and drawdown schedule of a goal
+/// that is being closed
+fn clear_pending_schedules(env: &Env, owner: &Address, goal_id: u64) {
+    env.storage()
+        .persistent()
+        .remove(&StorageKey::WithdrawRequest(owner.clone(), goal_id));
+    env.storage()
+        .persistent()
+        .remove(&StorageKey::Drawdown(owner.clone(), goal_id));
+}
+
+/// Load a goal for its beneficiary: `beneficiary` must be the designated
+/// one (`Unauthorized`) and the beneficiary window past unlock must have
+/// elapsed (`StillLocked`)
+fn beneficiary_goal(
+    env: &Env,
+    beneficiary: &Address,
+    owner: &Address,
+    goal_id: u64,
+) -> Result<SavingsGoal, Error> {
+    let goal: SavingsGoal = env
+        .storage()
+        .persistent()
+        .get(&StorageKey::Goal(owner.clone(), goal_id))
+        .ok_or(Error::GoalNotFound)?;
+
+    if goal.beneficiary != Some(beneficiary.clone()) {
+        return Err(Error::Unauthorized);
+    }
+
+    let window: u64 = env
+        .storage()
+        .instance()
+        .get(&StorageKey::BeneficiaryWindow)
+        .unwrap_or(DEFAULT_BENEFICIARY_WINDOW);
+    let claimable_at = goal
+        .unlock_time
+        .checked_add(window)
+        .ok_or(Error::Overflow)?;
+    if env.ledger().timestamp() < claimable_at {
+        return Err(Error::StillLocked);
+    }
+
+    Ok(goal)
+}
+
+/// Enforce the withdrawal cooldown for exits other than `withdraw` and
+/// `claim_withdraw`: with a cooldown configured, a request must be pending
+/// and its cooldown elapsed
+fn ensure_cooldown_elapsed(env: &Env, owner: &Address, goal_id: u64) -> Result<(), Error> {
+    let cooldown: u64 = env
+        .storage()
+        .instance()
+        .get(&StorageKey::WithdrawCooldown)
+        .unwrap_or(0);
+    if cooldown == 0 {
+        return Ok(());
+    }
+
+    let requested_at: u64 = env
+        .storage()
+        .persistent()
+        .get(&StorageKey::WithdrawRequest(owner.clone(), goal_id))
+        .ok_or(Error::WithdrawRequestRequired)?;
+    let claimable_at = requested_at
+        .checked_add(cooldown)
+        .ok_or(Error::Overflow)?;
+
+    if env.ledger().timestamp() < claimable_at {
+        return Err(Error::CooldownNotElapsed);
+    }
+
+    Ok(())
+}
+
+/// Verify that `admin` authorized the call and is the stored admin
+fn require_admin(env: &Env, admin: &Address) -> Result<(), Error> {
+    admin.require_auth();
+
+    let stored_admin: Address = env
+        .storage()
+        .instance()
+        .get(&StorageKey::Admin)
+        .ok_or(Error::NotInitialized)?;
+
+    if *admin != stored_admin {
+        return Err(Error::Unauthorized);
+    }
+
+    Ok(())
+}
+
+/// Emergency penalty rate in basis points that applies to `goal` right now
+/// 
+/// Flat by default; in `PenaltyMode::LinearDecay` it scales linearly from the
+/// full rate at the start of the lock to zero at unlock time:
+/// `base_penalty * remaining_lock / lock_duration`, and is zero once the goal
+/// is past unlock.
+fn effective_penalty_rate(env: &Env, goal: &SavingsGoal) -> Result<u32, Error> {
+    effective_penalty_rate_traced(env, goal).map_err(|_| Error::Overflow)
+}
+
+/// `effective_penalty_rate`, reporting the failing step as an `ErrorContext`
+fn effective_penalty_rate_traced(env: &Env, goal: &SavingsGoal) -> Result<u32, ErrorContext> {
+    let base: u32 = env
+        .storage()
+        .instance()
+        .get(&StorageKey::EmergencyPenalty)
+        .unwrap_or(1000); // Default 10%
+
+    let mode: PenaltyMode = env
+        .storage()
+        .instance()
+        .get(&StorageKey::PenaltyMode)
+        .unwrap_or(PenaltyMode::Flat);
+    if mode == PenaltyMode::Flat || goal.lock_duration == 0 {
+        return Ok(base);
+    }
+
+    let remaining = goal
+        .unlock_time
+        .saturating_sub(env.ledger().timestamp())
+        .min(goal.lock_duration);
+    let decayed = (base as u64)
+        .checked_mul(remaining)
+        .ok_or(ErrorContext::PenaltyDecay)?
+        .checked_div(goal.lock_duration)
+        .ok_or(ErrorContext::PenaltyDecay)?;
+
+    // remaining <= lock_duration, so the decayed rate never exceeds base
+    Ok(decayed as u32)
+}
+
+/// Release a closing goal's prepaid interest from the prepaid pool, moving it
+/// into the shared reserve when `to_reserve` (the goal's own payout no longer
+/// draws on it)
+fn release_prepaid(env: &Env, goal: &SavingsGoal, to_reserve: bool) -> Result<(), Error> {
+    if goal.prepaid_interest > 0 {
+        adjust_total(env, &StorageKey::PrepaidReserve, -goal.prepaid_interest)?;
+        if to_reserve {
+            adjust_total(env, &StorageKey::Reserve, goal.prepaid_interest)?;
+        }
+    }
+    Ok(())
+}
+
+/// Take `interest` being paid out of (or rolled into) a goal from its backing
+/// 
+/// Prepaid goals draw on their own allocation and never depend on the reserve;
+/// any interest beyond it is covered by the contract balance. Other goals
+/// draw from the reserve and fail with `InsufficientReserve` when it's short.
+fn fund_interest(env: &Env, goal: &mut SavingsGoal, interest: i128) -> Result<(), Error> {
+    if interest <= 0 {
+        return Ok(());
+    }
+
+    if goal.prepaid_interest > 0 {
+        let from_prepaid = interest.min(goal.prepaid_interest);
+        adjust_total(env, &StorageKey::PrepaidReserve, -from_prepaid)?;
+        goal.prepaid_interest -= from_prepaid;
+        return Ok(());
+    }
+
+    let key = interest_reserve_key(env, goal)?;
+    let reserve: i128 = env.storage().instance().get(&key).unwrap_or(0);
+    if reserve < interest {
+        return Err(Error::InsufficientReserve);
+    }
+    env.storage().instance().set(&key, &(reserve - interest));
+
+    Ok(())
+}
+
+/// Roll a matured goal's interest into principal and restart its lock for
+/// `lock_duration` from now; no tokens move, but the rolled interest is
+/// drawn from the goal's backing like a payout
+/// 
+/// Interest paid in the reward token can't join the principal, so such goals
+/// fail with `InvalidToken` while they hold any.
+fn start_new_term(env: &Env, goal: &mut SavingsGoal, lock_duration: u64) -> Result<(), Error> {
+    if goal.interest_in_reward && goal.accrued_interest > 0 {
+        return Err(Error::InvalidToken);
+    }
+
+    let current_time = env.ledger().timestamp();
+    let was_prepaid = goal.prepaid_interest > 0;
+    fund_interest(env, goal, goal.accrued_interest)?;
+
+    goal.principal = goal
+        .principal
+        .checked_add(goal.accrued_interest)
+        .ok_or(Error::Overflow)?;
+    goal.accrued_interest = 0;
+    goal.start_time = current_time;
+    goal.restarted = true;
+    goal.deposit_count = 0;
+    goal.maturity_notified = false;
+    goal.lock_duration = lock_duration;
+    goal.unlock_time = current_time
+        .checked_add(lock_duration)
+        .ok_or(Error::Overflow)?;
+
+    // The new term isn't prepaid; any unused allocation joins the reserve
+    if was_prepaid {
+        release_prepaid(env, goal, true)?;
+        goal.prepaid_interest = 0;
+    }
+
+    Ok(())
+}
+
+/// Pay out a matured goal to its owner. Callers must have checked the
+/// owner's authorization.
+fn settle_withdrawal(env: &Env, owner: &Address, goal_id: u64) -> Result<i128, Error> {
+    settle_withdrawal_to(env, owner, goal_id, owner)
+}
+
+/// Pay out a matured goal to `recipient`. Callers must have checked the
+/// owner's authorization.
+fn settle_withdrawal_to(
+    env: &Env,
+    owner: &Address,
+    goal_id: u64,
+    recipient: &Address,
+) -> Result<i128, Error> {
+    let (goal, total_amount) = close_matured_goal(env, owner, goal_id)?;
+    pay_from_goal(env, &goal, recipient, total_amount, goal.accrued_interest)?;
+    publish_withdrawn(env, owner, goal_id, total_amount, false, recipient);
+
+    Ok(total_amount)
+}
+
+/// Append an action to a goal's history, dropping the oldest entry once it
+/// holds `MAX_HISTORY_ENTRIES`
+fn record_history(env: &Env, owner: &Address, goal_id: u64, action: GoalAction, amount: i128) {
+    let key = StorageKey::GoalHistory(owner.clone(), goal_id);
+    let mut history: Vec<HistoryEntry> = env
+        .storage()
+        .persistent()
+        .get(&key)
+        .unwrap_or(Vec::new(env));
+
+    while history.len() >= MAX_HISTORY_ENTRIES {
+        history.pop_front();
+    }
+    history.push_back(HistoryEntry {
+        action,
+        amount,
+        timestamp: env.ledger().timestamp(),
+    });
+    env.storage().persistent().set(&key, &history);
+}
+
+/// Add interest actually paid out to `owner` to their lifetime total
+/// 
+/// Only normal payouts (matured withdrawals, installments and IOU claims)
+/// count; interest paid on an emergency withdrawal doesn't, since the penalty
+/// is taken from it and the goal never reached maturity.
+fn credit_lifetime_interest(env: &Env, owner: &Address, interest: i128) -> Result<(), Error> {
+    if interest <= 0 {
+        return Ok(());
+    }
+
+    let key = StorageKey::LifetimeInterest(owner.clone());
+    let total: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+    let new_total = total.checked_add(interest).ok_or(Error::Overflow)?;
+    env.storage().persistent().set(&key, &new_total);
+
+    Ok(())
+}
+
+/// Publish the `withdrawn` event for `amount` paid out of a goal to
+/// `recipient`, recording it in the goal's history
+fn publish_withdrawn(
+    env: &Env,
+    owner: &Address,
+    goal_id: u64,
+    amount: i128,
+    penalty_applied: bool,
+    recipient: &Address,
+) {
+    record_history(env, owner, goal_id, GoalAction::Withdrawal, amount);
+    publish_event(
+        env,
+        (symbol_short!("withdrawn"), owner.clone(), goal_id),
+        (amount, penalty_applied, recipient.clone()),
+    );
+}
+
+/// Close a matured goal and return it with the amount to pay out (its
+/// `accrued_interest` is the interest part); the caller makes the transfers.
+/// Callers must have checked the owner's authorization.
+fn close_matured_goal(
+    env: &Env,
+    owner: &Address,
+    goal_id: u64,
+) -> Result<(SavingsGoal, i128), Error> {
+    // Compound interest before withdrawal
+    TimeLockedSavings::compound_interest(env.clone(), owner.clone(), goal_id)?;
+
+    let mut goal: SavingsGoal = env
+        .storage()
+        .persistent()
+        .get(&StorageKey::Goal(owner.clone(), goal_id))
+        .ok_or(Error::GoalNotFound)?;
+
+    // Security: Check if goal is active
+    if !goal.is_active {
+        return Err(Error::AlreadyWithdrawn);
+    }
+    let before = goal.clone();
+    ensure_interest_realized(env, &goal)?;
+
+    let current_time = env.ledger().timestamp();
+
+    // Security: Ensure lock period has passed
+    if current_time < goal.unlock_time {
+        return Err(Error::StillLocked);
+    }
+
+    // Top up interest to the guaranteed minimum from the reserve
+    let guaranteed_rate: u32 = env
+        .storage()
+        .instance()
+        .get(&StorageKey::GuaranteedMinRate)
+        .unwrap_or(0);
+    // Security: The interest portion is paid from the goal's backing
+    let was_prepaid = goal.prepaid_interest > 0;
+    let accrued_interest = goal.accrued_interest;
+    fund_interest(env, &mut goal, accrued_interest)?;
+
+    // Prepaid goals release any unused allocation and skip the guarantee
+    if was_prepaid {
+        release_prepaid(env, &goal, true)?;
+    } else if guaranteed_rate > 0 {
+        let guaranteed_interest =
+            calculate_interest(goal.principal, guaranteed_rate, goal.lock_duration)?;
+        let shortfall = guaranteed_interest
+            .checked_sub(goal.accrued_interest)
+            .ok_or(Error::Underflow)?;
+
+        if shortfall > 0 {
+            let key = interest_reserve_key(env, &goal)?;
+            let reserve: i128 = env.storage().instance().get(&key).unwrap_or(0);
+
+            if reserve >= shortfall {
+                env.storage().instance().set(&key, &(reserve - shortfall));
+                goal.accrued_interest = guaranteed_interest;
+            } else {
+                // Security: Strict mode blocks the withdrawal; best-effort pays
+                // the accrued amount without the guarantee so users aren't trapped
+                let strict: bool = env
+                    .storage()
+                    .instance()
+                    .get(&StorageKey::GuaranteeStrict)
+                    .unwrap_or(false);
+                if strict {
+                    return Err(Error::InsufficientReserve);
+                }
+            }
+        }
+    }
+
+    // Calculate total withdrawal amount
+    let total_amount = goal
+        .principal
+        .checked_add(goal.accrued_interest)
+        .ok_or(Error::Overflow)?;
+
+    // Security: Mark goal as inactive before transfer to prevent reentrancy
+    goal.is_active = false;
+    goal.withdrawn_at = env.ledger().timestamp();
+    save_goal(env, &StorageKey::Goal(owner.clone(), goal_id), &goal);
+    track_goal_change(env, Some(&before), &goal)?;
+    clear_pending_schedules(env, owner, goal_id);
+    credit_lifetime_interest(env, owner, goal.accrued_interest)?;
+
+    Ok((goal, total_amount))
+}
+
+/// The operator's rate cap set by `configure_rates`, or `MAX_INTEREST_RATE`
+fn max_rate(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&ExtendedKey::MaxRate)
+        .unwrap_or(MAX_INTEREST_RATE)
+}
+
+/// Rate for a new goal: the configured default when the caller passes 0
+fn resolve_rate(env: &Env, rate: u32) -> u32 {
+    if rate > 0 {
+        return rate;
+    }
+    env.storage()
+        .instance()
+        .get(&ExtendedKey::DefaultRate)
+        .unwrap_or(0)
+}
+
+/// Reject rates above the configured maximum or above the cap of the rate
+/// tier `lock_duration` falls into
+/// 
+/// With tiers configured, the applicable tier is the one with the largest
+/// `min_duration` not exceeding `lock_duration`; durations below every tier
+/// only allow a zero rate.
+fn ensure_rate_allowed(env: &Env, lock_duration: u64, rate: u32) -> Result<(), Error> {
+    if rate > max_rate(env) {
+        return Err(Error::RateTooHigh);
+    }
+
+    let tiers: Vec<RateTier> = env
+        .storage()
+        .instance()
+        .get(&StorageKey::RateTiers)
+        .unwrap_or(Vec::new(env));
+    if tiers.is_empty() {
+        return Ok(());
+    }
+
+    let cap = tiers
+        .iter()
+        .filter(|tier| tier.min_duration <= lock_duration)
+        .last()
+        .map(|tier| tier.max_rate)
+        .unwrap_or(0);
+    if rate > cap {
+        return Err(Error::RateTooHigh);
+    }
+
+    Ok(())
+}
+
+/// Validate a rate tier table: strictly ascending durations, caps within
+/// `MAX_INTEREST_RATE`
+fn validate_rate_tiers(tiers: &Vec<RateTier>) -> Result<(), Error> {
+    let mut previous: Option<u64> = None;
+    for tier in tiers.iter() {
+        if tier.max_rate > MAX_INTEREST_RATE {
+            return Err(Error::InvalidRateTiers);
+        }
+        if previous.is_some_and(|min_duration| tier.min_duration <= min_duration) {
+            return Err(Error::InvalidRateTiers);
+        }
+        previous = Some(tier.min_duration);
+    }
+    Ok(())
+}
+
+/// Reject goal labels longer than `MAX_LABEL_LEN` bytes
+fn ensure_label_valid(label: &String) -> Result<(), Error> {
+    if label.len() > MAX_LABEL_LEN {
+        return Err(Error::LabelTooLong);
+    }
+    Ok(())
+}
+
+/// Validate and store a new goal funded by `owner`. Callers must have
+/// checked the owner's authorization.
+fn open_goal(
+    env: &Env,
+    owner: &Address,
+    amount: i128,
+    lock_duration: u64,
+    interest_rate: u32,
+    label: Option<String>,
+    token: Address,
+) -> Result<u64, Error> {
+    ensure_not_paused(env)?;
+    ensure_whitelisted(env, owner)?;
+    let interest_rate = resolve_rate(env, interest_rate);
+    validate_new_goal(env, amount, lock_duration, interest_rate, &label)?;
+
+    // Transfer tokens from user to contract
+    // Security: Fails with `InsufficientBalance` if the user can't cover it
+    let token_client = goal_token_client(env, &token)?;
+    pull_tokens(env, &token_client, owner, amount)?;
+
+    store_new_goal(env, owner, amount, lock_duration, interest_rate, label, token)
+}
+
+/// Move `amount` from `from` into the contract, surfacing failures as typed
+/// errors instead of host panics
+/// 
+/// The balance is checked upfront (`InsufficientBalance`); any other failure
+/// of the token's `transfer` maps to `TransferFailed`.
+fn pull_tokens(
+    env: &Env,
+    token: &token::Client,
+    from: &Address,
+    amount: i128,
+) -> Result<(), Error> {
+    if token.balance(from) < amount {
+        return Err(Error::InsufficientBalance);
+    }
+
+    match token.try_transfer(from, &env.current_contract_address(), &amount) {
+        Ok(Ok(())) => Ok(()),
+        _ => Err(Error::TransferFailed),
+    }
+}
+
+/// The token set at initialization, used when a goal doesn't name one
+fn default_token(env: &Env) -> Result<Address, Error> {
+    env.storage()
+        .instance()
+        .get(&StorageKey::Token)
+        .ok_or(Error::NotInitialized)
+}
+
+/// Token for a new goal: the default token when `None`, otherwise a token
+/// from the admin-maintained supported set
+fn resolve_goal_token(env: &Env, token: Option<Address>) -> Result<Address, Error> {
+    let default = default_token(env)?;
+    match token {
+        Some(token) if token != default => {
+            if !env
+                .storage()
+                .instance()
+                .has(&StorageKey::AllowedToken(token.clone()))
+            {
+                return Err(Error::TokenNotSupported);
+            }
+            Ok(token)
+        }
+        _ => Ok(default),
+    }
+}
+
+/// Client for a goal's token, failing if the admin has paused it
+fn goal_token_client<'a>(env: &'a Env, token: &Address) -> Result<token::Client<'a>, Error> {
+    ensure_token_active(env, token)?;
+    Ok(token::Client::new(env, token))
+}
+
+/// Storage key of the interest reserve backing goals in `token`
+/// 
+/// The default token keeps the original `Reserve` key, so penalty recycling,
+/// IOUs and health reporting (which are default-token only) see the same pool.
+fn reserve_key(env: &Env, token: &Address) -> StorageKey {
+    match default_token(env) {
+        Ok(default) if default != *token => StorageKey::TokenReserve(token.clone()),
+        _ => StorageKey::Reserve,
+    }
+}
+
+/// Token a goal's interest is paid in: the reward token for goals opened
+/// while one was configured (`NotInitialized` if it has since been unset),
+/// otherwise the goal's own token
+fn interest_token(env: &Env, goal: &SavingsGoal) -> Result<Address, Error> {
+    if !goal.interest_in_reward {
+        return Ok(goal.token.clone());
+    }
+
+    env.storage()
+        .instance()
+        .get(&ExtendedKey::RewardToken)
+        .ok_or(Error::NotInitialized)
+}
+
+/// Reserve a goal's interest is drawn from
+fn interest_reserve_key(env: &Env, goal: &SavingsGoal) -> Result<StorageKey, Error> {
+    Ok(reserve_key(env, &interest_token(env, goal)?))
+}
+
+/// Transfer `amount` paid out of a goal to `recipient`, of which `interest`
+/// is interest; that part is paid in the goal's interest token
+fn pay_from_goal(
+    env: &Env,
+    goal: &SavingsGoal,
+    recipient: &Address,
+    amount: i128,
+    interest: i128,
+) -> Result<(), Error> {
+    let contract = env.current_contract_address();
+    let token = goal_token_client(env, &goal.token)?;
+    if !goal.interest_in_reward {
+        token.transfer(&contract, recipient, &amount);
+        return Ok(());
+    }
+
+    let principal_part = amount.checked_sub(interest).ok_or(Error::Underflow)?;
+    if principal_part > 0 {
+        token.transfer(&contract, recipient, &principal_part);
+    }
+    if interest > 0 {
+        let reward = goal_token_client(env, &interest_token(env, goal)?)?;
+        reward.transfer(&contract, recipient, &interest);
+    }
+
+    Ok(())
+}
+
+/// Count a goal created by `owner` in the current ledger, failing once the
+/// admin's per-ledger limit is reached
+/// 
+/// There is no dedicated error, so this reports `CooldownActive`: the owner
+/// can create more goals from the next ledger on.
+fn record_creation(env: &Env, owner: &Address) -> Result<(), Error> {
+    let limit: Option<u32> = env.storage().instance().get(&ExtendedKey::CreationRateLimit);
+    let Some(limit) = limit else {
+        return Ok(());
+    };
+
+    let key = ExtendedKey::CreationsInLedger(owner.clone());
+    let sequence = env.ledger().sequence();
+    let count = match env.storage().persistent().get::<_, (u32, u32)>(&key) {
+        Some((last, count)) if last == sequence => count,
+        _ => 0,
+    };
+    if count >= limit {
+        return Err(Error::CooldownActive);
+    }
+    env.storage().persistent().set(&key, &(sequence, count + 1));
+
+    Ok(())
+}
+
+/// Seconds until `owner` may open goals again after an emergency withdrawal
+fn remaining_emergency_cooldown(env: &Env, owner: &Address) -> u64 {
+    let cooldown: u64 = env
+        .storage()
+        .instance()
+        .get(&StorageKey::EmergencyCooldown)
+        .unwrap_or(0);
+    if cooldown == 0 {
+        return 0;
+    }
+
+    let last: Option<u64> = env
+        .storage()
+        .persistent()
+        .get(&StorageKey::LastEmergencyWithdraw(owner.clone()));
+    match last {
+        Some(last) => last
+            .saturating_add(cooldown)
+            .saturating_sub(env.ledger().timestamp()),
+        None => 0,
+    }
+}
+
+/// Reject non-positive amounts, amounts outside the configured
+/// `MinDeposit`/`MaxDeposit` bounds (inclusive) and amounts that would take
+/// the total principal past the global cap
+fn ensure_deposit_allowed(env: &Env, amount: i128) -> Result<(), Error> {
+    if amount <= 0 {
+        return Err(Error::InvalidAmount);
+    }
+
+    ensure_min_deposit(env, amount)?;
+    let storage = env.storage().instance();
+    if let Some(max) = storage.get::<_, i128>(&StorageKey::MaxDeposit) {
+        if amount > max {
+            return Err(Error::DepositAboveMaximum);
+        }
+    }
+
+    ensure_within_global_cap(env, amount)
+}
+
+/// Reject goal balances below the admin-set `MinDeposit` with
+/// `DepositBelowMinimum`
+fn ensure_min_deposit(env: &Env, amount: i128) -> Result<(), Error> {
+    let min: Option<i128> = env.storage().instance().get(&StorageKey::MinDeposit);
+    if let Some(min) = min {
+        if amount < min {
+            return Err(Error::DepositBelowMinimum);
+        }
+    }
+    Ok(())
+}
+
+/// Reject adding `amount` of principal if the running total principal would
+/// exceed the admin-set `GlobalCap`
+/// 
+/// The total sums raw amounts across all supported tokens. Breaches fail with
+/// `DepositAboveMaximum`, as `Error` has no room for a dedicated variant.
+fn ensure_within_global_cap(env: &Env, amount: i128) -> Result<(), Error> {
+    let storage = env.storage().instance();
+    if let Some(cap) = storage.get::<_, i128>(&ExtendedKey::GlobalCap) {
+        let total: i128 = storage.get(&StorageKey::TotalPrincipal).unwrap_or(0);
+        if total.checked_add(amount).ok_or(Error::Overflow)? > cap {
+            return Err(Error::DepositAboveMaximum);
+        }
+    }
+
+    Ok(())
+}
+
+/// Deposit limits must be positive, with the floor not above the ceiling
+fn validate_deposit_limits(min: Option<i128>, max: Option<i128>) -> Result<(), Error> {
+    if min.is_some_and(|min| min <= 0) || max.is_some_and(|max| max <= 0) {
+        return Err(Error::InvalidAmount);
+    }
+    if let (Some(min), Some(max)) = (min, max) {
+        if min > max {
+            return Err(Error::InvalidAmount);
+        }
+    }
+
+    Ok(())
+}
+
+/// Check the parameters of a goal about to be opened
+fn validate_new_goal(
+    env: &Env,
+    amount: i128,
+    lock_duration: u64,
+    interest_rate: u32,
+    label: &Option<String>,
+) -> Result<(), Error> {
+    // Security: Validate inputs
+    ensure_deposit_allowed(env, amount)?;
+
+    if !(MIN_LOCK_DURATION..=MAX_LOCK_DURATION).contains(&lock_duration) {
+        return Err(Error::InvalidDuration);
+    }
+
+    ensure_rate_allowed(env, lock_duration, interest_rate)?;
+
+    if let Some(label) = label {
+        ensure_label_valid(label)?;
+    }
+
+    Ok(())
+}
+
+/// During an early-access period only approved addresses may take on goals;
+/// there is no dedicated error, so this reports `Unauthorized`
+fn ensure_whitelisted(env: &Env, owner: &Address) -> Result<(), Error> {
+    let storage = env.storage().instance();
+    if storage.get(&ExtendedKey::WhitelistEnabled).unwrap_or(false)
+        && !storage.has(&ExtendedKey::Whitelisted(owner.clone()))
+    {
+        return Err(Error::Unauthorized);
+    }
+    Ok(())
+}
+
+/// Bound the active goals one address can hold: fails with `GoalLimitReached`
+/// when `owner` is already at `MaxGoalsPerUser`
+fn ensure_goal_slot_free(env: &Env, owner: &Address) -> Result<(), Error> {
+    let max_goals: Option<u32> = env.storage().instance().get(&StorageKey::MaxGoalsPerUser);
+    if let Some(max_goals) = max_goals {
+        let active: u32 = env
+            .storage()
+            .persistent()
+            .get(&StorageKey::UserActiveGoalCount(owner.clone()))
+            .unwrap_or(0);
+        if active >= max_goals {
+            return Err(Error::GoalLimitReached);
+        }
+    }
+    Ok(())
+}
+
+/// Store a validated goal from a deposit of `amount` the contract already
+/// holds, paying the creation fee out of it to the treasury
+/// 
+/// The goal's principal is `amount` minus the fee; a fee that would leave
+/// nothing fails with `InvalidAmount`. Callers must have applied
+/// `ensure_whitelisted` to whoever funds the goal.
+fn store_new_goal(
+    env: &Env,
+    owner: &Address,
+    amount: i128,
+    lock_duration: u64,
+    interest_rate: u32,
+    label: Option<String>,
+    token: Address,
+) -> Result<u64, Error> {
+    if remaining_emergency_cooldown(env, owner) > 0 {
+        return Err(Error::CooldownActive);
+    }
+
+    record_creation(env, owner)?;
+
+    let fee_bps: u32 = env
+        .storage()
+        .instance()
+        .get(&ExtendedKey::CreationFeeBps)
+        .unwrap_or(0);
+    let fee = amount
+        .checked_mul(fee_bps as i128)
+        .ok_or(Error::Overflow)?
+        .checked_div(BASIS_POINTS)
+        .ok_or(Error::DivisionError)?;
+    let principal = amount.checked_sub(fee).ok_or(Error::Underflow)?;
+    if principal <= 0 {
+        return Err(Error::InvalidAmount);
+    }
+
+    ensure_goal_slot_free(env, owner)?;
+
+    // Get current timestamp
+    let current_time = env.ledger().timestamp();
+
+    // Security: Check for overflow when calculating unlock time
+    let unlock_time = current_time
+        .checked_add(lock_duration)
+        .ok_or(Error::Overflow)?;
+
+    // Generate unique goal ID
+    //
+    // The counter is deliberately global rather than a per-user
+    // `UserGoalCounter`: `GoalOwner`, the paginated global listings and the
+    // keeper scans all resolve a goal from its ID alone, and `transfer_goal`
+    // keeps the ID across owners. Per-user IDs would collide in all of them.
+    // Per-user enumeration goes through `UserGoalIds` instead
+    let goal_id: u64 = env
+        .storage()
+        .instance()
+        .get(&StorageKey::GoalCounter)
+        .unwrap_or(0);
+
+    // Security: Check for goal ID overflow
+    let next_goal_id = goal_id
+        .checked_add(1)
+        .ok_or(Error::GoalOverflow)?;
+
+    // Security: IDs are global and never reused; refuse to overwrite a goal
+    // should the counter ever fall behind the stored goals
+    let persistent = env.storage().persistent();
+    if persistent.has(&StorageKey::GoalOwner(goal_id))
+        || persistent.has(&StorageKey::Goal(owner.clone(), goal_id))
+    {
+        return Err(Error::GoalExists);
+    }
+
+    // Create the savings goal
+    let goal = SavingsGoal {
+        owner: owner.clone(),
+        principal,
+        interest_rate,
+        start_time: current_time,
+        lock_duration,
+        unlock_time,
+        accrued_interest: 0,
+        last_compound_time: current_time,
+        is_active: true,
+        linked_asset: LinkedAsset::None,
+        auto_renew: false,
+        created_ledger: env.ledger().sequence(),
+        compound_count: 0,
+        prepaid_interest: 0,
+        label,
+        compound_mode: CompoundMode::Linear,
+        beneficiary: None,
+        token,
+        recurring_amount: 0,
+        recurring_interval: 0,
+        deposit_count: 0,
+        accrue_after_unlock: None,
+        target_amount: 0,
+        maturity_notified: false,
+        interest_in_reward: env.storage().instance().has(&ExtendedKey::RewardToken),
+        frozen: false,
+        accrual_start_offset: 0,
+        withdrawn_at: 0,
+        created_at: current_time,
+        restarted: false,
+    };
+    let token = goal.token.clone();
+
+    // Store the goal
+    env.storage()
+        .persistent()
+        .set(&StorageKey::GoalOwner(goal_id), owner);
+    save_goal(env, &StorageKey::Goal(owner.clone(), goal_id), &goal);
+    track_goal_change(env, None, &goal)?;
+
+    // Update counters
+    env.storage()
+        .instance()
+        .set(&StorageKey::GoalCounter, &next_goal_id);
+
+    let user_count: u64 = env
+        .storage()
+        .persistent()
+        .get(&StorageKey::UserGoalCount(owner.clone()))
+        .unwrap_or(0);
+    env.storage()
+        .persistent()
+        .set(&StorageKey::UserGoalCount(owner.clone()), &(user_count + 1));
+
+    let ids_key = StorageKey::UserGoalIds(owner.clone());
+    let mut goal_ids: Vec<u64> = env
+        .storage()
+        .persistent()
+        .get(&ids_key)
+        .unwrap_or(Vec::new(env));
+    goal_ids.push_back(goal_id);
+    env.storage().persistent().set(&ids_key, &goal_ids);
+
+    if fee > 0 {
+        let treasury = TimeLockedSavings::get_treasury(env.clone())?;
+        goal_token_client(env, &token)?.transfer(
+            &env.current_contract_address(),
+            &treasury,
+            &fee,
+        );
+    }
+
+    record_history(env, owner, goal_id, GoalAction::Created, principal);
+    publish_event(
+        env,
+        (symbol_short!("created"), owner.clone(), goal_id),
+        (principal, interest_rate, unlock_time, fee),
+    );
+
+    // Soft guard: flag rates the reserve can't sustain without blocking them
+    let sustainable = sustainable_rate(env)?;
+    if interest_rate > sustainable {
+        publish_event(
+            env,
+            (symbol_short!("rate_warn"), owner.clone(), goal_id),
+            (interest_rate, sustainable),
+        );
+    }
+
+    // First goal ever created by this address: count a new unique saver.
+    // `UserGoalCount` can't tell, as transfers move goals in and out
+    let saved_key = ExtendedKey::HasSaved(owner.clone());
+    if !env.storage().persistent().has(&saved_key) {
+        env.storage().persistent().set(&saved_key, &true);
+        let unique_users: u64 = env
+            .storage()
+            .instance()
+            .get(&StorageKey::UniqueUsers)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&StorageKey::UniqueUsers, &(unique_users + 1));
+    }
+
+    Ok(goal_id)
+}
+
+/// Split an emergency exit from `goal`, with its interest realized up to now,
+/// into `(interest_paid, withdrawal_amount, penalty)`
+fn emergency_payout(env: &Env, goal: &SavingsGoal) -> Result<(i128, i128, i128), Error> {
+    let interest_paid = payable_interest(env, goal)?;
+
+    // Calculate total balance
+    let total_balance = goal
+        .principal
+        .checked_add(interest_paid)
+        .ok_or(Error::Overflow)?;
+
+    let penalty = emergency_penalty(env, goal, goal.principal, interest_paid)?;
+
+    let withdrawal_amount = total_balance
+        .checked_sub(penalty)
+        .ok_or(Error::Underflow)?;
+
+    Ok((interest_paid, withdrawal_amount, penalty))
+}
+
+/// Accrued interest an emergency exit can pay out now
+/// 
+/// Security: Interest is paid only as far as its backing allows, so an empty
+/// reserve never blocks an emergency exit.
+fn payable_interest(env: &Env, goal: &SavingsGoal) -> Result<i128, Error> {
+    if goal.prepaid_interest > 0 {
+        return Ok(goal.accrued_interest);
+    }
+
+    let reserve: i128 = env
+        .storage()
+        .instance()
+        .get(&interest_reserve_key(env, goal)?)
+        .unwrap_or(0);
+    Ok(goal.accrued_interest.min(reserve))
+}
+
+/// Emergency penalty on withdrawing `principal` and `interest` from `goal`
+/// early, at its penalty rate (decayed toward maturity if configured)
+/// 
+/// Goals paid interest in the reward token are penalized on principal only,
+/// since the penalty is paid in their token. The penalty is clamped so the
+/// owner keeps at least `MinReturnBps` of `principal + interest`.
+fn emergency_penalty(
+    env: &Env,
+    goal: &SavingsGoal,
+    principal: i128,
+    interest: i128,
+) -> Result<i128, Error> {
+    let penalty_rate = effective_penalty_rate(env, goal)?;
+    let penalty_base = if goal.interest_in_reward {
+        principal
+    } else {
+        principal.checked_add(interest).ok_or(Error::Overflow)?
+    };
+
+    let penalty = penalty_base
+        .checked_mul(penalty_rate as i128)
+        .ok_or(Error::Overflow)?
+        .checked_div(BASIS_POINTS)
+        .ok_or(Error::DivisionError)?;
+
+    let min_return_bps: u32 = env
+        .storage()
+        .instance()
+        .get(&ExtendedKey::MinReturnBps)
+        .unwrap_or(0);
+    if min_return_bps == 0 {
+        return Ok(penalty);
+    }
+    let withdrawn = principal.checked_add(interest).ok_or(Error::Overflow)?;
+    let floor = withdrawn
+        .checked_mul(min_return_bps as i128)
+        .ok_or(Error::Overflow)?
+        .checked_div(BASIS_POINTS)
+        .ok_or(Error::DivisionError)?;
+
+    Ok(penalty.min(withdrawn - floor))
+}
+
+/// Close a goal early with the emergency penalty; the body of
+/// `emergency_withdraw`. Callers must have checked the owner's authorization.
+fn emergency_exit(env: &Env, owner: &Address, goal_id: u64) -> Result<i128, Error> {
+    // Compound interest before withdrawal
+    TimeLockedSavings::compound_interest(env.clone(), owner.clone(), goal_id)?;
+
+    let mut goal: SavingsGoal = env
+        .storage()
+        .persistent()
+        .get(&StorageKey::Goal(owner.clone(), goal_id))
+        .ok_or(Error::GoalNotFound)?;
+
+    // Security: Check if goal is active
+    if !goal.is_active {
+        return Err(Error::AlreadyWithdrawn);
+    }
+    let before = goal.clone();
+    ensure_interest_realized(env, &goal)?;
+
+    let (interest_paid, withdrawal_amount, penalty) = emergency_payout(env, &goal)?;
+    fund_interest(env, &mut goal, interest_paid)?;
+
+    // An early exit forfeits the rest of any prepaid interest to the reserve
+    release_prepaid(env, &goal, true)?;
+
+    // Security: Mark goal as inactive before transfers
+    goal.is_active = false;
+    goal.withdrawn_at = env.ledger().timestamp();
+    save_goal(env, &StorageKey::Goal(owner.clone(), goal_id), &goal);
+    track_goal_change(env, Some(&before), &goal)?;
+    clear_pending_schedules(env, owner, goal_id);
+
+    // Transfer withdrawal amount to owner
+    pay_from_goal(env, &goal, owner, withdrawal_amount, interest_paid)?;
+    distribute_penalty(env, &goal, penalty)?;
+
+    env.storage().persistent().set(
+        &StorageKey::LastEmergencyWithdraw(owner.clone()),
+        &env.ledger().timestamp(),
+    );
+
+    publish_withdrawn(env, owner, goal_id, withdrawal_amount, penalty > 0, owner);
+
+    Ok(withdrawal_amount)
+}
+
+/// Route an emergency penalty collected from `goal`
+/// 
+/// The reserve share stays in the contract until rebalanced; the rest goes
+/// to the configured `PenaltyDestination`: the treasury (default), the
+/// reserve backing the goal's token, or the burn address (kept in the
+/// contract, untracked, when none is set).
+fn distribute_penalty(env: &Env, goal: &SavingsGoal, penalty: i128) -> Result<(), Error> {
+    let token = goal_token_client(env, &goal.token)?;
+
+    // Only default-token penalties are recycled, since the shared reserve is
+    // held in that token
+    let reserve_share_bps: u32 = if goal.token == default_token(env)? {
+        env.storage()
+            .instance()
+            .get(&StorageKey::PenaltyReserveShare)
+            .unwrap_or(0)
+    } else {
+        0
+    };
+    let recycled = penalty
+        .checked_mul(reserve_share_bps as i128)
+        .ok_or(Error::Overflow)?
+        .checked_div(BASIS_POINTS)
+        .ok_or(Error::DivisionError)?;
+    let operator_share = penalty.checked_sub(recycled).ok_or(Error::Underflow)?;
+    adjust_total(env, &StorageKey::PenaltyRevenue, recycled)?;
+
+    if operator_share <= 0 {
+        return Ok(());
+    }
+    let destination: PenaltyDestination = env
+        .storage()
+        .instance()
+        .get(&ExtendedKey::PenaltyDestination)
+        .unwrap_or(PenaltyDestination::Treasury);
+    match destination {
+        PenaltyDestination::Treasury => {
+            let treasury = TimeLockedSavings::get_treasury(env.clone())?;
+            token.transfer(&env.current_contract_address(), &treasury, &operator_share);
+        }
+        PenaltyDestination::Reserve => {
+            adjust_total(env, &reserve_key(env, &goal.token), operator_share)?;
+        }
+        PenaltyDestination::Burn => {
+            let burn: Option<Address> = env.storage().instance().get(&ExtendedKey::BurnAddress);
+            if let Some(burn) = burn {
+                token.transfer(&env.current_contract_address(), &burn, &operator_share);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Run a state-changing entry point with the contract-wide reentrancy lock
+/// held, failing with `Reentrancy` if it is already taken
+/// 
+/// Defense in depth on top of updating state before transfers: a token that
+/// calls back into the contract mid-transfer finds the lock set. The lock is
+/// released however `f` returns.
+fn with_reentrancy_guard<T>(env: &Env, f: impl FnOnce() -> Result<T, Error>) -> Result<T, Error> {
+    let storage = env.storage().instance();
+    if storage.has(&StorageKey::ReentrancyLock) {
+        return Err(Error::Reentrancy);
+    }
+
+    storage.set(&StorageKey::ReentrancyLock, &true);
+    let result = f();
+    storage.remove(&StorageKey::ReentrancyLock);
+
+    result
+}
+
+#[contract]
+pub struct TimeLockedSavings;
+
+#[contractimpl]
+impl TimeLockedSavings {
+    /// Initialize the contract with token address and admin
+    /// 
+    /// # Security:
+    /// - Can only be called once (initialization pattern)
+    /// - Sets up admin privileges for contract management
+    /// - Rejects the contract's own address as the token (`InvalidToken`) or
+    ///   the admin (`Unauthorized`)
+    /// 
+    /// # Parameters:
+    /// - `token`: Address of the token to be used for savings
+    /// - `admin`: Address with administrative privileges
+    /// - `emergency_penalty`: Penalty in basis points for early withdrawal (e.g., 1000 = 10%)
+    pub fn initialize(
+        env: Env,
+        token: Address,
+        admin: Address,
+        emergency_penalty: u32,
+    ) -> Result<(), Error> {
+        // Security: Prevent re-initialization
+        if env.storage().instance().has(&StorageKey::Token) {
+            return Err(Error::AlreadyInitialized);
+        }
+
+        // Security: Validate penalty rate
+        if emergency_penalty > 5000 {
+            // Max 50%
+            return Err(Error::PenaltyTooHigh);
+        }
+
+        // Security: The default token must behave like a token and the admin
+        // must be an outside account
+        let decimals = fetch_token_decimals(&env, &token)?;
+        ensure_not_self(&env, &admin)?;
+
+        // Store contract configuration
+        env.storage().instance().set(&StorageKey::Token, &token);
+        env.storage()
+            .instance()
+            .set(&StorageKey::AllowedToken(token.clone()), &decimals);
+        env.storage().instance().set(&StorageKey::Admin, &admin);
+        env.storage()
+            .instance()
+            .set(&StorageKey::EmergencyPenalty, &emergency_penalty);
+        env.storage().instance().set(&StorageKey::GoalCounter, &0u64);
+        env.storage()
+            .instance()
+            .set(&StorageKey::EventNamespace, &DEFAULT_EVENT_NAMESPACE);
+
+        Ok(())
+    }
+
+    /// Initialize the contract with its complete configuration in one call
+    /// 
+    /// # Security:
+    /// - Can only be called once, like `initialize`
+    /// - Validates every setting with the same rules as the individual admin
+    ///   setters; any invalid setting reverts the whole call
+    /// 
+    /// # Parameters:
+    /// - `config`: Full configuration template (see `FullConfig`)
+    pub fn initialize_full(env: Env, config: FullConfig) -> Result<(), Error> {
+        Self::initialize(
+            env.clone(),
+            config.token.clone(),
+            config.admin.clone(),
+            config.emergency_penalty,
+        )?;
+
+        // Security: Validate the remaining settings
+        if config.penalty_reserve_share_bps as i128 > BASIS_POINTS {
+            return Err(Error::InvalidAmount);
+        }
+        if config.guaranteed_min_rate > MAX_INTEREST_RATE {
+            return Err(Error::RateTooHigh);
+        }
+        validate_rate_tiers(&config.rate_tiers)?;
+        validate_deposit_limits(config.min_deposit, config.max_deposit)?;
+        let thresholds = &config.health_thresholds;
+        if thresholds.critical_solvency_bps > thresholds.warning_solvency_bps
+            || thresholds.critical_runway > thresholds.warning_runway
+        {
+            return Err(Error::InvalidThresholds);
+        }
+
+        let storage = env.storage().instance();
+        storage.set(
+            &StorageKey::PenaltyReserveShare,
+            &config.penalty_reserve_share_bps,
+        );
+        storage.set(&StorageKey::WithdrawCooldown, &config.withdraw_cooldown);
+        storage.set(&StorageKey::GuaranteedMinRate, &config.guaranteed_min_rate);
+        storage.set(&StorageKey::GuaranteeStrict, &config.guarantee_strict);
+        storage.set(&StorageKey::HealthThresholds, &config.health_thresholds);
+        storage.set(&StorageKey::TopUpMode, &config.top_up_mode);
+        storage.set(&StorageKey::EventNamespace, &config.event_namespace);
+        storage.set(&StorageKey::RateTiers, &config.rate_tiers);
+        if let Some(min) = config.min_deposit {
+            storage.set(&StorageKey::MinDeposit, &min);
+        }
+        if let Some(max) = config.max_deposit {
+            storage.set(&StorageKey::MaxDeposit, &max);
+        }
+        if let Some(treasury) = &config.treasury {
+            ensure_not_self(&env, treasury)?;
+            storage.set(&StorageKey::Treasury, treasury);
+        }
+        if let Some(max_goals) = config.max_goals_per_user {
+            storage.set(&StorageKey::MaxGoalsPerUser, &max_goals);
+        }
+
+        for token in config.extra_tokens.iter() {
+            let decimals = fetch_token_decimals(&env, &token)?;
+            storage.set(&StorageKey::AllowedToken(token), &decimals);
+        }
+
+        Ok(())
+    }
+
+    /// Create a new savings goal with time-lock
+    /// 
+    /// # Security:
+    /// - Validates all inputs before state changes
+    /// - Uses authorization to ensure only owner can create goals
+    /// - Atomic operation - either fully succeeds or reverts
+    /// - Protects against overflow in calculations
+    /// 
+    /// # Parameters:
+    /// - `owner`: Address of the goal owner (must authorize)
+    /// - `amount`: Amount to deposit
+    /// - `lock_duration`: How long funds are locked (in seconds)
+    /// - `interest_rate`: Annual interest rate in basis points
+    /// - `label`: Optional display name, at most 32 bytes
+    /// - `token`: Token to save in, from the supported set; `None` uses the
+    ///   token configured at initialization
+    /// - `target_amount`: Balance the owner is saving toward; 0 for none
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_goal(
+        env: Env,
+        owner: Address,
+        amount: i128,
+        lock_duration: u64,
+        interest_rate: u32,
+        label: Option<String>,
+        token: Option<Address>,
+        target_amount: i128,
+    ) -> Result<u64, Error> {
+        // Security: Require authorization from the owner
+        owner.require_auth();
+
+        if target_amount < 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        with_reentrancy_guard(&env, || {
+            let token = resolve_goal_token(&env, token)?;
+            let goal_id =
+                open_goal(&env, &owner, amount, lock_duration, interest_rate, label, token)?;
+
+            if target_amount > 0 {
+                let key = StorageKey::Goal(owner.clone(), goal_id);
+                let mut goal: SavingsGoal = env
+                    .storage()
+                    .persistent()
+                    .get(&key)
+                    .ok_or(Error::GoalNotFound)?;
+                goal.target_amount = target_amount;
+                save_goal(&env, &key, &goal);
+            }
+
+            Ok(goal_id)
+        })
+    }
+
+    /// Create a goal that unlocks at a specific timestamp instead of after a
+    /// duration
+    /// 
+    /// # Security:
+    /// - Same authorization and validation as `create_goal`, in the default
+    ///   token and without a label
+    /// - Rejects timestamps not in the future (`UnlockInPast`); the implied
+    ///   lock duration must be within the usual bounds (`InvalidDuration`)
+    /// 
+    /// # Parameters:
+    /// - `owner`: Address of the goal owner (must authorize)
+    /// - `amount`: Amount to deposit
+    /// - `unlock_timestamp`: Ledger timestamp at which the goal unlocks
+    /// - `interest_rate`: Annual interest rate in basis points
+    pub fn create_goal_until(
+        env: Env,
+        owner: Address,
+        amount: i128,
+        unlock_timestamp: u64,
+        interest_rate: u32,
+    ) -> Result<u64, Error> {
+        // Security: Require authorization from the owner
+        owner.require_auth();
+
+        with_reentrancy_guard(&env, || {
+            let current_time = env.ledger().timestamp();
+            if unlock_timestamp <= current_time {
+                return Err(Error::UnlockInPast);
+            }
+            let lock_duration = unlock_timestamp - current_time;
+
+            let token = default_token(&env)?;
+            open_goal(&env, &owner, amount, lock_duration, interest_rate, None, token)
+        })
+    }
+
+    /// Create a goal owned by `owner` but paid for by `funder`, e.g. a gift
+    /// 
+    /// # Security:
+    /// - Requires authorization from the funder only; the owner alone can
+    ///   later withdraw
+    /// - Same validation as `create_goal`, in the default token and without
+    ///   a label; the goal counts toward the owner's limits and totals
+    /// - During early access both the funder, as the caller, and the owner
+    ///   must be whitelisted, as for `transfer_goal` recipients
+    /// - Emits a `gifted` event naming the funder
+    /// 
+    /// # Parameters:
+    /// - `funder`: Address paying the principal (must authorize)
+    /// - `owner`: Address that will own the goal
+    /// - `amount`: Amount to deposit
+    /// - `lock_duration`: How long funds are locked (in seconds)
+    /// - `interest_rate`: Annual interest rate in basis points
+    pub fn create_goal_for(
+        env: Env,
+        funder: Address,
+        owner: Address,
+        amount: i128,
+        lock_duration: u64,
+        interest_rate: u32,
+    ) -> Result<u64, Error> {
+        // Security: Require authorization from the funder
+        funder.require_auth();
+
+        with_reentrancy_guard(&env, || {
+            ensure_not_paused(&env)?;
+            ensure_whitelisted(&env, &funder)?;
+            ensure_whitelisted(&env, &owner)?;
+            let interest_rate = resolve_rate(&env, interest_rate);
+            validate_new_goal(&env, amount, lock_duration, interest_rate, &None)?;
+
+            let token = default_token(&env)?;
+            let token_client = goal_token_client(&env, &token)?;
+            pull_tokens(&env, &token_client, &funder, amount)?;
+
+            let goal_id =
+                store_new_goal(&env, &owner, amount, lock_duration, interest_rate, None, token)?;
+            publish_event(
+                &env,
+                (symbol_short!("gifted"), owner.clone(), goal_id),
+                (funder.clone(), amount),
+            );
+
+            Ok(goal_id)
+        })
+    }
+
+    /// Create several goals with a single authorization and token transfer
+    /// 
+    /// # Security:
+    /// - Requires authorization from the owner
+    /// - Every entry is validated before any funds move; one invalid entry
+    ///   reverts the whole batch
+    /// - At most `MAX_BATCH_GOALS` entries, to bound the cost of one call
+    /// 
+    /// # Parameters:
+    /// - `owner`: Address of the goal owner
+    /// - `goals`: `(amount, lock_duration, interest_rate)` for each goal
+    /// - `token`: Token for every goal in the batch; `None` uses the default
+    /// 
+    /// # Returns:
+    /// - The new goal IDs, in the order of `goals`
+    pub fn create_goals_batch(
+        env: Env,
+        owner: Address,
+        goals: Vec<(i128, u64, u32)>,
+        token: Option<Address>,
+    ) -> Result<Vec<u64>, Error> {
+        // Security: Require authorization from the owner
+        owner.require_auth();
+
+        with_reentrancy_guard(&env, || {
+            ensure_not_paused(&env)?;
+            ensure_whitelisted(&env, &owner)?;
+
+            if goals.is_empty() || goals.len() > MAX_BATCH_GOALS {
+                return Err(Error::InvalidBatch);
+            }
+
+            let mut total: i128 = 0;
+            for (amount, lock_duration, interest_rate) in goals.iter() {
+                let interest_rate = resolve_rate(&env, interest_rate);
+                validate_new_goal(&env, amount, lock_duration, interest_rate, &None)?;
+                total = total.checked_add(amount).ok_or(Error::Overflow)?;
+            }
+            ensure_within_global_cap(&env, total)?;
+
+            // Security: One transfer for the whole batch; fails on insufficient balance
+            let token = resolve_goal_token(&env, token)?;
+            let token_client = goal_token_client(&env, &token)?;
+            pull_tokens(&env, &token_client, &owner, total)?;
+
+            let mut goal_ids = Vec::new(&env);
+            for (amount, lock_duration, interest_rate) in goals.iter() {
+                let goal_id = store_new_goal(
+                    &env,
+                    &owner,
+                    amount,
+                    lock_duration,
+                    resolve_rate(&env, interest_rate),
+                    None,
+                    token.clone(),
+                )?;
+                goal_ids.push_back(goal_id);
+            }
+
+            Ok(goal_ids)
+        })
+    }
+
+    /// Create a goal whose full expected interest is paid upfront
+    /// 
+    /// # Security:
+    /// - Requires authorization from the owner (principal) and the sponsor
+    ///   (interest); they may be the same address
+    /// - The prepaid interest is `principal * rate * lock_duration` and is held
+    ///   for this goal only, so its payout doesn't depend on reserve health
+    /// - An emergency withdrawal forfeits the prepaid interest to the reserve
+    /// 
+    /// # Parameters:
+    /// - `owner`: Address of the goal owner
+    /// - `sponsor`: Address paying the prepaid interest
+    /// - `amount`: Principal to deposit
+    /// - `lock_duration`: How long funds are locked (in seconds)
+    /// - `interest_rate`: Annual interest rate in basis points
+    pub fn create_goal_prepaid(
+        env: Env,
+        owner: Address,
+        sponsor: Address,
+        amount: i128,
+        lock_duration: u64,
+        interest_rate: u32,
+    ) -> Result<u64, Error> {
+        owner.require_auth();
+        if sponsor != owner {
+            sponsor.require_auth();
+        }
+
+        with_reentrancy_guard(&env, || {
+            // Prepaid interest is pooled in the default token, so goals use it too
+            let token_address = default_token(&env)?;
+            let interest_rate = resolve_rate(&env, interest_rate);
+            let goal_id = open_goal(
+                &env,
+                &owner,
+                amount,
+                lock_duration,
+                interest_rate,
+                None,
+                token_address.clone(),
+            )?;
+
+            // Interest is prepaid on the principal left after any creation fee
+            let key = StorageKey::Goal(owner, goal_id);
+            let mut goal: SavingsGoal = env
+                .storage()
+                .persistent()
+                .get(&key)
+                .ok_or(Error::GoalNotFound)?;
+            let prepaid = calculate_interest(goal.principal, interest_rate, lock_duration)?;
+            if prepaid > 0 {
+                let token = token::Client::new(&env, &token_address);
+                pull_tokens(&env, &token, &sponsor, prepaid)?;
+                adjust_total(&env, &StorageKey::PrepaidReserve, prepaid)?;
+
+                goal.prepaid_interest = prepaid;
+            }
+            // The prepaid allocation is in the default token, so interest is too
+            goal.interest_in_reward = false;
+            save_goal(&env, &key, &goal);
+
+            Ok(goal_id)
+        })
+    }
+
+    /// Get the total interest held for active prepaid goals
+    /// 
+    /// # Security:
+    /// - Read-only function
+    pub fn get_prepaid_reserve(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&StorageKey::PrepaidReserve)
+            .unwrap_or(0)
+    }
+
+    /// Add funds to an existing goal
+    /// 
+    /// # Security:
+    /// - Requires owner authorization
+    /// - Compounds interest first so the new principal only earns from now on
+    /// - Fails with `Paused` while the contract is paused
+    /// - Rejects inactive goals (`GoalInactive`) and goals past their unlock
+    ///   time (`GoalMatured`): new funds would be withdrawable immediately, so
+    ///   matured goals must be re-locked with `relock` before topping up
+    /// - Enforces the admin-configured deposit limits on `amount`
+    /// - Uses checked arithmetic
+    /// 
+    /// # Parameters:
+    /// - `owner`: Address of the goal owner
+    /// - `goal_id`: ID of the goal to top up
+    /// - `amount`: Amount to deposit
+    /// 
+    /// With `TopUpMode::ExtendBlended` the unlock time becomes
+    /// `now + (principal * remaining + amount * lock_duration) / (principal + amount)`;
+    /// a top-up that would stretch the goal's total lock past
+    /// `MAX_LOCK_DURATION` fails with `InvalidDuration`.
+    /// Returns the new total principal.
+    pub fn deposit(env: Env, owner: Address, goal_id: u64, amount: i128) -> Result<i128, Error> {
+        owner.require_auth();
+
+        with_reentrancy_guard(&env, || {
+            ensure_not_paused(&env)?;
+            ensure_deposit_allowed(&env, amount)?;
+
+            Self::compound_interest(env.clone(), owner.clone(), goal_id)?;
+
+            let key = StorageKey::Goal(owner.clone(), goal_id);
+            let mut goal: SavingsGoal = env
+                .storage()
+                .persistent()
+                .get(&key)
+                .ok_or(Error::GoalNotFound)?;
+            let before = goal.clone();
+
+            if env.ledger().timestamp() >= goal.unlock_time {
+                return Err(Error::GoalMatured);
+            }
+
+            let new_principal = goal
+                .principal
+                .checked_add(amount)
+                .ok_or(Error::Overflow)?;
+
+            let mode: TopUpMode = env
+                .storage()
+                .instance()
+                .get(&StorageKey::TopUpMode)
+                .unwrap_or(TopUpMode::KeepUnlock);
+
+            if mode == TopUpMode::ExtendBlended {
+                let current_time = env.ledger().timestamp();
+                let remaining = goal.unlock_time.saturating_sub(current_time);
+
+                let blended = goal
+                    .principal
+                    .checked_mul(remaining as i128)
+                    .ok_or(Error::Overflow)?
+                    .checked_add(
+                        amount
+                            .checked_mul(goal.lock_duration as i128)
+                            .ok_or(Error::Overflow)?,
+                    )
+                    .ok_or(Error::Overflow)?
+                    .checked_div(new_principal)
+                    .ok_or(Error::DivisionError)?;
+
+                goal.unlock_time = current_time
+                    .checked_add(blended as u64)
+                    .ok_or(Error::Overflow)?;
+                goal.lock_duration = goal.unlock_time - goal.start_time;
+                if goal.lock_duration > MAX_LOCK_DURATION {
+                    return Err(Error::InvalidDuration);
+                }
+            }
+
+            goal.principal = new_principal;
+            goal.deposit_count = goal.deposit_count.checked_add(1).ok_or(Error::Overflow)?;
+
+            let token = goal_token_client(&env, &goal.token)?;
+            pull_tokens(&env, &token, &owner, amount)?;
+            record_history(&env, &owner, goal_id, GoalAction::Deposit, amount);
+
+            save_goal(&env, &key, &goal);
+            track_goal_change(&env, Some(&before), &goal)?;
+
+            // Announce the top-up that carries the balance across the target
+            let balance_before = before
+                .principal
+                .checked_add(before.accrued_interest)
+                .ok_or(Error::Overflow)?;
+            let balance_after = new_principal
+                .checked_add(goal.accrued_interest)
+                .ok_or(Error::Overflow)?;
+            if goal.target_amount > 0
+                && balance_before < goal.target_amount
+                && balance_after >= goal.target_amount
+            {
+                publish_event(
+                    &env,
+                    (symbol_short!("target"), owner, goal_id),
+                    (goal.target_amount, balance_after),
+                );
+            }
+
+            Ok(new_principal)
+        })
+    }
+
+    /// Compound interest for a specific goal
+    /// 
+    /// # Security:
+    /// - Only calculates interest, doesn't modify principal
+    /// - Uses safe math to prevent overflow
+    /// - Can be called by anyone (public utility function)
+    /// 
+    /// # Parameters:
+    /// - `owner`: Address of the goal owner
+    /// - `goal_id`: ID of the goal to compound
+    pub fn compound_interest(env: Env, owner: Address, goal_id: u64) -> Result<(), Error> {
+        let mut goal: SavingsGoal = env
+            .storage()
+            .persistent()
+            .get(&StorageKey::Goal(owner.clone(), goal_id))
+            .ok_or(Error::GoalNotFound)?;
+
+        // Security: Check if goal is active
+        if !goal.is_active {
+            return Err(Error::GoalInactive);
+        }
+        let before = goal.clone();
+
+        let current_time = env.ledger().timestamp();
+
+        // Calculate time elapsed since last compound
+        let time_elapsed = current_time
+            .checked_sub(goal.last_compound_time)
+            .ok_or(Error::TimeError)?;
+
+        if time_elapsed == 0 {
+            return Ok(()); // No time passed, nothing to compound
+        }
+
+        // Calculate interest: (principal + accrued) * rate * time / (SECONDS_PER_YEAR * BASIS_POINTS)
+        // Security: Use checked arithmetic to prevent overflow
+        let total_balance = goal
+            .principal
+            .checked_add(goal.accrued_interest)
+            .ok_or(Error::Overflow)?;
+
+        // Time past unlock only counts when the goal accrues after maturity
+        let accruing = accruing_seconds(&env, &goal)?;
+        let interest = goal_interest(&goal, total_balance, accruing)?;
+
+        // Update accrued interest
+        goal.accrued_interest = goal
+            .accrued_interest
+            .checked_add(interest)
+            .ok_or(Error::Overflow)?;
+
+        goal.last_compound_time = current_time;
+        if interest > 0 {
+            goal.compound_count = goal.compound_count.saturating_add(1);
+        }
+
+        // Save updated goal
+        save_goal(&env, &StorageKey::Goal(owner.clone(), goal_id), &goal);
+        track_goal_change(&env, Some(&before), &goal)?;
+
+        if interest > 0 {
+            record_history(&env, &owner, goal_id, GoalAction::Compound, interest);
+            publish_event(
+                &env,
+                (symbol_short!("compound"), owner, goal_id),
+                (interest, goal.accrued_interest),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Compound every active goal of `owner` in one call
+    /// 
+    /// # Security:
+    /// - Can be called by anyone, like `compound_interest`
+    /// - Skips inactive goals and goals already compounded at this timestamp
+    /// - Compounds at most `MAX_COMPOUND_ALL` goals, in creation order, to
+    ///   stay within the invocation budget; later goals can be compounded
+    ///   individually with `compound_interest`
+    /// 
+    /// # Returns:
+    /// - The number of goals compounded
+    pub fn compound_all(env: Env, owner: Address) -> Result<u32, Error> {
+        let goal_ids: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&StorageKey::UserGoalIds(owner.clone()))
+            .unwrap_or(Vec::new(&env));
+        let current_time = env.ledger().timestamp();
+
+        let mut compounded = 0;
+        for goal_id in goal_ids.iter() {
+            if compounded >= MAX_COMPOUND_ALL {
+                break;
+            }
+
+            let goal: Option<SavingsGoal> = env
+                .storage()
+                .persistent()
+                .get(&StorageKey::Goal(owner.clone(), goal_id));
+            match goal {
+                Some(goal) if goal.is_active && goal.last_compound_time < current_time => {
+                    Self::compound_interest(env.clone(), owner.clone(), goal_id)?;
+                    compounded += 1;
+                }
+                _ => {}
+            }
+        }
+
+        Ok(compounded)
+    }
+
+    /// Withdraw funds from a matured goal
+    /// 
+    /// # Security:
+    /// - Requires owner authorization
+    /// - Checks unlock time before allowing withdrawal
+    /// - Compounds interest before withdrawal
+    /// - Marks goal as inactive to prevent double withdrawal
+    /// - Uses checked arithmetic
+    /// 
+    /// # Parameters:
+    /// - `owner`: Address of the goal owner
+    /// - `goal_id`: ID of the goal to withdraw from
+    pub fn withdraw(env: Env, owner: Address, goal_id: u64) -> Result<i128, Error> {
+        // Security: Require authorization
+        owner.require_auth();
+
+        with_reentrancy_guard(&env, || {
+            // Security: With a cooldown configured, withdrawals must go through
+            // request_withdraw/claim_withdraw
+            let cooldown: u64 = env
+                .storage()
+                .instance()
+                .get(&StorageKey::WithdrawCooldown)
+                .unwrap_or(0);
+            if cooldown > 0 {
+                return Err(Error::WithdrawRequestRequired);
+            }
+
+            settle_withdrawal(&env, &owner, goal_id)
+        })
+    }
+
+    /// Withdraw a matured goal to another address, e.g. a cold wallet
+    /// 
+    /// # Security:
+    /// - Requires owner authorization
+    /// - Same checks as `withdraw` (active, unlocked, cooldown, compound first,
+    ///   mark inactive); only the destination differs
+    /// 
+    /// # Parameters:
+    /// - `owner`: Address of the goal owner
+    /// - `goal_id`: ID of the goal to withdraw from
+    /// - `recipient`: Address receiving the matured total
+    pub fn withdraw_to(
+        env: Env,
+        owner: Address,
+        goal_id: u64,
+        recipient: Address,
+    ) -> Result<i128, Error> {
+        // Security: Require authorization
+        owner.require_auth();
+
+        with_reentrancy_guard(&env, || {
+            let cooldown: u64 = env
+                .storage()
+                .instance()
+                .get(&StorageKey::WithdrawCooldown)
+                .unwrap_or(0);
+            if cooldown > 0 {
+                return Err(Error::WithdrawRequestRequired);
+            }
+
+            settle_withdrawal_to(&env, &owner, goal_id, &recipient)
+        })
+    }
+
+    /// Withdraw part of a matured goal, leaving the rest earning interest
+    /// 
+    /// # Security:
+    /// - Requires owner authorization
+    /// - Same maturity and cooldown rules as `withdraw`
+    /// - Compounds interest first, then takes from interest before principal
+    /// - Withdrawing the full balance behaves exactly like `withdraw`
+    /// 
+    /// # Parameters:
+    /// - `owner`: Address of the goal owner
+    /// - `goal_id`: ID of the goal to withdraw from
+    /// - `amount`: Amount to withdraw, at most principal plus accrued interest
+    pub fn withdraw_partial(
+        env: Env,
+        owner: Address,
+        goal_id: u64,
+        amount: i128,
+    ) -> Result<i128, Error> {
+        // Security: Require authorization
+        owner.require_auth();
+
+        with_reentrancy_guard(&env, || {
+            if amount <= 0 {
+                return Err(Error::InvalidAmount);
+            }
+
+            let cooldown: u64 = env
+                .storage()
+                .instance()
+                .get(&StorageKey::WithdrawCooldown)
+                .unwrap_or(0);
+            if cooldown > 0 {
+                return Err(Error::WithdrawRequestRequired);
+            }
+
+            Self::compound_interest(env.clone(), owner.clone(), goal_id)?;
+
+            let key = StorageKey::Goal(owner.clone(), goal_id);
+            let mut goal: SavingsGoal = env
+                .storage()
+                .persistent()
+                .get(&key)
+                .ok_or(Error::GoalNotFound)?;
+
+            if !goal.is_active {
+                return Err(Error::AlreadyWithdrawn);
+            }
+            let before = goal.clone();
+            ensure_interest_realized(&env, &goal)?;
+
+            // Security: Ensure lock period has passed
+            if env.ledger().timestamp() < goal.unlock_time {
+                return Err(Error::StillLocked);
+            }
+
+            let total_balance = goal
+                .principal
+                .checked_add(goal.accrued_interest)
+                .ok_or(Error::Overflow)?;
+            if amount > total_balance {
+                return Err(Error::AmountExceedsBalance);
+            }
+            if amount == total_balance {
+                return settle_withdrawal(&env, &owner, goal_id);
+            }
+
+            // Security: Update state before transfer; the interest portion is
+            // paid from the goal's backing
+            let from_interest = amount.min(goal.accrued_interest);
+            fund_interest(&env, &mut goal, from_interest)?;
+            deduct_from_balance(&mut goal, amount)?;
+            save_goal(&env, &key, &goal);
+            track_goal_change(&env, Some(&before), &goal)?;
+            credit_lifetime_interest(&env, &owner, from_interest)?;
+
+            pay_from_goal(&env, &goal, &owner, amount, from_interest)?;
+            record_history(&env, &owner, goal_id, GoalAction::Withdrawal, amount);
+
+            publish_event(
+                &env,
+                (symbol_short!("partial"), owner, goal_id),
+                (amount, total_balance - amount),
+            );
+
+            Ok(amount)
+        })
+    }
+
+    /// Withdraw a matured goal, dividing the payout among several recipients
+    /// 
+    /// # Security:
+    /// - Requires owner authorization
+    /// - Same maturity and cooldown rules as `withdraw`
+    /// - Shares are basis points that must be non-zero and sum to 10000;
+    ///   rounding dust goes to the first recipient
+    /// 
+    /// # Parameters:
+    /// - `owner`: Address of the goal owner
+    /// - `goal_id`: ID of the goal to withdraw from
+    /// - `recipients`: Up to 10 `(recipient, share_bps)` pairs
+    pub fn withdraw_split(
+        env: Env,
+        owner: Address,
+        goal_id: u64,
+        recipients: Vec<(Address, u32)>,
+    ) -> Result<i128, Error> {
+        // Security: Require authorization
+        owner.require_auth();
+
+        with_reentrancy_guard(&env, || {
+            // Security: Validate the split before touching the goal
+            if recipients.is_empty() || recipients.len() > MAX_SPLIT_RECIPIENTS {
+                return Err(Error::InvalidSplit);
+            }
+            let mut total_bps: i128 = 0;
+            for (_, share_bps) in recipients.iter() {
+                if share_bps == 0 {
+                    return Err(Error::InvalidSplit);
+                }
+                total_bps += share_bps as i128;
+            }
+            if total_bps != BASIS_POINTS {
+                return Err(Error::InvalidSplit);
+            }
+
+            let cooldown: u64 = env
+                .storage()
+                .instance()
+                .get(&StorageKey::WithdrawCooldown)
+                .unwrap_or(0);
+            if cooldown > 0 {
+                return Err(Error::WithdrawRequestRequired);
+            }
+
+            let (goal, total_amount) = close_matured_goal(&env, &owner, goal_id)?;
+
+            // Each share would mix principal and reward-token interest
+            if goal.interest_in_reward {
+                return Err(Error::InvalidToken);
+            }
+            let token = goal_token_client(&env, &goal.token)?;
+
+            // Pay every recipient but the first its rounded-down share, then the
+            // first gets whatever remains
+            let mut remaining = total_amount;
+            for (recipient, share_bps) in recipients.iter().skip(1) {
+                let share = total_amount
+                    .checked_mul(share_bps as i128)
+                    .ok_or(Error::Overflow)?
+                    .checked_div(BASIS_POINTS)
+                    .ok_or(Error::DivisionError)?;
+                remaining = remaining.checked_sub(share).ok_or(Error::Underflow)?;
+                token.transfer(&env.current_contract_address(), &recipient, &share);
+                publish_withdrawn(&env, &owner, goal_id, share, false, &recipient);
+            }
+            let (first, _) = recipients.get_unchecked(0);
+            token.transfer(&env.current_contract_address(), &first, &remaining);
+            publish_withdrawn(&env, &owner, goal_id, remaining, false, &first);
+
+            Ok(total_amount)
+        })
+    }
+
+    /// Withdraw only the principal of a matured goal, deferring its interest
+    /// 
+    /// Intended for when the reserve can't cover the accrued interest: the
+    /// principal is returned now and the interest is recorded as an IOU,
+    /// redeemable via `claim_iou` once the reserve is funded. When the
+    /// reserve (or the goal's prepaid interest) does cover it, the goal is
+    /// paid out in full like `withdraw` and no IOU is recorded.
+    /// 
+    /// # Security:
+    /// - Requires owner authorization
+    /// - Same maturity, state and cooldown checks as `withdraw`
+    /// - Only for goals in the default token (`InvalidToken` otherwise)
+    /// - Compounds interest before converting it to an IOU
+    /// - Marks goal as inactive before transfer
+    /// 
+    /// # Returns:
+    /// - The amount paid now: the principal, or the full balance if the
+    ///   interest was covered
+    /// 
+    /// # Parameters:
+    /// - `owner`: Address of the goal owner
+    /// - `goal_id`: ID of the goal to withdraw from
+    pub fn withdraw_principal_only(env: Env, owner: Address, goal_id: u64) -> Result<i128, Error> {
+        owner.require_auth();
+
+        with_reentrancy_guard(&env, || {
+            ensure_cooldown_elapsed(&env, &owner, goal_id)?;
+
+            Self::compound_interest(env.clone(), owner.clone(), goal_id)?;
+
+            let mut goal: SavingsGoal = env
+                .storage()
+                .persistent()
+                .get(&StorageKey::Goal(owner.clone(), goal_id))
+                .ok_or(Error::GoalNotFound)?;
+
+            if !goal.is_active {
+                return Err(Error::AlreadyWithdrawn);
+            }
+            let before = goal.clone();
+            ensure_interest_realized(&env, &goal)?;
+
+            if env.ledger().timestamp() < goal.unlock_time {
+                return Err(Error::StillLocked);
+            }
+
+            // IOUs are redeemed from the default-token reserve
+            if goal.token != default_token(&env)? || goal.interest_in_reward {
+                return Err(Error::InvalidToken);
+            }
+
+            // Only a reserve shortfall turns the interest into an IOU
+            let reserve: i128 = env
+                .storage()
+                .instance()
+                .get(&interest_reserve_key(&env, &goal)?)
+                .unwrap_or(0);
+            if goal.prepaid_interest > 0 || reserve >= goal.accrued_interest {
+                return settle_withdrawal(&env, &owner, goal_id);
+            }
+
+            let principal = goal.principal;
+            let unpaid_interest = goal.accrued_interest;
+
+            // Security: Mark goal as inactive before transfer
+            goal.is_active = false;
+            goal.withdrawn_at = env.ledger().timestamp();
+            save_goal(&env, &StorageKey::Goal(owner.clone(), goal_id), &goal);
+            track_goal_change(&env, Some(&before), &goal)?;
+            clear_pending_schedules(&env, &owner, goal_id);
+
+            // Prepaid interest funds the IOU through the reserve
+            release_prepaid(&env, &goal, true)?;
+
+            let iou_key = StorageKey::Iou(owner.clone());
+            let iou: i128 = env.storage().persistent().get(&iou_key).unwrap_or(0);
+            let new_iou = iou.checked_add(unpaid_interest).ok_or(Error::Overflow)?;
+            env.storage().persistent().set(&iou_key, &new_iou);
+            adjust_total(&env, &StorageKey::TotalIou, unpaid_interest)?;
+
+            let token = goal_token_client(&env, &goal.token)?;
+            token.transfer(&env.current_contract_address(), &owner, &principal);
+
+            publish_withdrawn(&env, &owner, goal_id, principal, false, &owner);
+
+            Ok(principal)
+        })
+    }
+
+    /// Undo a goal created by mistake, refunding its principal in full
+    /// 
+    /// # Security:
+    /// - Requires owner authorization
+    /// - Only within `CANCEL_GRACE_PERIOD` of the goal's creation, and never
+    ///   once it was merged into, re-locked or renewed (its `start_time`
+    ///   moves then, and the goal may hold more than was deposited); these
+    ///   fail with `GracePeriodExpired`
+    /// - No penalty and no interest; any prepaid interest goes to the reserve
+    /// - Marks goal as inactive before transfer
+    /// 
+    /// # Parameters:
+    /// - `owner`: Address of the goal owner
+    /// - `goal_id`: ID of the goal to cancel
+    pub fn cancel_goal(env: Env, owner: Address, goal_id: u64) -> Result<i128, Error> {
+        owner.require_auth();
+
+        with_reentrancy_guard(&env, || {
+            let mut goal: SavingsGoal = env
+                .storage()
+                .persistent()
+                .get(&StorageKey::Goal(owner.clone(), goal_id))
+                .ok_or(Error::GoalNotFound)?;
+
+            if !goal.is_active {
+                return Err(Error::GoalInactive);
+            }
+
+            let grace_end = goal
+                .created_at
+                .checked_add(CANCEL_GRACE_PERIOD)
+                .ok_or(Error::Overflow)?;
+            if goal.restarted || env.ledger().timestamp() > grace_end {
+                return Err(Error::GracePeriodExpired);
+            }
+
+            let before = goal.clone();
+            let principal = goal.principal;
+
+            // Security: Mark goal as inactive before transfer
+            goal.is_active = false;
+            goal.withdrawn_at = env.ledger().timestamp();
+            goal.accrued_interest = 0;
+            save_goal(&env, &StorageKey::Goal(owner.clone(), goal_id), &goal);
+            track_goal_change(&env, Some(&before), &goal)?;
+            clear_pending_schedules(&env, &owner, goal_id);
+            release_prepaid(&env, &goal, true)?;
+
+            let token = goal_token_client(&env, &goal.token)?;
+            token.transfer(&env.current_contract_address(), &owner, &principal);
+            record_history(&env, &owner, goal_id, GoalAction::Withdrawal, principal);
+
+            publish_event(
+                &env,
+                (symbol_short!("cancelled"), owner.clone(), goal_id),
+                principal,
+            );
+
+            Ok(principal)
+        })
+    }
+
+    /// Redeem all interest owed to `owner` from principal-only withdrawals
+    /// 
+    /// # Security:
+    /// - Requires owner authorization
+    /// - Paid from the reserve; fails with `InsufficientReserve` until it can
+    ///   cover the full IOU
+    pub fn claim_iou(env: Env, owner: Address) -> Result<i128, Error> {
+        owner.require_auth();
+
+        with_reentrancy_guard(&env, || {
+            let iou_key = StorageKey::Iou(owner.clone());
+            let iou: i128 = env.storage().persistent().get(&iou_key).unwrap_or(0);
+            if iou == 0 {
+                return Err(Error::NoIou);
+            }
+
+            // IOUs only arise on default-token goals
+            let token_address = default_token(&env)?;
+            let reserve_key = reserve_key(&env, &token_address);
+            let reserve: i128 = env.storage().instance().get(&reserve_key).unwrap_or(0);
+            if reserve < iou {
+                return Err(Error::InsufficientReserve);
+            }
+
+            // Security: Clear the IOU before transfer
+            env.storage().persistent().remove(&iou_key);
+            adjust_total(&env, &StorageKey::TotalIou, -iou)?;
+            env.storage().instance().set(&reserve_key, &(reserve - iou));
+            credit_lifetime_interest(&env, &owner, iou)?;
+
+            let token = goal_token_client(&env, &token_address)?;
+            token.transfer(&env.current_contract_address(), &owner, &iou);
+
+            Ok(iou)
+        })
+    }
+
+    /// Get the interest owed to `owner` from principal-only withdrawals
+    /// 
+    /// # Security:
+    /// - Read-only function
+    pub fn get_iou(env: Env, owner: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&StorageKey::Iou(owner))
+            .unwrap_or(0)
+    }
+
+    /// Get the total interest paid out to `owner` across all their goals
+    /// 
+    /// Counts interest from matured withdrawals, installments and IOU claims;
+    /// interest paid on emergency withdrawals is excluded.
+    /// 
+    /// # Security:
+    /// - Read-only function
+    pub fn get_lifetime_interest(env: Env, owner: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&StorageKey::LifetimeInterest(owner))
+            .unwrap_or(0)
+    }
+
+    /// Emergency withdrawal with penalty before unlock time
+    /// 
+    /// # Security:
+    /// - Requires owner authorization
+    /// - Applies penalty to discourage misuse
     /// - Compounds interest before calculating penalty
     /// - Marks goal as inactive to prevent double withdrawal
-    /// - Admin receives penalty as contract revenue
+    /// - The treasury (admin by default) receives the penalty as contract
+    ///   revenue, minus the reserve share held back for `rebalance_reserve`
+    /// - Accrued interest is paid only up to what the reserve holds (prepaid
+    ///   goals use their allocation); the rest is forfeited
+    /// 
+    /// # Parameters:
+    /// - `owner`: Address of the goal owner
+    /// - `goal_id`: ID of the goal to withdraw from
+    pub fn emergency_withdraw(env: Env, owner: Address, goal_id: u64) -> Result<i128, Error> {
+        // Security: Require authorization
+        owner.require_auth();
+
+        with_reentrancy_guard(&env, || emergency_exit(&env, &owner, goal_id))
+    }
+
+    /// Emergency-withdraw part of a goal, penalizing only the amount taken
+    /// 
+    /// # Security:
+    /// - Requires owner authorization
+    /// - Compounds first, then takes `amount` from payable interest before
+    ///   principal; the goal stays active with the rest of its balance and
+    ///   its schedules
+    /// - The penalty is computed on `amount` at the same rate as
+    ///   `emergency_withdraw` and routed the same way; the owner receives
+    ///   `amount - penalty`
+    /// - `amount` can't exceed the principal plus the interest the reserve
+    ///   can pay (`AmountExceedsBalance`); withdrawing exactly that closes the
+    ///   goal like `emergency_withdraw`
+    /// - Starts the emergency cooldown, like a full emergency withdrawal
+    /// 
+    /// # Returns:
+    /// - The amount paid to the owner after the penalty
+    pub fn emergency_withdraw_partial(
+        env: Env,
+        owner: Address,
+        goal_id: u64,
+        amount: i128,
+    ) -> Result<i128, Error> {
+        owner.require_auth();
+
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        with_reentrancy_guard(&env, || {
+            Self::compound_interest(env.clone(), owner.clone(), goal_id)?;
+
+            let key = StorageKey::Goal(owner.clone(), goal_id);
+            let mut goal: SavingsGoal = env
+                .storage()
+                .persistent()
+                .get(&key)
+                .ok_or(Error::GoalNotFound)?;
+            if !goal.is_active {
+                return Err(Error::AlreadyWithdrawn);
+            }
+            let before = goal.clone();
+            ensure_interest_realized(&env, &goal)?;
+
+            let available_interest = payable_interest(&env, &goal)?;
+            let available = goal
+                .principal
+                .checked_add(available_interest)
+                .ok_or(Error::Overflow)?;
+            if amount > available {
+                return Err(Error::AmountExceedsBalance);
+            }
+            if amount == available {
+                return emergency_exit(&env, &owner, goal_id);
+            }
+
+            let from_interest = amount.min(available_interest);
+            let from_principal = amount.checked_sub(from_interest).ok_or(Error::Underflow)?;
+            let penalty = emergency_penalty(&env, &goal, from_principal, from_interest)?;
+            let paid = amount.checked_sub(penalty).ok_or(Error::Underflow)?;
+
+            // Security: Update state before transfers
+            fund_interest(&env, &mut goal, from_interest)?;
+            goal.accrued_interest -= from_interest;
+            goal.principal -= from_principal;
+            save_goal(&env, &key, &goal);
+            track_goal_change(&env, Some(&before), &goal)?;
+
+            pay_from_goal(&env, &goal, &owner, paid, from_interest)?;
+            distribute_penalty(&env, &goal, penalty)?;
+            record_history(&env, &owner, goal_id, GoalAction::Withdrawal, amount);
+
+            env.storage().persistent().set(
+                &StorageKey::LastEmergencyWithdraw(owner.clone()),
+                &env.ledger().timestamp(),
+            );
+            publish_withdrawn(&env, &owner, goal_id, paid, penalty > 0, &owner);
+
+            Ok(paid)
+        })
+    }
+
+    /// Preview an emergency withdrawal without performing it
+    /// 
+    /// # Security:
+    /// - Read-only function, doesn't modify state or move tokens
+    /// - Same math as `emergency_withdraw`, with interest projected to now
+    /// - Fails with `GoalNotFound`/`AlreadyWithdrawn` like `emergency_withdraw`
+    /// 
+    /// # Returns:
+    /// - `(withdrawal_amount, penalty)` the owner would receive and lose
+    pub fn quote_emergency_withdraw(
+        env: Env,
+        owner: Address,
+        goal_id: u64,
+    ) -> Result<(i128, i128), Error> {
+        let mut goal: SavingsGoal = env
+            .storage()
+            .persistent()
+            .get(&StorageKey::Goal(owner, goal_id))
+            .ok_or(Error::GoalNotFound)?;
+
+        if !goal.is_active {
+            return Err(Error::AlreadyWithdrawn);
+        }
+
+        goal.accrued_interest = goal
+            .accrued_interest
+            .checked_add(pending_interest(&env, &goal)?)
+            .ok_or(Error::Overflow)?;
+        let (_, withdrawal_amount, penalty) = emergency_payout(&env, &goal)?;
+
+        Ok((withdrawal_amount, penalty))
+    }
+
+    /// Set up installment releases for a goal once it matures
+    /// 
+    /// # Security:
+    /// - Requires owner authorization
+    /// - Replaces any existing schedule for the goal
+    /// - The first installment is due at unlock time (or immediately if already matured)
+    /// 
+    /// # Parameters:
+    /// - `owner`: Address of the goal owner
+    /// - `goal_id`: ID of the goal to draw down
+    /// - `installment`: Amount released per claim
+    /// - `interval`: Seconds between claims
+    pub fn setup_drawdown(
+        env: Env,
+        owner: Address,
+        goal_id: u64,
+        installment: i128,
+        interval: u64,
+    ) -> Result<(), Error> {
+        owner.require_auth();
+
+        if installment <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        if interval == 0 {
+            return Err(Error::InvalidDuration);
+        }
+
+        let goal: SavingsGoal = env
+            .storage()
+            .persistent()
+            .get(&StorageKey::Goal(owner.clone(), goal_id))
+            .ok_or(Error::GoalNotFound)?;
+
+        if !goal.is_active {
+            return Err(Error::GoalInactive);
+        }
+
+        let drawdown = Drawdown {
+            installment,
+            interval,
+            next_installment_time: goal.unlock_time.max(env.ledger().timestamp()),
+        };
+        env.storage()
+            .persistent()
+            .set(&StorageKey::Drawdown(owner, goal_id), &drawdown);
+
+        Ok(())
+    }
+
+    /// Claim the next installment of a matured goal's drawdown
+    /// 
+    /// # Security:
+    /// - Requires owner authorization
+    /// - Rejects claims before `next_installment_time`
+    /// - With a withdraw cooldown configured, a request must be pending and
+    ///   its cooldown elapsed, as for `withdraw_principal_only`
+    /// - Compounds interest first; the remainder keeps accruing
+    /// - Releases at most one installment per claim and deactivates the goal
+    ///   once its balance is exhausted
+    /// 
+    /// # Parameters:
+    /// - `owner`: Address of the goal owner
+    /// - `goal_id`: ID of the goal to claim from
+    pub fn claim_installment(env: Env, owner: Address, goal_id: u64) -> Result<i128, Error> {
+        owner.require_auth();
+
+        with_reentrancy_guard(&env, || {
+            let drawdown_key = StorageKey::Drawdown(owner.clone(), goal_id);
+            let mut drawdown: Drawdown = env
+                .storage()
+                .persistent()
+                .get(&drawdown_key)
+                .ok_or(Error::NoDrawdown)?;
+
+            Self::compound_interest(env.clone(), owner.clone(), goal_id)?;
+
+            let goal_key = StorageKey::Goal(owner.clone(), goal_id);
+            let mut goal: SavingsGoal = env
+                .storage()
+                .persistent()
+                .get(&goal_key)
+                .ok_or(Error::GoalNotFound)?;
+            let before = goal.clone();
+            ensure_interest_realized(&env, &goal)?;
+
+            let current_time = env.ledger().timestamp();
+
+            // Security: Ensure lock period has passed
+            if current_time < goal.unlock_time {
+                return Err(Error::StillLocked);
+            }
+
+            if current_time < drawdown.next_installment_time {
+                return Err(Error::InstallmentNotDue);
+            }
+            ensure_cooldown_elapsed(&env, &owner, goal_id)?;
+
+            let total_balance = goal
+                .principal
+                .checked_add(goal.accrued_interest)
+                .ok_or(Error::Overflow)?;
+            let amount = drawdown.installment.min(total_balance);
+
+            let from_interest = amount.min(goal.accrued_interest);
+            fund_interest(&env, &mut goal, from_interest)?;
+            deduct_from_balance(&mut goal, amount)?;
+            if goal.principal == 0 && goal.accrued_interest == 0 {
+                goal.is_active = false;
+                goal.withdrawn_at = env.ledger().timestamp();
+                env.storage().persistent().remove(&drawdown_key);
+                release_prepaid(&env, &goal, true)?;
+            } else {
+                drawdown.next_installment_time = drawdown
+                    .next_installment_time
+                    .checked_add(drawdown.interval)
+                    .ok_or(Error::Overflow)?;
+                env.storage().persistent().set(&drawdown_key, &drawdown);
+            }
+
+            // Security: Update state before transfer
+            save_goal(&env, &goal_key, &goal);
+            track_goal_change(&env, Some(&before), &goal)?;
+            credit_lifetime_interest(&env, &owner, from_interest)?;
+
+            pay_from_goal(&env, &goal, &owner, amount, from_interest)?;
+            record_history(&env, &owner, goal_id, GoalAction::Withdrawal, amount);
+
+            Ok(amount)
+        })
+    }
+
+    /// Get a goal's drawdown schedule, if any
+    /// 
+    /// # Security:
+    /// - Read-only function
+    pub fn get_drawdown(env: Env, owner: Address, goal_id: u64) -> Option<Drawdown> {
+        env.storage()
+            .persistent()
+            .get(&StorageKey::Drawdown(owner, goal_id))
+    }
+
+    /// Find active goals whose interest hasn't been compounded recently
+    /// 
+    /// # Security:
+    /// - Read-only function
+    /// - Scans at most `MAX_PAGE_LIMIT` IDs per call to bound read cost
+    /// 
+    /// # Parameters:
+    /// - `threshold_seconds`: Return goals last compounded more than this long ago
+    /// - `start_id`: First global goal ID to scan (pagination cursor)
+    /// - `limit`: Number of IDs to scan; continue from `start_id + limit`
+    pub fn get_stale_goals(
+        env: Env,
+        threshold_seconds: u64,
+        start_id: u64,
+        limit: u32,
+    ) -> Vec<(Address, u64)> {
+        let current_time = env.ledger().timestamp();
+        let mut stale = Vec::new(&env);
+
+        for goal_id in page_range(&env, start_id, limit) {
+            if let Some((owner, goal)) = load_indexed_goal(&env, goal_id) {
+                if goal.is_active
+                    && current_time.saturating_sub(goal.last_compound_time) > threshold_seconds
+                {
+                    stale.push_back((owner, goal_id));
+                }
+            }
+        }
+
+        stale
+    }
+
+    /// Opt a goal in or out of automatic renewal at maturity
+    /// 
+    /// # Security:
+    /// - Requires owner authorization
+    pub fn set_auto_renew(
+        env: Env,
+        owner: Address,
+        goal_id: u64,
+        auto_renew: bool,
+    ) -> Result<(), Error> {
+        owner.require_auth();
+
+        let key = StorageKey::Goal(owner, goal_id);
+        let mut goal: SavingsGoal = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(Error::GoalNotFound)?;
+
+        if !goal.is_active {
+            return Err(Error::GoalInactive);
+        }
+
+        goal.auto_renew = auto_renew;
+        save_goal(&env, &key, &goal);
+
+        Ok(())
+    }
+
+    /// Renew a matured auto-renew goal for another term
+    /// 
+    /// # Security:
+    /// - Can be called by anyone (keepers); the owner opted in via `set_auto_renew`
+    /// - Compounds interest and rolls it into principal
+    /// - Restarts the lock for the same duration at the same rate; no tokens move
+    /// - Drops any pending withdrawal request or drawdown for the old term
+    /// 
+    /// # Parameters:
+    /// - `owner`: Address of the goal owner
+    /// - `goal_id`: ID of the goal to renew
+    /// 
+    /// Returns the new unlock time.
+    pub fn process_renewal(env: Env, owner: Address, goal_id: u64) -> Result<u64, Error> {
+        Self::compound_interest(env.clone(), owner.clone(), goal_id)?;
+
+        let key = StorageKey::Goal(owner.clone(), goal_id);
+        let mut goal: SavingsGoal = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(Error::GoalNotFound)?;
+        let before = goal.clone();
+
+        if !goal.auto_renew {
+            return Err(Error::NotRenewable);
+        }
+
+        let current_time = env.ledger().timestamp();
+        if current_time < goal.unlock_time {
+            return Err(Error::StillLocked);
+        }
+
+        let lock_duration = goal.lock_duration;
+        start_new_term(&env, &mut goal, lock_duration)?;
+
+        save_goal(&env, &key, &goal);
+        track_goal_change(&env, Some(&before), &goal)?;
+        clear_pending_schedules(&env, &owner, goal_id);
+
+        Ok(goal.unlock_time)
+    }
+
+    /// Re-lock a matured goal for a new term without withdrawing
+    /// 
+    /// # Security:
+    /// - Requires owner authorization
+    /// - Validates the new duration and rate (including rate tiers) like
+    ///   `create_goal`
+    /// - Compounds interest and rolls it into principal; no tokens move
+    /// - Drops any pending withdrawal request or drawdown for the old term
+    /// 
+    /// # Parameters:
+    /// - `owner`: Address of the goal owner
+    /// - `goal_id`: ID of the matured goal
+    /// - `new_duration`: Lock duration of the new term (in seconds)
+    /// - `new_rate`: Annual interest rate in basis points for the new term
+    /// 
+    /// Returns the new unlock time.
+    pub fn relock(
+        env: Env,
+        owner: Address,
+        goal_id: u64,
+        new_duration: u64,
+        new_rate: u32,
+    ) -> Result<u64, Error> {
+        owner.require_auth();
+
+        if !(MIN_LOCK_DURATION..=MAX_LOCK_DURATION).contains(&new_duration) {
+            return Err(Error::InvalidDuration);
+        }
+
+        Self::compound_interest(env.clone(), owner.clone(), goal_id)?;
+
+        let key = StorageKey::Goal(owner.clone(), goal_id);
+        let mut goal: SavingsGoal = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(Error::GoalNotFound)?;
+        let before = goal.clone();
+
+        if env.ledger().timestamp() < goal.unlock_time {
+            return Err(Error::StillLocked);
+        }
+
+        start_new_term(&env, &mut goal, new_duration)?;
+        renegotiate_rate(&env, &before, &mut goal, new_rate)?;
+
+        save_goal(&env, &key, &goal);
+        clear_pending_schedules(&env, &owner, goal_id);
+
+        Ok(goal.unlock_time)
+    }
+
+    /// Admin function to lower an active goal's interest rate, e.g. when a
+    /// promotional rate ends
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    /// - Compounds interest at the old rate up to now first, so interest
+    ///   already earned is kept
+    /// - The rate can only go down: `new_rate` must be strictly below the
+    ///   current rate (`RateTooHigh` otherwise)
+    /// - Publishes a `rate_cut` event with the old and new rate
+    pub fn admin_reduce_rate(
+        env: Env,
+        admin: Address,
+        owner: Address,
+        goal_id: u64,
+        new_rate: u32,
+    ) -> Result<(), Error> {
+        require_admin(&env, &admin)?;
+
+        Self::compound_interest(env.clone(), owner.clone(), goal_id)?;
+
+        let key = StorageKey::Goal(owner.clone(), goal_id);
+        let mut goal: SavingsGoal = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(Error::GoalNotFound)?;
+        let before = goal.clone();
+
+        reduce_rate(&env, &before, &mut goal, new_rate)?;
+        save_goal(&env, &key, &goal);
+
+        publish_event(
+            &env,
+            (symbol_short!("rate_cut"), owner, goal_id),
+            (before.interest_rate, new_rate),
+        );
+
+        Ok(())
+    }
+
+    /// Push a goal's unlock time further out, keeping its ID and history
+    /// 
+    /// # Security:
+    /// - Requires owner authorization
+    /// - Compounds interest first
+    /// - `additional_duration` must be at least `MIN_LOCK_DURATION`, and the
+    ///   resulting total lock (from `start_time`) at most `MAX_LOCK_DURATION`
+    /// - A goal that has already matured is extended from now rather than from
+    ///   its past unlock time, so the extension always re-locks the funds
+    /// - The new unlock time is always in the future, so any pending
+    ///   withdrawal request or drawdown is dropped as for `relock`
+    /// 
+    /// # Parameters:
+    /// - `owner`: Address of the goal owner
+    /// - `goal_id`: ID of the goal to extend
+    /// - `additional_duration`: Seconds to add to the lock
+    /// 
+    /// Returns the new unlock time.
+    pub fn extend_lock(
+        env: Env,
+        owner: Address,
+        goal_id: u64,
+        additional_duration: u64,
+    ) -> Result<u64, Error> {
+        owner.require_auth();
+
+        if additional_duration < MIN_LOCK_DURATION {
+            return Err(Error::InvalidDuration);
+        }
+
+        Self::compound_interest(env.clone(), owner.clone(), goal_id)?;
+
+        let key = StorageKey::Goal(owner.clone(), goal_id);
+        let mut goal: SavingsGoal = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(Error::GoalNotFound)?;
+        let before = goal.clone();
+
+        let new_unlock_time = goal
+            .unlock_time
+            .max(env.ledger().timestamp())
+            .checked_add(additional_duration)
+            .ok_or(Error::Overflow)?;
+        let new_lock_duration = new_unlock_time
+            .checked_sub(goal.start_time)
+            .ok_or(Error::TimeError)?;
+        if new_lock_duration > MAX_LOCK_DURATION {
+            return Err(Error::InvalidDuration);
+        }
+
+        goal.unlock_time = new_unlock_time;
+        goal.lock_duration = new_lock_duration;
+        goal.maturity_notified = false;
+
+        save_goal(&env, &key, &goal);
+        track_goal_change(&env, Some(&before), &goal)?;
+        clear_pending_schedules(&env, &owner, goal_id);
+
+        publish_event(
+            &env,
+            (symbol_short!("extended"), owner, goal_id),
+            new_unlock_time,
+        );
+
+        Ok(new_unlock_time)
+    }
+
+    /// Find matured active goals opted into auto-renewal
+    /// 
+    /// # Security:
+    /// - Read-only function
+    /// - Scans at most `MAX_PAGE_LIMIT` IDs per call to bound read cost
+    /// 
+    /// # Parameters:
+    /// - `start_id`: First global goal ID to scan (pagination cursor)
+    /// - `limit`: Number of IDs to scan; continue from `start_id + limit`
+    pub fn get_renewable_goals(env: Env, start_id: u64, limit: u32) -> Vec<(Address, u64)> {
+        let current_time = env.ledger().timestamp();
+        let mut renewable = Vec::new(&env);
+
+        for goal_id in page_range(&env, start_id, limit) {
+            if let Some((owner, goal)) = load_indexed_goal(&env, goal_id) {
+                if goal.is_active && goal.auto_renew && current_time >= goal.unlock_time {
+                    renewable.push_back((owner, goal_id));
+                }
+            }
+        }
+
+        renewable
+    }
+
+    /// Get details of a specific savings goal
+    /// 
+    /// # Security:
+    /// - Read-only function, no state changes
+    /// - Anyone can view goal details (transparency)
+    pub fn get_goal(env: Env, owner: Address, goal_id: u64) -> Result<SavingsGoal, Error> {
+        env.storage()
+            .persistent()
+            .get(&StorageKey::Goal(owner, goal_id))
+            .ok_or(Error::GoalNotFound)
+    }
+
+    /// Get several of an owner's goals in one call
+    /// 
+    /// # Security:
+    /// - Read-only function, no state changes
+    /// - At most `MAX_PAGE_LIMIT` IDs per call to bound read cost
+    ///   (`InvalidBatch` otherwise)
+    /// 
+    /// # Returns:
+    /// - One entry per requested ID, in order; `None` where the owner has no
+    ///   goal with that ID
+    pub fn get_goals(
+        env: Env,
+        owner: Address,
+        goal_ids: Vec<u64>,
+    ) -> Result<Vec<Option<SavingsGoal>>, Error> {
+        if goal_ids.len() > MAX_PAGE_LIMIT {
+            return Err(Error::InvalidBatch);
+        }
+
+        let mut goals = Vec::new(&env);
+        for goal_id in goal_ids {
+            goals.push_back(
+                env.storage()
+                    .persistent()
+                    .get(&StorageKey::Goal(owner.clone(), goal_id)),
+            );
+        }
+
+        Ok(goals)
+    }
+
+    /// Attach or clear a reference to an external position (e.g. an NFT)
+    /// 
+    /// # Security:
+    /// - Requires owner authorization
+    /// - Purely informational: no tokens are transferred or locked
+    /// 
+    /// # Parameters:
+    /// - `owner`: Address of the goal owner
+    /// - `goal_id`: ID of the goal to update
+    /// - `linked_asset`: Contract and token ID of the position, or `LinkedAsset::None` to clear
+    pub fn set_linked_asset(
+        env: Env,
+        owner: Address,
+        goal_id: u64,
+        linked_asset: LinkedAsset,
+    ) -> Result<(), Error> {
+        owner.require_auth();
+
+        let key = StorageKey::Goal(owner, goal_id);
+        let mut goal: SavingsGoal = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(Error::GoalNotFound)?;
+
+        if !goal.is_active {
+            return Err(Error::GoalInactive);
+        }
+
+        goal.linked_asset = linked_asset;
+        save_goal(&env, &key, &goal);
+
+        Ok(())
+    }
+
+    /// Admin function to set whether interest keeps accruing past unlock
+    /// time for goals without their own setting
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    /// - Applies from each goal's next compounding; interest already
+    ///   compounded is kept
+    /// - Defaults to `true` (accrue until withdrawal)
+    pub fn set_accrue_after_unlock(env: Env, admin: Address, accrue: bool) -> Result<(), Error> {
+        require_admin(&env, &admin)?;
+
+        env.storage()
+            .instance()
+            .set(&StorageKey::AccrueAfterUnlock, &accrue);
+
+        Ok(())
+    }
+
+    /// Admin function to override whether one goal accrues past unlock time
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    /// - Compounds interest under the old setting first
+    /// 
+    /// # Parameters:
+    /// - `admin`: Admin address (must authorize)
+    /// - `owner`: Address of the goal owner
+    /// - `goal_id`: ID of the goal
+    /// - `accrue`: The goal's setting; `None` follows the contract-wide one
+    pub fn set_goal_accrue_after_unlock(
+        env: Env,
+        admin: Address,
+        owner: Address,
+        goal_id: u64,
+        accrue: Option<bool>,
+    ) -> Result<(), Error> {
+        require_admin(&env, &admin)?;
+
+        Self::compound_interest(env.clone(), owner.clone(), goal_id)?;
+
+        let key = StorageKey::Goal(owner, goal_id);
+        let mut goal: SavingsGoal = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(Error::GoalNotFound)?;
+
+        goal.accrue_after_unlock = accrue;
+        save_goal(&env, &key, &goal);
+
+        Ok(())
+    }
+
+    /// Admin function to delay when a goal starts accruing interest, for
+    /// cliff-then-accrue schedules
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    /// - Compounds interest under the old offset first; time already
+    ///   compounded is never revisited
+    /// - The offset can't exceed the goal's lock duration (`InvalidDuration`)
+    /// 
+    /// # Parameters:
+    /// - `admin`: Admin address (must authorize)
+    /// - `owner`: Address of the goal owner
+    /// - `goal_id`: ID of the goal
+    /// - `offset`: Seconds after `start_time` before interest accrues
+    pub fn set_accrual_start_offset(
+        env: Env,
+        admin: Address,
+        owner: Address,
+        goal_id: u64,
+        offset: u64,
+    ) -> Result<(), Error> {
+        require_admin(&env, &admin)?;
+
+        Self::compound_interest(env.clone(), owner.clone(), goal_id)?;
+
+        let key = StorageKey::Goal(owner, goal_id);
+        let mut goal: SavingsGoal = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(Error::GoalNotFound)?;
+
+        if offset > goal.lock_duration {
+            return Err(Error::InvalidDuration);
+        }
+        goal.accrual_start_offset = offset;
+        save_goal(&env, &key, &goal);
+
+        Ok(())
+    }
+
+    /// Switch how a goal's interest accrues
+    /// 
+    /// # Security:
+    /// - Requires owner authorization
+    /// - Compounds interest in the old mode first, so the new mode only
+    ///   applies from now on
+    /// 
+    /// # Parameters:
+    /// - `owner`: Address of the goal owner
+    /// - `goal_id`: ID of the goal
+    /// - `mode`: `Linear` (default) or `Periodic` (daily compounding)
+    pub fn set_compound_mode(
+        env: Env,
+        owner: Address,
+        goal_id: u64,
+        mode: CompoundMode,
+    ) -> Result<(), Error> {
+        owner.require_auth();
+
+        Self::compound_interest(env.clone(), owner.clone(), goal_id)?;
+
+        let key = StorageKey::Goal(owner, goal_id);
+        let mut goal: SavingsGoal = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(Error::GoalNotFound)?;
+
+        goal.compound_mode = mode;
+        save_goal(&env, &key, &goal);
+
+        Ok(())
+    }
+
+    /// Freeze a goal's interest accrual without touching its funds
+    /// 
+    /// # Security:
+    /// - Requires owner authorization
+    /// - Compounds interest up to now first; while frozen, compounding only
+    ///   advances `last_compound_time`, so the frozen time never earns
+    ///   interest, even after `unfreeze_goal`
+    /// - Withdrawals are unaffected and still follow the unlock time
+    pub fn freeze_goal(env: Env, owner: Address, goal_id: u64) -> Result<(), Error> {
+        owner.require_auth();
+
+        Self::compound_interest(env.clone(), owner.clone(), goal_id)?;
+
+        let key = StorageKey::Goal(owner, goal_id);
+        let mut goal: SavingsGoal = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(Error::GoalNotFound)?;
+
+        goal.frozen = true;
+        save_goal(&env, &key, &goal);
+
+        Ok(())
+    }
+
+    /// Resume interest accrual on a frozen goal from now on
+    /// 
+    /// # Security:
+    /// - Requires owner authorization
+    /// - Compounds first, which forfeits the time spent frozen
+    pub fn unfreeze_goal(env: Env, owner: Address, goal_id: u64) -> Result<(), Error> {
+        owner.require_auth();
+
+        Self::compound_interest(env.clone(), owner.clone(), goal_id)?;
+
+        let key = StorageKey::Goal(owner, goal_id);
+        let mut goal: SavingsGoal = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(Error::GoalNotFound)?;
+
+        goal.frozen = false;
+        save_goal(&env, &key, &goal);
+
+        Ok(())
+    }
+
+    /// Move a locked goal to another owner without unlocking it
+    /// 
+    /// # Security:
+    /// - Requires authorization from the current owner
+    /// - The goal keeps its ID, balance, rate and unlock time; the recipient
+    ///   becomes the only address able to withdraw it
+    /// - Any beneficiary is cleared; a pending withdrawal request, drawdown
+    ///   schedule and the goal's history move with it
+    /// - Fails with `GoalExists` if `to` already holds a goal under this ID
+    ///   (including `to == from`)
+    /// - `to` must pass the same whitelist and `MaxGoalsPerUser` checks as a
+    ///   new goal's owner
+    /// 
+    /// # Parameters:
+    /// - `from`: Current owner
+    /// - `to`: New owner
+    /// - `goal_id`: ID of the goal to transfer
+    pub fn transfer_goal(env: Env, from: Address, to: Address, goal_id: u64) -> Result<(), Error> {
+        from.require_auth();
+
+        let from_key = StorageKey::Goal(from.clone(), goal_id);
+        let mut goal: SavingsGoal = env
+            .storage()
+            .persistent()
+            .get(&from_key)
+            .ok_or(Error::GoalNotFound)?;
+
+        if !goal.is_active {
+            return Err(Error::GoalInactive);
+        }
+
+        let to_key = StorageKey::Goal(to.clone(), goal_id);
+        let storage = env.storage().persistent();
+        if storage.has(&to_key) {
+            return Err(Error::GoalExists);
+        }
+        ensure_whitelisted(&env, &to)?;
+        ensure_goal_slot_free(&env, &to)?;
+
+        goal.owner = to.clone();
+        goal.beneficiary = None;
+        storage.remove(&from_key);
+        storage.set(&StorageKey::GoalOwner(goal_id), &to);
+        save_goal(&env, &to_key, &goal);
+
+        // Schedules are keyed by owner, so they follow the goal
+        let request_key = StorageKey::WithdrawRequest(from.clone(), goal_id);
+        if let Some(requested_at) = storage.get::<_, u64>(&request_key) {
+            storage.remove(&request_key);
+            storage.set(&StorageKey::WithdrawRequest(to.clone(), goal_id), &requested_at);
+        }
+        let drawdown_key = StorageKey::Drawdown(from.clone(), goal_id);
+        if let Some(drawdown) = storage.get::<_, Drawdown>(&drawdown_key) {
+            storage.remove(&drawdown_key);
+            storage.set(&StorageKey::Drawdown(to.clone(), goal_id), &drawdown);
+        }
+        let history_key = StorageKey::GoalHistory(from.clone(), goal_id);
+        if let Some(history) = storage.get::<_, Vec<HistoryEntry>>(&history_key) {
+            storage.remove(&history_key);
+            storage.set(&StorageKey::GoalHistory(to.clone(), goal_id), &history);
+        }
+
+        // Move the goal between the owners' indexes
+        let from_ids_key = StorageKey::UserGoalIds(from.clone());
+        let from_ids: Vec<u64> = storage.get(&from_ids_key).unwrap_or(Vec::new(&env));
+        if let Some(index) = from_ids.first_index_of(goal_id) {
+            let mut from_ids = from_ids;
+            from_ids.remove(index);
+            storage.set(&from_ids_key, &from_ids);
+        }
+        let to_ids_key = StorageKey::UserGoalIds(to.clone());
+        let mut to_ids: Vec<u64> = storage.get(&to_ids_key).unwrap_or(Vec::new(&env));
+        to_ids.push_back(goal_id);
+        storage.set(&to_ids_key, &to_ids);
+
+        let from_count: u64 = storage
+            .get(&StorageKey::UserGoalCount(from.clone()))
+            .unwrap_or(0);
+        storage.set(
+            &StorageKey::UserGoalCount(from.clone()),
+            &from_count.checked_sub(1).ok_or(Error::Underflow)?,
+        );
+        let to_count: u64 = storage
+            .get(&StorageKey::UserGoalCount(to.clone()))
+            .unwrap_or(0);
+        storage.set(&StorageKey::UserGoalCount(to.clone()), &(to_count + 1));
+        adjust_user_active_goals(&env, &from, false)?;
+        adjust_user_active_goals(&env, &to, true)?;
+
+        publish_event(&env, (symbol_short!("transfer"), from, goal_id), to);
+
+        Ok(())
+    }
+
+    /// Carve part of a goal's principal out into a new goal with the same
+    /// terms
+    /// 
+    /// # Security:
+    /// - Requires owner authorization
+    /// - Compounds first; the new goal takes `split_amount` of principal and
+    ///   the same share of accrued (and prepaid) interest, rounded down, and
+    ///   keeps the parent's `start_time`, `unlock_time`, rate and settings
+    /// - No tokens move, so the contract's totals are unchanged
+    /// - `split_amount` must be positive and below the parent's principal
+    ///   (`InvalidAmount` / `InvalidSplit`); the per-user goal limit applies
+    /// - Both halves must meet the minimum deposit (`DepositBelowMinimum`),
+    ///   and the split counts as a creation for the per-ledger rate limit
+    /// 
+    /// # Returns:
+    /// - The new goal's ID
+    pub fn split_goal(
+        env: Env,
+        owner: Address,
+        goal_id: u64,
+        split_amount: i128,
+    ) -> Result<u64, Error> {
+        owner.require_auth();
+
+        if split_amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        Self::compound_interest(env.clone(), owner.clone(), goal_id)?;
+
+        let key = StorageKey::Goal(owner.clone(), goal_id);
+        let mut goal: SavingsGoal = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(Error::GoalNotFound)?;
+        if split_amount >= goal.principal {
+            return Err(Error::InvalidSplit);
+        }
+        ensure_min_deposit(&env, split_amount)?;
+        ensure_min_deposit(&env, goal.principal - split_amount)?;
+
+        ensure_goal_slot_free(&env, &owner)?;
+        record_creation(&env, &owner)?;
+
+        let share = |value: i128| -> Result<i128, Error> {
+            value
+                .checked_mul(split_amount)
+                .ok_or(Error::Overflow)?
+                .checked_div(goal.principal)
+                .ok_or(Error::DivisionError)
+        };
+        let mut child = goal.clone();
+        child.principal = split_amount;
+        child.accrued_interest = share(goal.accrued_interest)?;
+        child.prepaid_interest = share(goal.prepaid_interest)?;
+        child.created_ledger = env.ledger().sequence();
+        child.compound_count = 0;
+        child.deposit_count = 0;
+        child.recurring_amount = 0;
+        child.recurring_interval = 0;
+        child.target_amount = 0;
+
+        let before = goal.clone();
+        goal.principal -= child.principal;
+        goal.accrued_interest -= child.accrued_interest;
+        goal.prepaid_interest -= child.prepaid_interest;
+        save_goal(&env, &key, &goal);
+        track_goal_change(&env, Some(&before), &goal)?;
+
+        // Register the new goal under the next global ID; IDs are global for
+        // the reasons given in `store_new_goal`
+        let child_id: u64 = env
+            .storage()
+            .instance()
+            .get(&StorageKey::GoalCounter)
+            .unwrap_or(0);
+        let next_goal_id = child_id.checked_add(1).ok_or(Error::GoalOverflow)?;
+        let storage = env.storage().persistent();
+        if storage.has(&StorageKey::GoalOwner(child_id))
+            || storage.has(&StorageKey::Goal(owner.clone(), child_id))
+        {
+            return Err(Error::GoalExists);
+        }
+        storage.set(&StorageKey::GoalOwner(child_id), &owner);
+        save_goal(&env, &StorageKey::Goal(owner.clone(), child_id), &child);
+        track_goal_change(&env, None, &child)?;
+        env.storage()
+            .instance()
+            .set(&StorageKey::GoalCounter, &next_goal_id);
+
+        let count: u64 = storage
+            .get(&StorageKey::UserGoalCount(owner.clone()))
+            .unwrap_or(0);
+        storage.set(&StorageKey::UserGoalCount(owner.clone()), &(count + 1));
+        let ids_key = StorageKey::UserGoalIds(owner.clone());
+        let mut goal_ids: Vec<u64> = storage.get(&ids_key).unwrap_or(Vec::new(&env));
+        goal_ids.push_back(child_id);
+        storage.set(&ids_key, &goal_ids);
+
+        publish_event(
+            &env,
+            (symbol_short!("split"), owner, goal_id),
+            (child_id, split_amount),
+        );
+
+        Ok(child_id)
+    }
+
+    /// Fold goal `goal_id_b` into `goal_id_a`, the inverse of `split_goal`
+    /// 
+    /// # Security:
+    /// - Requires owner authorization
+    /// - Both goals must be active and share their interest rate, token,
+    ///   compound mode, reward-token and frozen settings and any accrual cliff
+    ///   still ahead; otherwise this fails with `InvalidSplit`, the closest
+    ///   existing error
+    /// - Compounds both first, then moves `goal_id_b`'s principal, accrued
+    ///   and prepaid interest into `goal_id_a` and deactivates it; any
+    ///   withdrawal request or drawdown on `goal_id_b` is dropped
+    /// - The merged goal unlocks at the later of the two unlock times, so
+    ///   neither balance becomes withdrawable earlier, and keeps the earlier
+    ///   creation time; it can no longer be cancelled with `cancel_goal`
+    /// - No tokens move, so the contract's totals are unchanged
+    pub fn merge_goals(
+        env: Env,
+        owner: Address,
+        goal_id_a: u64,
+        goal_id_b: u64,
+    ) -> Result<(), Error> {
+        owner.require_auth();
+
+        if goal_id_a == goal_id_b {
+            return Err(Error::InvalidSplit);
+        }
+
+        Self::compound_interest(env.clone(), owner.clone(), goal_id_a)?;
+        Self::compound_interest(env.clone(), owner.clone(), goal_id_b)?;
+
+        let key_a = StorageKey::Goal(owner.clone(), goal_id_a);
+        let key_b = StorageKey::Goal(owner.clone(), goal_id_b);
+        let mut goal_a: SavingsGoal = env
+            .storage()
+            .persistent()
+            .get(&key_a)
+            .ok_or(Error::GoalNotFound)?;
+        let mut goal_b: SavingsGoal = env
+            .storage()
+            .persistent()
+            .get(&key_b)
+            .ok_or(Error::GoalNotFound)?;
+
+        // Accrual cliffs still ahead must coincide
+        let cliff_a = goal_a.start_time.saturating_add(goal_a.accrual_start_offset);
+        let cliff_b = goal_b.start_time.saturating_add(goal_b.accrual_start_offset);
+        let pending_cliff = cliff_a.max(cliff_b) > env.ledger().timestamp();
+
+        if goal_a.interest_rate != goal_b.interest_rate
+            || (pending_cliff && cliff_a != cliff_b)
+            || goal_a.token != goal_b.token
+            || goal_a.compound_mode != goal_b.compound_mode
+            || goal_a.interest_in_reward != goal_b.interest_in_reward
+            || goal_a.frozen != goal_b.frozen
+        {
+            return Err(Error::InvalidSplit);
+        }
+
+        let before_a = goal_a.clone();
+        let before_b = goal_b.clone();
+        goal_a.principal = goal_a
+            .principal
+            .checked_add(goal_b.principal)
+            .ok_or(Error::Overflow)?;
+        goal_a.accrued_interest = goal_a
+            .accrued_interest
+            .checked_add(goal_b.accrued_interest)
+            .ok_or(Error::Overflow)?;
+        goal_a.prepaid_interest = goal_a
+            .prepaid_interest
+            .checked_add(goal_b.prepaid_interest)
+            .ok_or(Error::Overflow)?;
+        goal_a.restarted = true;
+        goal_a.created_at = goal_a.created_at.min(goal_b.created_at);
+        if goal_b.unlock_time > goal_a.unlock_time {
+            goal_a.lock_duration += goal_b.unlock_time - goal_a.unlock_time;
+            goal_a.unlock_time = goal_b.unlock_time;
+            goal_a.maturity_notified = false;
+        }
+
+        goal_b.is_active = false;
+        goal_b.principal = 0;
+        goal_b.accrued_interest = 0;
+        goal_b.prepaid_interest = 0;
+
+        save_goal(&env, &key_a, &goal_a);
+        save_goal(&env, &key_b, &goal_b);
+        track_goal_change(&env, Some(&before_a), &goal_a)?;
+        track_goal_change(&env, Some(&before_b), &goal_b)?;
+        clear_pending_schedules(&env, &owner, goal_id_b);
+
+        publish_event(
+            &env,
+            (symbol_short!("merged"), owner, goal_id_a),
+            goal_id_b,
+        );
+
+        Ok(())
+    }
+
+    /// Designate who can recover a goal if the owner loses access
+    /// 
+    /// # Security:
+    /// - Requires owner authorization
+    /// - The beneficiary can only claim after unlock time plus the
+    ///   beneficiary window, so an active owner always withdraws first
+    /// - `None` removes the beneficiary
+    pub fn set_beneficiary(
+        env: Env,
+        owner: Address,
+        goal_id: u64,
+        beneficiary: Option<Address>,
+    ) -> Result<(), Error> {
+        owner.require_auth();
+
+        let key = StorageKey::Goal(owner, goal_id);
+        let mut goal: SavingsGoal = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(Error::GoalNotFound)?;
+
+        if !goal.is_active {
+            return Err(Error::GoalInactive);
+        }
+
+        goal.beneficiary = beneficiary;
+        save_goal(&env, &key, &goal);
+
+        Ok(())
+    }
+
+    /// Claim a goal's matured balance as its beneficiary
+    /// 
+    /// # Security:
+    /// - Requires the beneficiary's authorization and that it matches the
+    ///   goal's designated beneficiary
+    /// - Only after unlock time plus the beneficiary window (`StillLocked`
+    ///   before that)
+    /// - With a withdraw cooldown configured, a request must be pending and
+    ///   its cooldown elapsed; the beneficiary can file it with
+    ///   `request_beneficiary_claim`
+    /// - Compounds and closes the goal like `withdraw`, paying the beneficiary
+    /// 
+    /// # Parameters:
+    /// - `beneficiary`: Designated beneficiary address
+    /// - `owner`: Address of the goal owner
+    /// - `goal_id`: ID of the goal to claim
+    pub fn claim_as_beneficiary(
+        env: Env,
+        beneficiary: Address,
+        owner: Address,
+        goal_id: u64,
+    ) -> Result<i128, Error> {
+        beneficiary.require_auth();
+
+        with_reentrancy_guard(&env, || {
+            beneficiary_goal(&env, &beneficiary, &owner, goal_id)?;
+            ensure_cooldown_elapsed(&env, &owner, goal_id)?;
+
+            settle_withdrawal_to(&env, &owner, goal_id, &beneficiary)
+        })
+    }
+
+    /// File the withdrawal request a beneficiary claim needs while a withdraw
+    /// cooldown is configured
+    /// 
+    /// # Security:
+    /// - Same authorization and window checks as `claim_as_beneficiary`, so
+    ///   the owner can no longer be expected to file it
+    /// - Records the same request `request_withdraw` would, so an owner who
+    ///   is still active can void it with `cancel_withdraw` during the
+    ///   cooldown; the beneficiary may file again afterwards
+    /// 
+    /// # Parameters:
+    /// - `beneficiary`: Designated beneficiary address
+    /// - `owner`: Address of the goal owner
+    /// - `goal_id`: ID of the goal to claim
+    /// 
+    /// Returns the timestamp from which `claim_as_beneficiary` succeeds.
+    pub fn request_beneficiary_claim(
+        env: Env,
+        beneficiary: Address,
+        owner: Address,
+        goal_id: u64,
+    ) -> Result<u64, Error> {
+        beneficiary.require_auth();
+
+        let goal = beneficiary_goal(&env, &beneficiary, &owner, goal_id)?;
+        if !goal.is_active {
+            return Err(Error::AlreadyWithdrawn);
+        }
+
+        let request_key = StorageKey::WithdrawRequest(owner, goal_id);
+        if env.storage().persistent().has(&request_key) {
+            return Err(Error::WithdrawAlreadyRequested);
+        }
+
+        let current_time = env.ledger().timestamp();
+        env.storage().persistent().set(&request_key, &current_time);
+
+        let cooldown: u64 = env
+            .storage()
+            .instance()
+            .get(&StorageKey::WithdrawCooldown)
+            .unwrap_or(0);
+
+        current_time.checked_add(cooldown).ok_or(Error::Overflow)
+    }
+
+    /// Record a recurring contribution plan for a goal
+    /// 
+    /// # Security:
+    /// - Requires owner authorization
+    /// - Metadata only: nothing is pulled automatically, each contribution is
+    ///   still an owner-authorized `deposit`
+    /// - `amount` and `interval` must both be positive, or both zero to clear
+    ///   the plan (`InvalidAmount` otherwise)
+    /// 
+    /// # Parameters:
+    /// - `owner`: Address of the goal owner
+    /// - `goal_id`: ID of the goal
+    /// - `amount`: Planned contribution per interval
+    /// - `interval`: Seconds between contributions, counted from `start_time`
+    pub fn set_recurring_plan(
+        env: Env,
+        owner: Address,
+        goal_id: u64,
+        amount: i128,
+        interval: u64,
+    ) -> Result<(), Error> {
+        owner.require_auth();
+
+        if amount < 0 || (amount == 0) != (interval == 0) {
+            return Err(Error::InvalidAmount);
+        }
+
+        let key = StorageKey::Goal(owner, goal_id);
+        let mut goal: SavingsGoal = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(Error::GoalNotFound)?;
+
+        if !goal.is_active {
+            return Err(Error::GoalInactive);
+        }
+
+        goal.recurring_amount = amount;
+        goal.recurring_interval = interval;
+        save_goal(&env, &key, &goal);
+
+        Ok(())
+    }
+
+    /// Get when a goal's next planned contribution is due
+    /// 
+    /// # Security:
+    /// - Read-only function
+    /// - `start_time + (deposit_count + 1) * recurring_interval`, so each
+    ///   `deposit` this term advances the schedule by one interval
+    /// - Zero when the goal has no plan or is inactive
+    pub fn next_contribution_due(env: Env, owner: Address, goal_id: u64) -> Result<u64, Error> {
+        let goal: SavingsGoal = env
+            .storage()
+            .persistent()
+            .get(&StorageKey::Goal(owner, goal_id))
+            .ok_or(Error::GoalNotFound)?;
+
+        if !goal.is_active || goal.recurring_interval == 0 {
+            return Ok(0);
+        }
+
+        (goal.deposit_count as u64)
+            .checked_add(1)
+            .and_then(|count| count.checked_mul(goal.recurring_interval))
+            .and_then(|offset| goal.start_time.checked_add(offset))
+            .ok_or(Error::Overflow)
+    }
+
+    /// Whether a goal's planned contribution is past due
+    /// 
+    /// # Security:
+    /// - Read-only function
+    /// - `false` when the goal has no plan or is inactive
+    pub fn is_contribution_overdue(env: Env, owner: Address, goal_id: u64) -> Result<bool, Error> {
+        let due = Self::next_contribution_due(env.clone(), owner, goal_id)?;
+
+        Ok(due != 0 && env.ledger().timestamp() > due)
+    }
+
+    /// Rename a goal
+    /// 
+    /// # Security:
+    /// - Requires owner authorization
+    /// - Labels are metadata only and at most 32 bytes; an empty label
+    ///   clears it
+    pub fn set_goal_label(
+        env: Env,
+        owner: Address,
+        goal_id: u64,
+        label: String,
+    ) -> Result<(), Error> {
+        owner.require_auth();
+        ensure_label_valid(&label)?;
+
+        let key = StorageKey::Goal(owner, goal_id);
+        let mut goal: SavingsGoal = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(Error::GoalNotFound)?;
+
+        goal.label = if label.is_empty() { None } else { Some(label) };
+        save_goal(&env, &key, &goal);
+
+        Ok(())
+    }
+
+    /// Get the ledger timestamp the contract uses for unlock checks, so
+    /// clients can align countdowns with it
+    /// 
+    /// # Security:
+    /// - Read-only function
+    pub fn get_ledger_time(env: Env) -> u64 {
+        env.ledger().timestamp()
+    }
+
+    /// Seconds until a goal unlocks, measured against the ledger clock
+    /// 
+    /// # Security:
+    /// - Read-only function
+    /// - 0 once unlocked and for inactive goals
+    pub fn time_until_unlock(env: Env, owner: Address, goal_id: u64) -> Result<u64, Error> {
+        let goal: SavingsGoal = env
+            .storage()
+            .persistent()
+            .get(&StorageKey::Goal(owner, goal_id))
+            .ok_or(Error::GoalNotFound)?;
+
+        if !goal.is_active {
+            return Ok(0);
+        }
+
+        Ok(goal.unlock_time.saturating_sub(env.ledger().timestamp()))
+    }
+
+    /// Whether a goal has reached its unlock time
+    /// 
+    /// # Security:
+    /// - Read-only function
+    /// - `false` for inactive goals
+    pub fn is_matured(env: Env, owner: Address, goal_id: u64) -> Result<bool, Error> {
+        let goal: SavingsGoal = env
+            .storage()
+            .persistent()
+            .get(&StorageKey::Goal(owner, goal_id))
+            .ok_or(Error::GoalNotFound)?;
+
+        Ok(goal.is_active && env.ledger().timestamp() >= goal.unlock_time)
+    }
+
+    /// Whether a goal's current balance has reached its target
+    /// 
+    /// # Security:
+    /// - Read-only function
+    /// - `false` for goals without a target and for inactive goals
+    pub fn is_goal_reached(env: Env, owner: Address, goal_id: u64) -> Result<bool, Error> {
+        let goal: SavingsGoal = env
+            .storage()
+            .persistent()
+            .get(&StorageKey::Goal(owner.clone(), goal_id))
+            .ok_or(Error::GoalNotFound)?;
+
+        if goal.target_amount == 0 || !goal.is_active {
+            return Ok(false);
+        }
+
+        Ok(Self::get_current_balance(env, owner, goal_id)? >= goal.target_amount)
+    }
+
+    /// Announce that a goal has reached its unlock time, for keepers turning
+    /// maturity into notifications
+    /// 
+    /// # Security:
+    /// - Can be called by anyone
+    /// - Emits a `matured` event once per term: the first call at or after
+    ///   unlock time sets the goal's `maturity_notified` flag, and later calls
+    ///   do nothing until the goal is extended or renewed
+    /// 
+    /// # Returns:
+    /// - Whether the event was emitted
+    pub fn poke(env: Env, owner: Address, goal_id: u64) -> Result<bool, Error> {
+        let key = StorageKey::Goal(owner.clone(), goal_id);
+        let mut goal: SavingsGoal = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(Error::GoalNotFound)?;
+
+        if !goal.is_active
+            || goal.maturity_notified
+            || env.ledger().timestamp() < goal.unlock_time
+        {
+            return Ok(false);
+        }
+
+        goal.maturity_notified = true;
+        save_goal(&env, &key, &goal);
+        publish_event(
+            &env,
+            (symbol_short!("matured"), owner, goal_id),
+            goal.unlock_time,
+        );
+
+        Ok(true)
+    }
+
+    /// Whether `withdraw` would accept this goal right now: it exists, is
+    /// active and has reached its unlock time
+    /// 
+    /// With a withdraw cooldown configured the payout must still go through
+    /// `request_withdraw`/`claim_withdraw`.
+    /// 
+    /// # Security:
+    /// - Read-only function
+    /// - `false` rather than an error for missing goals
+    pub fn can_withdraw(env: Env, owner: Address, goal_id: u64) -> bool {
+        let goal: Option<SavingsGoal> = env
+            .storage()
+            .persistent()
+            .get(&StorageKey::Goal(owner, goal_id));
+
+        goal.is_some_and(|goal| {
+            goal.is_active && env.ledger().timestamp() >= goal.unlock_time
+        })
+    }
+
+    /// Get the ledger sequence a goal was created in, for correlating with indexers
+    /// 
+    /// # Security:
+    /// - Read-only function
+    pub fn get_goal_created_ledger(env: Env, owner: Address, goal_id: u64) -> Result<u32, Error> {
+        let goal: SavingsGoal = env
+            .storage()
+            .persistent()
+            .get(&StorageKey::Goal(owner, goal_id))
+            .ok_or(Error::GoalNotFound)?;
+
+        Ok(goal.created_ledger)
+    }
+
+    /// Get a goal's most recent actions, oldest first
+    /// 
+    /// # Security:
+    /// - Read-only function
+    /// - Holds at most `MAX_HISTORY_ENTRIES` entries; older ones are dropped
+    pub fn get_goal_history(
+        env: Env,
+        owner: Address,
+        goal_id: u64,
+    ) -> Result<Vec<HistoryEntry>, Error> {
+        let storage = env.storage().persistent();
+        if !storage.has(&StorageKey::Goal(owner.clone(), goal_id)) {
+            return Err(Error::GoalNotFound);
+        }
+
+        Ok(storage
+            .get(&StorageKey::GoalHistory(owner, goal_id))
+            .unwrap_or(Vec::new(&env)))
+    }
+
+    /// Get the total number of goals for a user
+    /// 
+    /// # Security:
+    /// - Read-only function
+    pub fn get_user_goal_count(env: Env, owner: Address) -> u64 {
+        env.storage()
+            .persistent()
+            .get(&StorageKey::UserGoalCount(owner))
+            .unwrap_or(0)
+    }
+
+    /// Get the number of a user's goals that are still active
+    /// 
+    /// # Security:
+    /// - Read-only function
+    /// - Unlike `get_user_goal_count`, goes down when goals are closed
+    pub fn get_active_goal_count(env: Env, owner: Address) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&StorageKey::UserActiveGoalCount(owner))
+            .unwrap_or(0)
+    }
+
+    /// Get a page of a user's goal IDs in creation order
+    /// 
+    /// # Security:
+    /// - Read-only function
+    /// - Returns at most `MAX_PAGE_LIMIT` IDs per call
+    /// 
+    /// # Parameters:
+    /// - `owner`: Address of the goal owner
+    /// - `start`: Index into the user's goal list (pagination cursor)
+    /// - `limit`: Number of entries to return; continue from `start + limit`
+    pub fn get_user_goal_ids(env: Env, owner: Address, start: u32, limit: u32) -> Vec<u64> {
+        let goal_ids: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&StorageKey::UserGoalIds(owner))
+            .unwrap_or(Vec::new(&env));
+
+        let end = start
+            .saturating_add(limit.min(MAX_PAGE_LIMIT))
+            .min(goal_ids.len());
+        if start >= end {
+            return Vec::new(&env);
+        }
+        goal_ids.slice(start..end)
+    }
+
+    /// Get a page of a user's goals in creation order
+    /// 
+    /// # Security:
+    /// - Read-only function
+    /// - Scans at most `MAX_PAGE_LIMIT` entries per call to bound read cost
+    /// 
+    /// # Parameters:
+    /// - `owner`: Address of the goal owner
+    /// - `start`: Index into the user's goal list (pagination cursor)
+    /// - `limit`: Number of entries to scan; continue from `start + limit`
+    /// - `include_inactive`: Whether withdrawn goals are returned; when
+    ///   `false` a page may hold fewer than `limit` goals
+    pub fn list_goals(
+        env: Env,
+        owner: Address,
+        start: u32,
+        limit: u32,
+        include_inactive: bool,
+    ) -> Vec<SavingsGoal> {
+        let mut goals = Vec::new(&env);
+
+        for goal_id in Self::get_user_goal_ids(env.clone(), owner.clone(), start, limit) {
+            let goal: Option<SavingsGoal> = env
+                .storage()
+                .persistent()
+                .get(&StorageKey::Goal(owner.clone(), goal_id));
+            if let Some(goal) = goal {
+                if include_inactive || goal.is_active {
+                    goals.push_back(goal);
+                }
+            }
+        }
+
+        goals
+    }
+
+    /// Get a page of a user's goals in one lifecycle stage, in creation order
+    /// 
+    /// # Security:
+    /// - Read-only function
+    /// - Scans at most `MAX_PAGE_LIMIT` entries per call to bound read cost,
+    ///   so a page may hold fewer than `limit` goals
+    /// 
+    /// # Parameters:
+    /// - `owner`: Address of the goal owner
+    /// - `status`: `Locked`, `Matured` (unlocked but still active) or
+    ///   `Withdrawn` (inactive)
+    /// - `start`: Index into the user's goal list (pagination cursor)
+    /// - `limit`: Number of entries to scan; continue from `start + limit`
+    pub fn list_goals_by_status(
+        env: Env,
+        owner: Address,
+        status: GoalStatus,
+        start: u32,
+        limit: u32,
+    ) -> Vec<SavingsGoal> {
+        let current_time = env.ledger().timestamp();
+        let mut goals = Vec::new(&env);
+
+        for goal in Self::list_goals(env.clone(), owner, start, limit, true) {
+            let goal_status = if !goal.is_active {
+                GoalStatus::Withdrawn
+            } else if current_time >= goal.unlock_time {
+                GoalStatus::Matured
+            } else {
+                GoalStatus::Locked
+            };
+            if goal_status == status {
+                goals.push_back(goal);
+            }
+        }
+
+        goals
+    }
+
+    /// Get the IDs of a user's active goals unlocking within `window_seconds`
+    /// from now, e.g. for "your goal unlocks tomorrow" reminders
+    /// 
+    /// # Security:
+    /// - Read-only function
+    /// - Scans at most `MAX_PAGE_LIMIT` entries per call to bound read cost,
+    ///   so a page may hold fewer than `limit` IDs
+    /// 
+    /// # Parameters:
+    /// - `owner`: Address of the goal owner
+    /// - `window_seconds`: Goals with `now <= unlock_time <= now + window`
+    ///   are returned; already-matured goals are not
+    /// - `start`: Index into the user's goal list (pagination cursor)
+    /// - `limit`: Number of entries to scan; continue from `start + limit`
+    pub fn goals_maturing_within(
+        env: Env,
+        owner: Address,
+        window_seconds: u64,
+        start: u32,
+        limit: u32,
+    ) -> Vec<u64> {
+        let now = env.ledger().timestamp();
+        let window_end = now.saturating_add(window_seconds);
+        let mut goal_ids = Vec::new(&env);
+
+        for goal_id in Self::get_user_goal_ids(env.clone(), owner.clone(), start, limit) {
+            let goal: Option<SavingsGoal> = env
+                .storage()
+                .persistent()
+                .get(&StorageKey::Goal(owner.clone(), goal_id));
+            if let Some(goal) = goal {
+                if goal.is_active && goal.unlock_time >= now && goal.unlock_time <= window_end {
+                    goal_ids.push_back(goal_id);
+                }
+            }
+        }
+
+        goal_ids
+    }
+
+    /// Get the number of distinct addresses that have ever created a goal
+    /// 
+    /// # Security:
+    /// - Read-only function
+    pub fn get_unique_user_count(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&StorageKey::UniqueUsers)
+            .unwrap_or(0)
+    }
+
+    /// Calculate current total balance (principal + interest) for a goal
+    /// 
+    /// # Security:
+    /// - Read-only function, doesn't modify state
+    /// - Calculates up-to-date interest without changing storage
+    pub fn get_current_balance(env: Env, owner: Address, goal_id: u64) -> Result<i128, Error> {
+        let goal: SavingsGoal = env
+            .storage()
+            .persistent()
+            .get(&StorageKey::Goal(owner, goal_id))
+            .ok_or(Error::GoalNotFound)?;
+
+        if !goal.is_active {
+            return Ok(0);
+        }
+
+        goal.principal
+            .checked_add(goal.accrued_interest)
+            .ok_or(Error::Overflow)?
+            .checked_add(pending_interest(&env, &goal)?)
+            .ok_or(Error::Overflow)
+    }
+
+    /// Balance a goal will hold at unlock time if left untouched
+    /// 
+    /// # Security:
+    /// - Read-only function, doesn't modify state
+    /// - Uses the same interest math as `compound_interest`, run from the last
+    ///   compounding to unlock time
+    /// - The current balance once matured; 0 for inactive goals
+    pub fn projected_maturity_balance(
+        env: Env,
+        owner: Address,
+        goal_id: u64,
+    ) -> Result<i128, Error> {
+        let goal: SavingsGoal = env
+            .storage()
+            .persistent()
+            .get(&StorageKey::Goal(owner.clone(), goal_id))
+            .ok_or(Error::GoalNotFound)?;
+
+        if !goal.is_active {
+            return Ok(0);
+        }
+        if env.ledger().timestamp() >= goal.unlock_time {
+            return Self::get_current_balance(env, owner, goal_id);
+        }
+
+        let total_balance = goal
+            .principal
+            .checked_add(goal.accrued_interest)
+            .ok_or(Error::Overflow)?;
+        let remaining = goal.unlock_time.saturating_sub(goal.last_compound_time);
+
+        total_balance
+            .checked_add(goal_interest(&goal, total_balance, remaining)?)
+            .ok_or(Error::Overflow)
+    }
+
+    /// Effective annual yield of a goal in basis points, after compounding
+    /// 
+    /// `Linear` goals earn simple interest, so this is their nominal
+    /// `interest_rate`. `Periodic` goals compound `n = SECONDS_PER_YEAR /
+    /// COMPOUND_PERIOD` (365) times a year, giving
+    /// `((1 + rate / (BASIS_POINTS * n))^n - 1) * BASIS_POINTS`, computed with
+    /// the same fixed-point math as `compound_interest` and truncated.
+    /// 
+    /// # Security:
+    /// - Read-only function, doesn't modify state
+    pub fn effective_apy(env: Env, owner: Address, goal_id: u64) -> Result<u32, Error> {
+        let goal: SavingsGoal = env
+            .storage()
+            .persistent()
+            .get(&StorageKey::Goal(owner, goal_id))
+            .ok_or(Error::GoalNotFound)?;
+
+        match goal.compound_mode {
+            CompoundMode::Linear => Ok(goal.interest_rate),
+            CompoundMode::Periodic => {
+                // One year's interest on a balance of BASIS_POINTS, kept at
+                // COMPOUND_SCALE precision before truncating to whole points
+                let reference = BASIS_POINTS.checked_mul(COMPOUND_SCALE).ok_or(Error::Overflow)?;
+                let interest = calculate_compound_interest(
+                    reference,
+                    goal.interest_rate,
+                    SECONDS_PER_YEAR as u64,
+                )?;
+                u32::try_from(interest / COMPOUND_SCALE).map_err(|_| Error::Overflow)
+            }
+        }
+    }
+
+    /// Split a goal's current balance into what was deposited and what was
+    /// earned
+    /// 
+    /// # Security:
+    /// - Read-only function, doesn't modify state
+    /// - Uses the same interest math as `get_current_balance`, so the three
+    ///   parts always sum to it
+    /// - All zeros for inactive goals
+    /// 
+    /// # Returns:
+    /// - `(principal, accrued_interest, pending_interest)`, where pending is
+    ///   the interest not yet compounded since `last_compound_time`
+    pub fn get_balance_breakdown(
+        env: Env,
+        owner: Address,
+        goal_id: u64,
+    ) -> Result<(i128, i128, i128), Error> {
+        let goal: SavingsGoal = env
+            .storage()
+            .persistent()
+            .get(&StorageKey::Goal(owner, goal_id))
+            .ok_or(Error::GoalNotFound)?;
+
+        if !goal.is_active {
+            return Ok((0, 0, 0));
+        }
+
+        Ok((goal.principal, goal.accrued_interest, pending_interest(&env, &goal)?))
+    }
+
+    /// Amount the owner could withdraw right now without a penalty
+    /// 
+    /// # Security:
+    /// - Read-only function
+    /// - Zero while the goal is locked, inactive, its token is paused, or a
+    ///   configured withdrawal cooldown hasn't elapsed for a pending request
+    /// - Otherwise the full current balance (principal plus interest to now)
+    pub fn get_penalty_free_available(
+        env: Env,
+        owner: Address,
+        goal_id: u64,
+    ) -> Result<i128, Error> {
+        let goal: SavingsGoal = env
+            .storage()
+            .persistent()
+            .get(&StorageKey::Goal(owner.clone(), goal_id))
+            .ok_or(Error::GoalNotFound)?;
+
+        if !goal.is_active || env.ledger().timestamp() < goal.unlock_time {
+            return Ok(0);
+        }
+
+        if ensure_token_active(&env, &goal.token).is_err()
+            || ensure_cooldown_elapsed(&env, &owner, goal_id).is_err()
+        {
+            return Ok(0);
+        }
+
+        Self::get_current_balance(env, owner, goal_id)
+    }
+
+    /// Whether a goal's interest to maturity is currently backed
+    /// 
+    /// # Security:
+    /// - Read-only function
+    /// - Prepaid goals are always backed by their own allocation
+    /// - Otherwise the reserve must cover the goal's accrued interest plus the
+    ///   interest still to accrue until unlock time
+    /// - `false` for inactive goals
+    pub fn is_goal_funded(env: Env, owner: Address, goal_id: u64) -> Result<bool, Error> {
+        let goal: SavingsGoal = env
+            .storage()
+            .persistent()
+            .get(&StorageKey::Goal(owner, goal_id))
+            .ok_or(Error::GoalNotFound)?;
+
+        if !goal.is_active {
+            return Ok(false);
+        }
+        if goal.prepaid_interest > 0 {
+            return Ok(true);
+        }
+
+        let total_balance = goal
+            .principal
+            .checked_add(goal.accrued_interest)
+            .ok_or(Error::Overflow)?;
+        let remaining = goal.unlock_time.saturating_sub(goal.last_compound_time);
+        let projected = goal_interest(&goal, total_balance, remaining)?
+            .checked_add(goal.accrued_interest)
+            .ok_or(Error::Overflow)?;
+
+        let reserve: i128 = env
+            .storage()
+            .instance()
+            .get(&interest_reserve_key(&env, &goal)?)
+            .unwrap_or(0);
+
+        Ok(reserve >= projected)
+    }
+
+    /// Value a goal's current balance in the oracle's pegged unit (e.g. USD)
+    /// 
+    /// # Security:
+    /// - Read-only, display-only: never affects withdrawal amounts
+    /// - Rejects missing or stale oracle prices
+    pub fn quote_in_unit(env: Env, owner: Address, goal_id: u64) -> Result<i128, Error> {
+        let oracle_address: Address = env
+            .storage()
+            .instance()
+            .get(&StorageKey::PriceOracle)
+            .ok_or(Error::OracleNotConfigured)?;
+        let max_age: u64 = env
+            .storage()
+            .instance()
+            .get(&StorageKey::OracleMaxAge)
+            .unwrap_or(0);
+        let token_address = env
+            .storage()
+            .persistent()
+            .get::<_, SavingsGoal>(&StorageKey::Goal(owner.clone(), goal_id))
+            .ok_or(Error::GoalNotFound)?
+            .token;
+
+        let balance = Self::get_current_balance(env.clone(), owner, goal_id)?;
+
+        let oracle = PriceOracleClient::new(&env, &oracle_address);
+        let price = oracle
+            .lastprice(&token_address)
+            .ok_or(Error::PriceUnavailable)?;
+
+        if env.ledger().timestamp().saturating_sub(price.timestamp) > max_age {
+            return Err(Error::PriceStale);
+        }
+
+        let scale = 10i128
+            .checked_pow(oracle.decimals())
+            .ok_or(Error::Overflow)?;
+
+        balance
+            .checked_mul(price.price)
+            .ok_or(Error::Overflow)?
+            .checked_div(scale)
+            .ok_or(Error::DivisionError)
+    }
+
+    /// Admin function to configure the price oracle used by `quote_in_unit`
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    /// 
+    /// # Parameters:
+    /// - `oracle`: Oracle contract implementing `PriceOracle`
+    /// - `max_age`: Maximum age in seconds of a usable price
+    pub fn set_price_oracle(
+        env: Env,
+        admin: Address,
+        oracle: Address,
+        max_age: u64,
+    ) -> Result<(), Error> {
+        require_admin(&env, &admin)?;
+
+        env.storage().instance().set(&StorageKey::PriceOracle, &oracle);
+        env.storage().instance().set(&StorageKey::OracleMaxAge, &max_age);
+
+        Ok(())
+    }
+
+    /// Propose a new admin; takes effect once it calls `accept_admin`
+    /// 
+    /// # Security:
+    /// - Only the current admin can call this
+    /// - Two-step handover so a mistyped address can't take control; proposing
+    ///   again replaces the pending proposal
+    /// - The contract itself is rejected with `Unauthorized`
+    pub fn propose_admin(
+        env: Env,
+        current_admin: Address,
+        new_admin: Address,
+    ) -> Result<(), Error> {
+        require_admin(&env, &current_admin)?;
+        ensure_not_self(&env, &new_admin)?;
+
+        env.storage()
+            .instance()
+            .set(&StorageKey::PendingAdmin, &new_admin);
+
+        Ok(())
+    }
+
+    /// Admin function to replace the contract's code, keeping its address,
+    /// storage and balances
+    /// 
+    /// # Security:
+    /// - Only admin can call this (`Unauthorized` otherwise)
+    /// - Publishes an `upgraded` event with the new WASM hash
+    /// - Storage compatibility: the new code reads the existing entries as-is,
+    ///   so it must keep every `StorageKey` variant, `contracttype` layout and
+    ///   `Error` code stable. Never reorder or remove `SavingsGoal` fields;
+    ///   add new ones in a new type or as versioned entries, as a changed
+    ///   layout makes stored goals fail to deserialize.
+    /// - Code with a changed interface must bump `CONTRACT_VERSION` so
+    ///   clients can detect it through `version`
+    /// 
+    /// # Parameters:
+    /// - `admin`: Current admin address
+    /// - `new_wasm_hash`: Hash of WASM already uploaded to the network
+    pub fn upgrade(env: Env, admin: Address, new_wasm_hash: BytesN<32>) -> Result<(), Error> {
+        require_admin(&env, &admin)?;
+
+        env.deployer().update_current_contract_wasm(new_wasm_hash.clone());
+        publish_event(&env, (symbol_short!("upgraded"),), new_wasm_hash);
+
+        Ok(())
+    }
+
+    /// Accept a pending admin proposal, becoming the admin
+    /// 
+    /// # Security:
+    /// - Requires authorization from the proposed admin
+    /// - Publishes an `admin` event with the old and new admin
+    pub fn accept_admin(env: Env, new_admin: Address) -> Result<(), Error> {
+        new_admin.require_auth();
+
+        let pending: Address = env
+            .storage()
+            .instance()
+            .get(&StorageKey::PendingAdmin)
+            .ok_or(Error::Unauthorized)?;
+        if pending != new_admin {
+            return Err(Error::Unauthorized);
+        }
+
+        let old_admin: Address = env
+            .storage()
+            .instance()
+            .get(&StorageKey::Admin)
+            .ok_or(Error::NotInitialized)?;
+
+        env.storage().instance().set(&StorageKey::Admin, &new_admin);
+        env.storage().instance().remove(&StorageKey::PendingAdmin);
+
+        publish_event(&env, (symbol_short!("admin"), old_admin, new_admin), ());
+
+        Ok(())
+    }
+
+    /// Get the admin proposed by `propose_admin`, if any
+    /// 
+    /// # Security:
+    /// - Read-only function
+    pub fn get_pending_admin(env: Env) -> Option<Address> {
+        env.storage().instance().get(&StorageKey::PendingAdmin)
+    }
+
+    /// Admin function to set the duration-tiered rate caps for new goals
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    /// - Tiers must have strictly ascending `min_duration` and caps within
+    ///   `MAX_INTEREST_RATE`; an empty table disables tiering
+    /// - Applies to new goals and re-locks, not to existing terms
+    pub fn set_rate_tiers(env: Env, admin: Address, tiers: Vec<RateTier>) -> Result<(), Error> {
+        require_admin(&env, &admin)?;
+        validate_rate_tiers(&tiers)?;
+
+        env.storage().instance().set(&StorageKey::RateTiers, &tiers);
+
+        Ok(())
+    }
+
+    /// Get the duration-tiered rate caps
+    /// 
+    /// # Security:
+    /// - Read-only function
+    pub fn get_rate_tiers(env: Env) -> Vec<RateTier> {
+        env.storage()
+            .instance()
+            .get(&StorageKey::RateTiers)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Admin function to update emergency penalty rate
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    /// - Validates new penalty rate
+    pub fn set_emergency_penalty(env: Env, admin: Address, new_penalty: u32) -> Result<(), Error> {
+        require_admin(&env, &admin)?;
+
+        if new_penalty > 5000 {
+            return Err(Error::PenaltyTooHigh);
+        }
+
+        env.storage()
+            .instance()
+            .set(&StorageKey::EmergencyPenalty, &new_penalty);
+
+        Ok(())
+    }
+
+    /// Admin function to guarantee emergency withdrawals return a minimum
+    /// share of the balance withdrawn
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    /// - Penalties are clamped so the owner receives at least
+    ///   `balance * min_return_bps / 10000`, for full and partial emergency
+    ///   withdrawals alike; 0 (the default) leaves penalties unclamped
+    /// - At most 10000 basis points (`InvalidAmount` otherwise)
+    pub fn set_min_return_bps(env: Env, admin: Address, min_return_bps: u32) -> Result<(), Error> {
+        require_admin(&env, &admin)?;
+
+        if min_return_bps as i128 > BASIS_POINTS {
+            return Err(Error::InvalidAmount);
+        }
+        env.storage()
+            .instance()
+            .set(&ExtendedKey::MinReturnBps, &min_return_bps);
+
+        Ok(())
+    }
+
+    /// Admin function to deposit tokens into the interest reserve
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    /// - Tokens are transferred from the admin into the contract
+    /// - Withdrawals, installments and renewals draw the interest they pay
+    ///   from this reserve and fail with `InsufficientReserve` once it's
+    ///   depleted; principal never depends on it
+    /// 
+    /// # Parameters:
+    /// - `admin`: Admin address (must authorize)
+    /// - `amount`: Amount of tokens to add to the reserve
+    pub fn fund_reserve(env: Env, admin: Address, amount: i128) -> Result<i128, Error> {
+        require_admin(&env, &admin)?;
+
+        with_reentrancy_guard(&env, || {
+            if amount <= 0 {
+                return Err(Error::InvalidAmount);
+            }
+
+            let reserve: i128 = env
+                .storage()
+                .instance()
+                .get(&StorageKey::Reserve)
+                .unwrap_or(0);
+            let new_reserve = reserve.checked_add(amount).ok_or(Error::Overflow)?;
+
+            let token_address: Address = env
+                .storage()
+                .instance()
+                .get(&StorageKey::Token)
+                .ok_or(Error::NotInitialized)?;
+            let token = token::Client::new(&env, &token_address);
+            pull_tokens(&env, &token, &admin, amount)?;
+
+            env.storage()
+                .instance()
+                .set(&StorageKey::Reserve, &new_reserve);
+
+            Ok(new_reserve)
+        })
+    }
+
+    /// Admin function to fund the interest reserve for goals in a non-default
+    /// supported token
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    /// - Each token's reserve only ever pays interest on goals in that token;
+    ///   the default token's reserve is funded via `fund_reserve`
+    pub fn fund_token_reserve(
+        env: Env,
+        admin: Address,
+        token: Address,
+        amount: i128,
+    ) -> Result<i128, Error> {
+        require_admin(&env, &admin)?;
+
+        with_reentrancy_guard(&env, || {
+            if amount <= 0 {
+                return Err(Error::InvalidAmount);
+            }
+            if token == default_token(&env)? {
+                return Err(Error::InvalidToken);
+            }
+            resolve_goal_token(&env, Some(token.clone()))?;
+
+            let key = StorageKey::TokenReserve(token.clone());
+            let reserve: i128 = env.storage().instance().get(&key).unwrap_or(0);
+            let new_reserve = reserve.checked_add(amount).ok_or(Error::Overflow)?;
+
+            pull_tokens(&env, &token::Client::new(&env, &token), &admin, amount)?;
+            env.storage().instance().set(&key, &new_reserve);
+
+            Ok(new_reserve)
+        })
+    }
+
+    /// Get the interest reserve backing goals in `token`
+    /// 
+    /// # Security:
+    /// - Read-only function
+    pub fn get_token_reserve(env: Env, token: Address) -> i128 {
+        env.storage()
+            .instance()
+            .get(&reserve_key(&env, &token))
+            .unwrap_or(0)
+    }
+
+    /// Admin function to choose how the emergency penalty applies over a lock
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    /// - `LinearDecay`: the penalty falls linearly from the full rate at
+    ///   deposit to zero at unlock time
+    /// - `Flat` (default): the full penalty applies until unlock time
+    pub fn set_penalty_mode(env: Env, admin: Address, mode: PenaltyMode) -> Result<(), Error> {
+        require_admin(&env, &admin)?;
+
+        env.storage().instance().set(&StorageKey::PenaltyMode, &mode);
+
+        Ok(())
+    }
+
+    /// Admin function to set where penalty revenue is paid
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    /// - Separates the operator key from the address accumulating revenue;
+    ///   until set, penalties go to the admin
+    /// - The contract itself is rejected with `Unauthorized`
+    pub fn set_treasury(env: Env, admin: Address, treasury: Address) -> Result<(), Error> {
+        require_admin(&env, &admin)?;
+        ensure_not_self(&env, &treasury)?;
+
+        env.storage().instance().set(&StorageKey::Treasury, &treasury);
+
+        Ok(())
+    }
+
+    /// Get the address receiving penalty revenue: the treasury if set,
+    /// otherwise the admin
+    /// 
+    /// # Security:
+    /// - Read-only function
+    pub fn get_treasury(env: Env) -> Result<Address, Error> {
+        let storage = env.storage().instance();
+        match storage.get(&StorageKey::Treasury) {
+            Some(treasury) => Ok(treasury),
+            None => storage.get(&StorageKey::Admin).ok_or(Error::NotInitialized),
+        }
+    }
+
+    /// Get the interface version of the deployed code, so clients can gate
+    /// features and detect upgrades
+    /// 
+    /// # Security:
+    /// - Read-only function; works before `initialize`
+    pub fn version(_env: Env) -> u32 {
+        CONTRACT_VERSION
+    }
+
+    /// Get a snapshot of the contract's effective settings, e.g. to confirm
+    /// a deployment in one call
+    /// 
+    /// # Security:
+    /// - Read-only function
+    /// - Fails with `NotInitialized` before `initialize`
+    pub fn get_config(env: Env) -> Result<Config, Error> {
+        let storage = env.storage().instance();
+
+        Ok(Config {
+            token: default_token(&env)?,
+            admin: storage.get(&StorageKey::Admin).ok_or(Error::NotInitialized)?,
+            treasury: Self::get_treasury(env.clone())?,
+            emergency_penalty: storage
+                .get(&StorageKey::EmergencyPenalty)
+                .ok_or(Error::NotInitialized)?,
+            min_deposit: storage.get(&StorageKey::MinDeposit),
+            max_deposit: storage.get(&StorageKey::MaxDeposit),
+            max_rate: max_rate(&env),
+            default_rate: resolve_rate(&env, 0),
+            global_cap: storage.get(&ExtendedKey::GlobalCap),
+            creation_fee_bps: storage.get(&ExtendedKey::CreationFeeBps).unwrap_or(0),
+            max_goals_per_user: storage.get(&StorageKey::MaxGoalsPerUser),
+            withdraw_cooldown: storage.get(&StorageKey::WithdrawCooldown).unwrap_or(0),
+            paused: storage.get(&StorageKey::Paused).unwrap_or(false),
+        })
+    }
+
+    /// Admin function to choose where emergency penalties go
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    /// - Applies to the part of each penalty not recycled by
+    ///   `set_penalty_reserve_share`
+    /// - `Reserve` credits the reserve backing the goal's token, so penalties
+    ///   fund future interest; `Burn` sends them to `burn_address`, or keeps
+    ///   them in the contract outside every aggregate when it is `None`
+    /// 
+    /// # Parameters:
+    /// - `destination`: `Treasury` (default), `Reserve` or `Burn`
+    /// - `burn_address`: Recipient of burned penalties, only used by `Burn`
+    pub fn set_penalty_destination(
+        env: Env,
+        admin: Address,
+        destination: PenaltyDestination,
+        burn_address: Option<Address>,
+    ) -> Result<(), Error> {
+        require_admin(&env, &admin)?;
+
+        let storage = env.storage().instance();
+        storage.set(&ExtendedKey::PenaltyDestination, &destination);
+        match burn_address {
+            Some(burn_address) => storage.set(&ExtendedKey::BurnAddress, &burn_address),
+            None => storage.remove(&ExtendedKey::BurnAddress),
+        }
+
+        Ok(())
+    }
+
+    /// Admin function to set the share of each emergency penalty recycled
+    /// into the interest reserve instead of paid to the treasury
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    /// - Share is capped at 10000 basis points (the whole penalty)
+    pub fn set_penalty_reserve_share(
+        env: Env,
+        admin: Address,
+        share_bps: u32,
+    ) -> Result<(), Error> {
+        require_admin(&env, &admin)?;
+
+        if share_bps as i128 > BASIS_POINTS {
+            return Err(Error::InvalidAmount);
+        }
+
+        env.storage()
+            .instance()
+            .set(&StorageKey::PenaltyReserveShare, &share_bps);
+
+        Ok(())
+    }
+
+    /// Move recycled penalty revenue into the interest reserve
+    /// 
+    /// # Security:
+    /// - Permissionless maintenance call; only moves tokens already held by
+    ///   the contract between internal accounts
+    /// - Publishes a `rebalance` event with the moved amount
+    /// 
+    /// # Returns:
+    /// The amount moved into the reserve (0 if nothing was pending)
+    pub fn rebalance_reserve(env: Env) -> Result<i128, Error> {
+        let pending: i128 = env
+            .storage()
+            .instance()
+            .get(&StorageKey::PenaltyRevenue)
+            .unwrap_or(0);
+        if pending == 0 {
+            return Ok(0);
+        }
+
+        adjust_total(&env, &StorageKey::PenaltyRevenue, -pending)?;
+        adjust_total(&env, &StorageKey::Reserve, pending)?;
+
+        publish_event(&env, (symbol_short!("rebalance"),), pending);
+
+        Ok(pending)
+    }
+
+    /// Get recycled penalty revenue waiting to be moved into the reserve
+    /// 
+    /// # Security:
+    /// - Read-only function
+    pub fn get_pending_penalty_revenue(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&StorageKey::PenaltyRevenue)
+            .unwrap_or(0)
+    }
+
+    /// Get the current interest reserve balance
+    /// 
+    /// # Security:
+    /// - Read-only function
+    pub fn get_reserve_balance(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&StorageKey::Reserve)
+            .unwrap_or(0)
+    }
+
+    /// Get the total principal currently locked across all active goals
+    /// 
+    /// # Security:
+    /// - Read-only function, uses the maintained `TotalPrincipal` aggregate
+    ///   rather than iteration
+    /// - Excludes accrued interest, the reserve and any prepaid interest
+    pub fn get_total_value_locked(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&StorageKey::TotalPrincipal)
+            .unwrap_or(0)
+    }
+
+    /// Compare what the contract holds against what it owes savers
+    /// 
+    /// # Security:
+    /// - Read-only function, uses maintained aggregates rather than iteration
+    /// - Liabilities are the total principal plus total accrued interest of
+    ///   active goals, excluding interest not yet compounded; the balance is
+    ///   the contract's holding of the default token, which also includes the
+    ///   reserves
+    /// - Aggregates cover goals in every token, so with additional tokens the
+    ///   comparison is only meaningful when they share the default token's
+    ///   unit
+    /// 
+    /// # Returns:
+    /// - `(contract_token_balance, total_liabilities)`; the contract is
+    ///   undercollateralized when the first is below the second
+    pub fn solvency_check(env: Env) -> Result<(i128, i128), Error> {
+        let balance = token::Client::new(&env, &default_token(&env)?)
+            .balance(&env.current_contract_address());
+
+        let storage = env.storage().instance();
+        let total_principal: i128 = storage.get(&StorageKey::TotalPrincipal).unwrap_or(0);
+        let total_accrued: i128 = storage
+            .get(&StorageKey::TotalAccruedInterest)
+            .unwrap_or(0);
+        let liabilities = total_principal
+            .checked_add(total_accrued)
+            .ok_or(Error::Overflow)?;
+
+        Ok((balance, liabilities))
+    }
+
+    /// Get the principal-weighted average seconds until unlock across active goals
+    /// 
+    /// # Security:
+    /// - Read-only function, uses maintained aggregates rather than iteration
+    /// - Matured goals count as negative time remaining, so the average can
+    ///   reach zero before every goal has matured; zero with no active goals
+    pub fn get_average_maturity(env: Env) -> u64 {
+        let total_principal: i128 = env
+            .storage()
+            .instance()
+            .get(&StorageKey::TotalPrincipal)
+            .unwrap_or(0);
+        if total_principal <= 0 {
+            return 0;
+        }
+        let weighted_unlock: i128 = env
+            .storage()
+            .instance()
+            .get(&StorageKey::TotalWeightedUnlock)
+            .unwrap_or(0);
+
+        let average_unlock = (weighted_unlock / total_principal) as u64;
+        average_unlock.saturating_sub(env.ledger().timestamp())
+    }
+
+    /// Get the average annual rate in basis points the reserve can sustain
+    /// 
+    /// # Security:
+    /// - Read-only function
+    /// - Computed as `reserve / (total principal + accrued interest)`, i.e. the
+    ///   rate the reserve can pay on current TVL for one year
+    /// - New goals above this rate emit a `rate_warn` event but are not blocked
+    pub fn get_sustainable_rate_bps(env: Env) -> Result<u32, Error> {
+        sustainable_rate(&env)
+    }
+
+    /// Admin function to choose how `deposit` treats the unlock time
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    /// - Defaults to `TopUpMode::KeepUnlock`
+    pub fn set_top_up_mode(env: Env, admin: Address, mode: TopUpMode) -> Result<(), Error> {
+        require_admin(&env, &admin)?;
+
+        env.storage().instance().set(&StorageKey::TopUpMode, &mode);
+
+        Ok(())
+    }
+
+    /// Admin function to bound the amount of a single `create_goal` or
+    /// `deposit`
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    /// - Bounds are inclusive and in raw token units; `None` removes a bound
+    /// - Fails with `InvalidAmount` for non-positive bounds or `min > max`
+    pub fn set_deposit_limits(
+        env: Env,
+        admin: Address,
+        min: Option<i128>,
+        max: Option<i128>,
+    ) -> Result<(), Error> {
+        require_admin(&env, &admin)?;
+        validate_deposit_limits(min, max)?;
+
+        let storage = env.storage().instance();
+        match min {
+            Some(min) => storage.set(&StorageKey::MinDeposit, &min),
+            None => storage.remove(&StorageKey::MinDeposit),
+        }
+        match max {
+            Some(max) => storage.set(&StorageKey::MaxDeposit, &max),
+            None => storage.remove(&StorageKey::MaxDeposit),
+        }
+
+        Ok(())
+    }
+
+    /// Admin function to set the fee taken from each new goal's deposit
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    /// - At most `MAX_CREATION_FEE_BPS` (`InvalidAmount` otherwise); 0
+    ///   disables the fee
+    /// - The fee is paid to the treasury when a goal is created and the rest
+    ///   of the deposit becomes its principal; deposit limits apply to the
+    ///   full deposit
+    pub fn set_creation_fee(env: Env, admin: Address, bps: u32) -> Result<(), Error> {
+        require_admin(&env, &admin)?;
+
+        if bps > MAX_CREATION_FEE_BPS {
+            return Err(Error::InvalidAmount);
+        }
+        env.storage()
+            .instance()
+            .set(&ExtendedKey::CreationFeeBps, &bps);
+
+        Ok(())
+    }
+
+    /// Admin function to pay new goals' interest in a separate reward token
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    /// - The reward token must be a supported token other than the default
+    ///   (`InvalidToken` / `TokenNotSupported` otherwise); its interest is
+    ///   funded with `fund_token_reserve`
+    /// - Only goals created while it is set are paid in it; principal is
+    ///   always returned in the goal's own token
+    /// - `None` stops new goals using it; existing reward goals then fail
+    ///   to pay interest with `NotInitialized` until one is set again
+    pub fn set_reward_token(
+        env: Env,
+        admin: Address,
+        reward_token: Option<Address>,
+    ) -> Result<(), Error> {
+        require_admin(&env, &admin)?;
+
+        match reward_token {
+            Some(reward_token) => {
+                if reward_token == default_token(&env)? {
+                    return Err(Error::InvalidToken);
+                }
+                resolve_goal_token(&env, Some(reward_token.clone()))?;
+                env.storage()
+                    .instance()
+                    .set(&ExtendedKey::RewardToken, &reward_token);
+            }
+            None => env.storage().instance().remove(&ExtendedKey::RewardToken),
+        }
+
+        Ok(())
+    }
+
+    /// Admin function to limit goal creation to whitelisted owners
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    /// - While enabled, creating a goal for an owner not on the whitelist
+    ///   fails with `Unauthorized`; withdrawals and other exits are never
+    ///   gated
+    pub fn set_whitelist_enabled(env: Env, admin: Address, enabled: bool) -> Result<(), Error> {
+        require_admin(&env, &admin)?;
+
+        env.storage()
+            .instance()
+            .set(&ExtendedKey::WhitelistEnabled, &enabled);
+
+        Ok(())
+    }
+
+    /// Admin function to approve an address to create goals while the
+    /// whitelist is enabled
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    pub fn add_to_whitelist(env: Env, admin: Address, user: Address) -> Result<(), Error> {
+        require_admin(&env, &admin)?;
+
+        env.storage()
+            .instance()
+            .set(&ExtendedKey::Whitelisted(user), &true);
+
+        Ok(())
+    }
+
+    /// Admin function to remove an address from the whitelist; its existing
+    /// goals are unaffected
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    pub fn remove_from_whitelist(env: Env, admin: Address, user: Address) -> Result<(), Error> {
+        require_admin(&env, &admin)?;
+
+        env.storage()
+            .instance()
+            .remove(&ExtendedKey::Whitelisted(user));
+
+        Ok(())
+    }
+
+    /// Admin function to limit how many goals one address may create per
+    /// ledger
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    /// - Creations beyond `limit` in the same ledger fail with
+    ///   `CooldownActive`; each goal of a batch counts
+    /// - `None` removes the limit (the default); a zero limit is rejected
+    ///   with `InvalidAmount`
+    pub fn set_creation_rate_limit(
+        env: Env,
+        admin: Address,
+        limit: Option<u32>,
+    ) -> Result<(), Error> {
+        require_admin(&env, &admin)?;
+
+        match limit {
+            Some(0) => return Err(Error::InvalidAmount),
+            Some(limit) => env
+                .storage()
+                .instance()
+                .set(&ExtendedKey::CreationRateLimit, &limit),
+            None => env.storage().instance().remove(&ExtendedKey::CreationRateLimit),
+        }
+
+        Ok(())
+    }
+
+    /// Admin function to cap the total principal the contract may hold
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    /// - New goals and top-ups that would take the total principal of active
+    ///   goals past the cap fail with `DepositAboveMaximum`; interest and
+    ///   existing balances above a lowered cap are unaffected
+    /// - `None` removes the cap
+    pub fn set_global_cap(env: Env, admin: Address, cap: Option<i128>) -> Result<(), Error> {
+        require_admin(&env, &admin)?;
+
+        match cap {
+            Some(cap) if cap <= 0 => return Err(Error::InvalidAmount),
+            Some(cap) => env.storage().instance().set(&ExtendedKey::GlobalCap, &cap),
+            None => env.storage().instance().remove(&ExtendedKey::GlobalCap),
+        }
+
+        Ok(())
+    }
+
+    /// Admin function to change several settings in one call
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    /// - Every provided field is validated like its own setter, against the
+    ///   stored values of the fields left out, before anything is written;
+    ///   one invalid field reverts the whole update
+    /// - Limits can only be replaced here; `set_deposit_limits` removes them
+    /// 
+    /// # Parameters:
+    /// - `admin`: Admin address (must authorize)
+    /// - `update`: The settings to change
+    pub fn update_config(env: Env, admin: Address, update: ConfigUpdate) -> Result<(), Error> {
+        require_admin(&env, &admin)?;
+
+        let storage = env.storage().instance();
+        if update.emergency_penalty.is_some_and(|penalty| penalty > 5000) {
+            return Err(Error::PenaltyTooHigh);
+        }
+
+        let min_deposit = update
+            .min_deposit
+            .or(storage.get(&StorageKey::MinDeposit));
+        let max_deposit = update
+            .max_deposit
+            .or(storage.get(&StorageKey::MaxDeposit));
+        validate_deposit_limits(min_deposit, max_deposit)?;
+
+        if let Some(max_rate) = update.max_rate {
+            let default_rate: u32 = storage.get(&ExtendedKey::DefaultRate).unwrap_or(0);
+            if max_rate > MAX_INTEREST_RATE || default_rate > max_rate {
+                return Err(Error::RateTooHigh);
+            }
+        }
+
+        if let Some(penalty) = update.emergency_penalty {
+            storage.set(&StorageKey::EmergencyPenalty, &penalty);
+        }
+        if let Some(min) = update.min_deposit {
+            storage.set(&StorageKey::MinDeposit, &min);
+        }
+        if let Some(max) = update.max_deposit {
+            storage.set(&StorageKey::MaxDeposit, &max);
+        }
+        if let Some(max_rate) = update.max_rate {
+            storage.set(&ExtendedKey::MaxRate, &max_rate);
+        }
+
+        Ok(())
+    }
+
+    /// Admin function to set the balance below which abandoned goals may be
+    /// force-closed with `admin_close_dust_goal`
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    /// - 0 disables closing active goals
+    pub fn set_dust_threshold(env: Env, admin: Address, threshold: i128) -> Result<(), Error> {
+        require_admin(&env, &admin)?;
+
+        if threshold < 0 {
+            return Err(Error::InvalidAmount);
+        }
+        env.storage()
+            .instance()
+            .set(&StorageKey::DustThreshold, &threshold);
+
+        Ok(())
+    }
+
+    /// Admin function to close an abandoned dust goal, returning whatever it
+    /// holds to its owner
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    /// - Eligible goals are already inactive, or hold less than the dust
+    ///   threshold and are at least `DUST_GRACE_PERIOD` past unlock; others
+    ///   fail with `AmountExceedsBalance` (balance not below the threshold)
+    ///   or `StillLocked` (not yet abandoned). `Error` is at the contract
+    ///   spec's 50-variant limit, so no dedicated `NotDust` variant exists
+    /// - Residual funds always go to the owner, never the admin; interest is
+    ///   paid as far as the reserve covers it and no penalty applies
+    /// - Marks goal as inactive before transfer
+    /// - An already inactive goal holds nothing; only any leftover withdrawal
+    ///   request or drawdown is dropped. The goal record and its history are
+    ///   always kept for the owner
+    /// 
+    /// # Parameters:
+    /// - `admin`: Admin address (must authorize)
+    /// - `owner`: Address of the goal owner
+    /// - `goal_id`: ID of the goal to close
+    /// 
+    /// # Returns:
+    /// - The amount returned to the owner (0 for an already inactive goal)
+    pub fn admin_close_dust_goal(
+        env: Env,
+        admin: Address,
+        owner: Address,
+        goal_id: u64,
+    ) -> Result<i128, Error> {
+        require_admin(&env, &admin)?;
+
+        with_reentrancy_guard(&env, || {
+            let key = StorageKey::Goal(owner.clone(), goal_id);
+            let goal: SavingsGoal = env
+                .storage()
+                .persistent()
+                .get(&key)
+                .ok_or(Error::GoalNotFound)?;
+            if !goal.is_active {
+                clear_pending_schedules(&env, &owner, goal_id);
+                return Ok(0);
+            }
+
+            let threshold: i128 = env
+                .storage()
+                .instance()
+                .get(&StorageKey::DustThreshold)
+                .unwrap_or(0);
+            let balance = goal
+                .principal
+                .checked_add(goal.accrued_interest)
+                .ok_or(Error::Overflow)?
+                .checked_add(pending_interest(&env, &goal)?)
+                .ok_or(Error::Overflow)?;
+            let abandoned_at = goal
+                .unlock_time
+                .checked_add(DUST_GRACE_PERIOD)
+                .ok_or(Error::Overflow)?;
+            if balance >= threshold {
+                return Err(Error::AmountExceedsBalance);
+            }
+            if env.ledger().timestamp() < abandoned_at {
+                return Err(Error::StillLocked);
+            }
+
+            Self::compound_interest(env.clone(), owner.clone(), goal_id)?;
+            let mut goal: SavingsGoal = env
+                .storage()
+                .persistent()
+                .get(&key)
+                .ok_or(Error::GoalNotFound)?;
+            let before = goal.clone();
+
+            // Security: A short reserve never keeps the goal open
+            let interest_paid = if goal.prepaid_interest > 0 {
+                goal.accrued_interest
+            } else {
+                let reserve: i128 = env
+                    .storage()
+                    .instance()
+                    .get(&interest_reserve_key(&env, &goal)?)
+                    .unwrap_or(0);
+                goal.accrued_interest.min(reserve)
+            };
+            fund_interest(&env, &mut goal, interest_paid)?;
+            release_prepaid(&env, &goal, true)?;
+            let amount = goal
+                .principal
+                .checked_add(interest_paid)
+                .ok_or(Error::Overflow)?;
+
+            // Security: Mark goal as inactive before transfer
+            goal.is_active = false;
+            goal.withdrawn_at = env.ledger().timestamp();
+            save_goal(&env, &key, &goal);
+            track_goal_change(&env, Some(&before), &goal)?;
+            clear_pending_schedules(&env, &owner, goal_id);
+            credit_lifetime_interest(&env, &owner, interest_paid)?;
+
+            if amount > 0 {
+                pay_from_goal(&env, &goal, &owner, amount, interest_paid)?;
+            }
+            publish_withdrawn(&env, &owner, goal_id, amount, false, &owner);
+
+            Ok(amount)
+        })
+    }
+
+    /// Admin function to cap how many active goals one address may hold
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    /// - Checked against active goals only, so closing a goal frees a slot;
+    ///   existing goals above a lowered cap are unaffected
+    /// - `None` removes the cap
+    pub fn set_max_goals_per_user(
+        env: Env,
+        admin: Address,
+        max_goals: Option<u32>,
+    ) -> Result<(), Error> {
+        require_admin(&env, &admin)?;
+
+        match max_goals {
+            Some(max_goals) => env
+                .storage()
+                .instance()
+                .set(&StorageKey::MaxGoalsPerUser, &max_goals),
+            None => env.storage().instance().remove(&StorageKey::MaxGoalsPerUser),
+        }
+
+        Ok(())
+    }
+
+    /// Admin function to add a token to the supported set
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    /// - Rejects tokens whose `decimals()` call fails
+    /// - Caches the token's decimals so amounts in different tokens can be
+    ///   normalized without a cross-contract call
+    /// 
+    /// Returns the cached decimals.
+    pub fn add_supported_token(env: Env, admin: Address, token: Address) -> Result<u32, Error> {
+        require_admin(&env, &admin)?;
+
+        let decimals = fetch_token_decimals(&env, &token)?;
+        env.storage()
+            .instance()
+            .set(&StorageKey::AllowedToken(token), &decimals);
+
+        Ok(decimals)
+    }
+
+    /// Admin function to remove a token from the supported set
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    /// - The default token set at initialization can't be removed
+    pub fn remove_supported_token(env: Env, admin: Address, token: Address) -> Result<(), Error> {
+        require_admin(&env, &admin)?;
+
+        let default_token: Address = env
+            .storage()
+            .instance()
+            .get(&StorageKey::Token)
+            .ok_or(Error::NotInitialized)?;
+        if token == default_token {
+            return Err(Error::InvalidToken);
+        }
+
+        env.storage()
+            .instance()
+            .remove(&StorageKey::AllowedToken(token));
+
+        Ok(())
+    }
+
+    /// Get the cached decimals of a supported token
+    /// 
+    /// # Security:
+    /// - Read-only function
+    pub fn get_token_decimals(env: Env, token: Address) -> Result<u32, Error> {
+        env.storage()
+            .instance()
+            .get(&StorageKey::AllowedToken(token))
+            .ok_or(Error::TokenNotSupported)
+    }
+
+    /// Admin function to set the namespace prefixed to all event topics
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    /// - Lets indexers filter events per deployment; defaults to `savings`
+    pub fn set_event_namespace(env: Env, admin: Address, namespace: Symbol) -> Result<(), Error> {
+        require_admin(&env, &admin)?;
+
+        env.storage()
+            .instance()
+            .set(&StorageKey::EventNamespace, &namespace);
+
+        Ok(())
+    }
+
+    /// Get the namespace prefixed to all event topics
+    /// 
+    /// # Security:
+    /// - Read-only function
+    pub fn get_event_namespace(env: Env) -> Symbol {
+        env.storage()
+            .instance()
+            .get(&StorageKey::EventNamespace)
+            .unwrap_or(DEFAULT_EVENT_NAMESPACE)
+    }
+
+    /// Admin function to set how long past unlock time a beneficiary must wait
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    /// - Defaults to 180 days
+    pub fn set_beneficiary_window(env: Env, admin: Address, window: u64) -> Result<(), Error> {
+        require_admin(&env, &admin)?;
+
+        env.storage()
+            .instance()
+            .set(&StorageKey::BeneficiaryWindow, &window);
+
+        Ok(())
+    }
+
+    /// Admin function to halt new goals and deposits during an incident
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    /// - Withdrawals (including emergency withdrawals) stay available so
+    ///   users are never locked out of their funds
+    pub fn pause(env: Env, admin: Address) -> Result<(), Error> {
+        require_admin(&env, &admin)?;
+
+        env.storage().instance().set(&StorageKey::Paused, &true);
+
+        Ok(())
+    }
+
+    /// Admin function to resume new goals and deposits
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    pub fn unpause(env: Env, admin: Address) -> Result<(), Error> {
+        require_admin(&env, &admin)?;
+
+        env.storage().instance().set(&StorageKey::Paused, &false);
+
+        Ok(())
+    }
+
+    /// Whether new goals and deposits are currently halted
+    /// 
+    /// # Security:
+    /// - Read-only function
+    pub fn is_paused(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&StorageKey::Paused)
+            .unwrap_or(false)
+    }
+
+    /// Admin function to pause all goal activity in one token
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    /// - Creation, deposits and withdrawals of goals in `token` fail with
+    ///   `TokenPaused`; other tokens keep working
+    pub fn pause_token(env: Env, admin: Address, token: Address) -> Result<(), Error> {
+        require_admin(&env, &admin)?;
+
+        env.storage()
+            .instance()
+            .set(&StorageKey::TokenPaused(token), &true);
+
+        Ok(())
+    }
+
+    /// Admin function to resume goal activity in a paused token
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    pub fn unpause_token(env: Env, admin: Address, token: Address) -> Result<(), Error> {
+        require_admin(&env, &admin)?;
+
+        env.storage()
+            .instance()
+            .remove(&StorageKey::TokenPaused(token));
+
+        Ok(())
+    }
+
+    /// Check whether goal activity in `token` is paused
+    /// 
+    /// # Security:
+    /// - Read-only function
+    pub fn is_token_paused(env: Env, token: Address) -> bool {
+        env.storage()
+            .instance()
+            .get(&StorageKey::TokenPaused(token))
+            .unwrap_or(false)
+    }
+
+    /// Admin function to set this deployment's rate policy
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    /// - `max_rate` may only tighten `MAX_INTEREST_RATE`, and `default_rate`
+    ///   must be within it (`RateTooHigh` otherwise)
+    /// - New and re-locked goals are checked against `max_rate`; existing
+    ///   goals keep their rate
+    /// 
+    /// # Parameters:
+    /// - `admin`: Admin address (must authorize)
+    /// - `max_rate`: Highest annual rate in basis points a goal may use
+    /// - `default_rate`: Rate given to new goals created with a rate of 0
+    pub fn configure_rates(
+        env: Env,
+        admin: Address,
+        max_rate: u32,
+        default_rate: u32,
+    ) -> Result<(), Error> {
+        require_admin(&env, &admin)?;
+
+        if max_rate > MAX_INTEREST_RATE || default_rate > max_rate {
+            return Err(Error::RateTooHigh);
+        }
+
+        let storage = env.storage().instance();
+        storage.set(&ExtendedKey::MaxRate, &max_rate);
+        storage.set(&ExtendedKey::DefaultRate, &default_rate);
+
+        Ok(())
+    }
+
+    /// Admin function to set the guaranteed minimum interest rate
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    /// - Rate is bounded by the maximum interest rate
+    /// - Matured withdrawals are topped up from the reserve to at least
+    ///   `principal * rate * lock_duration` of interest (0 disables)
+    pub fn set_guaranteed_min_rate(env: Env, admin: Address, rate: u32) -> Result<(), Error> {
+        require_admin(&env, &admin)?;
+
+        if rate > MAX_INTEREST_RATE {
+            return Err(Error::RateTooHigh);
+        }
+
+        env.storage()
+            .instance()
+            .set(&StorageKey::GuaranteedMinRate, &rate);
+
+        Ok(())
+    }
+
+    /// Admin function to choose what happens when the reserve can't cover the guarantee
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    /// - `true`: withdrawals fail with `InsufficientReserve` until the reserve is funded
+    /// - `false` (default): withdrawals pay the accrued interest without the guarantee
+    pub fn set_guarantee_strict(env: Env, admin: Address, strict: bool) -> Result<(), Error> {
+        require_admin(&env, &admin)?;
+
+        env.storage()
+            .instance()
+            .set(&StorageKey::GuaranteeStrict, &strict);
+
+        Ok(())
+    }
+
+    /// Get a composite health indicator for alerting
+    /// 
+    /// # Security:
+    /// - Read-only function, uses maintained aggregates rather than iteration
+    /// 
+    /// # Returns:
+    /// - Solvency: contract token balance / (total principal + accrued interest + IOUs)
+    /// - Runway: reserve / projected interest per second across active goals
+    pub fn get_health(env: Env) -> Result<HealthReport, Error> {
+        let token_address: Address = env
+            .storage()
+            .instance()
+            .get(&StorageKey::Token)
+            .ok_or(Error::NotInitialized)?;
+        let balance =
+            token::Client::new(&env, &token_address).balance(&env.current_contract_address());
+
+        let total_principal: i128 = env
+            .storage()
+            .instance()
+            .get(&StorageKey::TotalPrincipal)
+            .unwrap_or(0);
+        let total_accrued: i128 = env
+            .storage()
+            .instance()
+            .get(&StorageKey::TotalAccruedInterest)
+            .unwrap_or(0);
+        let weighted_rate: i128 = env
+            .storage()
+            .instance()
+            .get(&StorageKey::TotalWeightedRate)
+            .unwrap_or(0);
+        let reserve: i128 = env
+            .storage()
+            .instance()
+            .get(&StorageKey::Reserve)
+            .unwrap_or(0);
+        let active_goals: u64 = env
+            .storage()
+            .instance()
+            .get(&StorageKey::ActiveGoalCount)
+            .unwrap_or(0);
+
+        let total_iou: i128 = env
+            .storage()
+            .instance()
+            .get(&StorageKey::TotalIou)
+            .unwrap_or(0);
+
+        let obligations = total_principal
+            .checked_add(total_accrued)
+            .ok_or(Error::Overflow)?
+            .checked_add(total_iou)
+            .ok_or(Error::Overflow)?;
+
+        let solvency_bps = if obligations == 0 {
+            u32::MAX
+        } else {
+            balance
+                .checked_mul(BASIS_POINTS)
+                .map(|scaled| scaled / obligations)
+                .map_or(u32::MAX, |ratio| ratio.clamp(0, u32::MAX as i128) as u32)
+        };
+
+        // Interest accrues at weighted_rate / (SECONDS_PER_YEAR * BASIS_POINTS) per second
+        let runway = if weighted_rate == 0 {
+            u64::MAX
+        } else {
+            reserve
+                .checked_mul(SECONDS_PER_YEAR * BASIS_POINTS)
+                .map(|scaled| scaled / weighted_rate)
+                .map_or(u64::MAX, |seconds| seconds.clamp(0, u64::MAX as i128) as u64)
+        };
+
+        let thresholds: HealthThresholds = env
+            .storage()
+            .instance()
+            .get(&StorageKey::HealthThresholds)
+            .unwrap_or(HealthThresholds {
+                warning_solvency_bps: BASIS_POINTS as u32,
+                critical_solvency_bps: 9000,
+                warning_runway: DEFAULT_WARNING_RUNWAY,
+                critical_runway: DEFAULT_CRITICAL_RUNWAY,
+            });
+
+        let status = if solvency_bps < thresholds.critical_solvency_bps
+            || runway < thresholds.critical_runway
+        {
+            HealthStatus::Critical
+        } else if solvency_bps < thresholds.warning_solvency_bps
+            || runway < thresholds.warning_runway
+        {
+            HealthStatus::Warning
+        } else {
+            HealthStatus::Healthy
+        };
+
+        Ok(HealthReport {
+            solvency_bps,
+            runway,
+            active_goals,
+            status,
+        })
+    }
+
+    /// Admin function to set the thresholds used by `get_health`
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    /// - Critical thresholds must not exceed warning thresholds
+    pub fn set_health_thresholds(
+        env: Env,
+        admin: Address,
+        thresholds: HealthThresholds,
+    ) -> Result<(), Error> {
+        require_admin(&env, &admin)?;
+
+        if thresholds.critical_solvency_bps > thresholds.warning_solvency_bps
+            || thresholds.critical_runway > thresholds.warning_runway
+        {
+            return Err(Error::InvalidThresholds);
+        }
+
+        env.storage()
+            .instance()
+            .set(&StorageKey::HealthThresholds, &thresholds);
+
+        Ok(())
+    }
+
+    /// Request a withdrawal, starting the cooldown for `claim_withdraw`
+    /// 
+    /// # Security:
+    /// - Requires owner authorization
+    /// - Only one pending request per goal
+    /// - Can be made before maturity; the claim still requires the unlock time
     /// 
     /// # Parameters:
     /// - `owner`: Address of the goal owner
     /// - `goal_id`: ID of the goal to withdraw from
-    pub fn emergency_withdraw(env: Env, owner: Address, goal_id: u64) -> Result<i128, Error> {
-        // Security: Require authorization
+    /// 
+    /// Returns the earliest timestamp at which the cooldown has elapsed.
+    pub fn request_withdraw(env: Env, owner: Address, goal_id: u64) -> Result<u64, Error> {
+        owner.require_auth();
+
+        let goal: SavingsGoal = env
+            .storage()
+            .persistent()
+            .get(&StorageKey::Goal(owner.clone(), goal_id))
+            .ok_or(Error::GoalNotFound)?;
+
+        if !goal.is_active {
+            return Err(Error::AlreadyWithdrawn);
+        }
+
+        let request_key = StorageKey::WithdrawRequest(owner, goal_id);
+        if env.storage().persistent().has(&request_key) {
+            return Err(Error::WithdrawAlreadyRequested);
+        }
+
+        let current_time = env.ledger().timestamp();
+        env.storage().persistent().set(&request_key, &current_time);
+
+        let cooldown: u64 = env
+            .storage()
+            .instance()
+            .get(&StorageKey::WithdrawCooldown)
+            .unwrap_or(0);
+
+        current_time.checked_add(cooldown).ok_or(Error::Overflow)
+    }
+
+    /// Claim a previously requested withdrawal
+    /// 
+    /// # Security:
+    /// - Requires owner authorization
+    /// - Fails until the cooldown since the request has elapsed
+    /// - Applies the same maturity and state checks as `withdraw`
+    /// 
+    /// # Parameters:
+    /// - `owner`: Address of the goal owner
+    /// - `goal_id`: ID of the goal to withdraw from
+    pub fn claim_withdraw(env: Env, owner: Address, goal_id: u64) -> Result<i128, Error> {
+        owner.require_auth();
+
+        with_reentrancy_guard(&env, || {
+            let requested_at: u64 = env
+                .storage()
+                .persistent()
+                .get(&StorageKey::WithdrawRequest(owner.clone(), goal_id))
+                .ok_or(Error::WithdrawNotRequested)?;
+
+            let cooldown: u64 = env
+                .storage()
+                .instance()
+                .get(&StorageKey::WithdrawCooldown)
+                .unwrap_or(0);
+
+            let claimable_at = requested_at
+                .checked_add(cooldown)
+                .ok_or(Error::Overflow)?;
+
+            if env.ledger().timestamp() < claimable_at {
+                return Err(Error::CooldownNotElapsed);
+            }
+
+            settle_withdrawal(&env, &owner, goal_id)
+        })
+    }
+
+    /// Cancel a pending withdrawal request
+    /// 
+    /// # Security:
+    /// - Requires owner authorization
+    pub fn cancel_withdraw(env: Env, owner: Address, goal_id: u64) -> Result<(), Error> {
         owner.require_auth();
 
-        // Compound interest before withdrawal
-        Self::compound_interest(env.clone(), owner.clone(), goal_id)?;
+        let request_key = StorageKey::WithdrawRequest(owner, goal_id);
+        if !env.storage().persistent().has(&request_key) {
+            return Err(Error::WithdrawNotRequested);
+        }
+
+        env.storage().persistent().remove(&request_key);
+
+        Ok(())
+    }
+
+    /// Get the timestamp of a goal's pending withdrawal request, if any
+    /// 
+    /// # Security:
+    /// - Read-only function
+    pub fn get_withdraw_request(env: Env, owner: Address, goal_id: u64) -> Option<u64> {
+        env.storage()
+            .persistent()
+            .get(&StorageKey::WithdrawRequest(owner, goal_id))
+    }
+
+    /// Admin function to set the withdrawal cooldown
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    /// - A non-zero cooldown makes `request_withdraw`/`claim_withdraw` mandatory
+    pub fn set_withdraw_cooldown(env: Env, admin: Address, cooldown: u64) -> Result<(), Error> {
+        require_admin(&env, &admin)?;
+
+        env.storage()
+            .instance()
+            .set(&StorageKey::WithdrawCooldown, &cooldown);
+
+        Ok(())
+    }
+
+    /// Admin function to set the cooldown after an emergency withdrawal
+    /// 
+    /// # Security:
+    /// - Only admin can call this
+    /// - For `seconds` after an `emergency_withdraw`, that address can't open
+    ///   new goals (`CooldownActive`), which deters rapidly cycling funds
+    /// - Unrelated to the request/claim cooldown of `set_withdraw_cooldown`;
+    ///   0 (the default) disables it
+    pub fn set_withdrawal_cooldown(env: Env, admin: Address, seconds: u64) -> Result<(), Error> {
+        require_admin(&env, &admin)?;
+
+        env.storage()
+            .instance()
+            .set(&StorageKey::EmergencyCooldown, &seconds);
+
+        Ok(())
+    }
+
+    /// Get the seconds left before `owner` may open goals again after an
+    /// emergency withdrawal
+    /// 
+    /// # Security:
+    /// - Read-only function
+    pub fn get_remaining_cooldown(env: Env, owner: Address) -> u64 {
+        remaining_emergency_cooldown(&env, &owner)
+    }
+
+    /// Get the configured withdrawal cooldown in seconds
+    /// 
+    /// # Security:
+    /// - Read-only function
+    pub fn get_withdraw_cooldown(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&StorageKey::WithdrawCooldown)
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(feature = "diagnostics")]
+#[contractimpl]
+impl TimeLockedSavings {
+    /// Re-run a goal's balance, interest and emergency-penalty math at the
+    /// current time and return the `ErrorContext` code of the first step that
+    /// fails, or `0` if all succeed
+    /// 
+    /// Failed invocations revert their storage writes, so error locations
+    /// can't be recorded by the failing call itself; this companion query
+    /// replays the same steps instead. Only built with the `diagnostics`
+    /// feature.
+    /// 
+    /// # Security:
+    /// - Read-only function
+    pub fn diagnose_goal(env: Env, owner: Address, goal_id: u64) -> Result<u32, Error> {
+        let goal: SavingsGoal = env
+            .storage()
+            .persistent()
+            .get(&StorageKey::Goal(owner, goal_id))
+            .ok_or(Error::GoalNotFound)?;
+
+        let trace = || -> Result<(), ErrorContext> {
+            let time_elapsed =
+                accruing_seconds(&env, &goal).map_err(|_| ErrorContext::ElapsedTime)?;
+            let total_balance = goal
+                .principal
+                .checked_add(goal.accrued_interest)
+                .ok_or(ErrorContext::TotalBalance)?;
+            let pending_interest =
+                calculate_interest_traced(total_balance, goal.interest_rate, time_elapsed)
+                    .map_err(|(_, context)| context)?;
+            let balance = total_balance
+                .checked_add(pending_interest)
+                .ok_or(ErrorContext::BalanceWithInterest)?;
+
+            let penalty_rate = effective_penalty_rate_traced(&env, &goal)?;
+            let penalty = balance
+                .checked_mul(penalty_rate as i128)
+                .ok_or(ErrorContext::PenaltyMul)?
+                / BASIS_POINTS;
+            balance
+                .checked_sub(penalty)
+                .ok_or(ErrorContext::PenaltySub)?;
+
+            Ok(())
+        };
+
+        Ok(trace().err().unwrap_or(ErrorContext::None) as u32)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use soroban_sdk::{
+        testutils::{storage::Persistent as _, Address as _, Events, Ledger},
+        token::{self, StellarAssetClient},
+        vec, TryFromVal,
+    };
+
+    /// 30 days in seconds
+    const THIRTY_DAYS: u64 = 2592000;
+
+    struct Setup<'a> {
+        env: Env,
+        client: TimeLockedSavingsClient<'a>,
+        admin: Address,
+        user: Address,
+        token: token::Client<'a>,
+    }
+
+    /// Register the contract and a token, initialize with a 10% penalty, mint
+    /// 10000 tokens to the user and seed the contract with tokens to pay interest
+    fn setup<'a>() -> Setup<'a> {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register(TimeLockedSavings, ());
+        let client = TimeLockedSavingsClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let user = Address::generate(&env);
+        let token_id = env.register_stellar_asset_contract_v2(admin.clone());
+        let token = token::Client::new(&env, &token_id.address());
+        let token_admin = StellarAssetClient::new(&env, &token_id.address());
+
+        client.initialize(&token_id.address(), &admin, &1000);
+
+        token_admin.mint(&user, &10000);
+        token_admin.mint(&contract_id, &100000);
+
+        Setup {
+            env,
+            client,
+            admin,
+            user,
+            token,
+        }
+    }
+
+    /// Mint `amount` to the admin and move it into the interest reserve
+    fn fund_reserve(env: &Env, client: &TimeLockedSavingsClient, admin: &Address, amount: i128) {
+        let token: Address = env.as_contract(&client.address, || {
+            env.storage().instance().get(&StorageKey::Token).unwrap()
+        });
+        StellarAssetClient::new(env, &token).mint(admin, &amount);
+        client.fund_reserve(admin, &amount);
+    }
+
+    /// Number of events in the last invocation whose name topic (after the
+    /// namespace) is `name`
+    fn count_events(env: &Env, name: Symbol) -> u32 {
+        env.events()
+            .all()
+            .iter()
+            .filter(|(_, topics, _)| {
+                topics
+                    .get(1)
+                    .and_then(|topic| Symbol::try_from_val(env, &topic).ok())
+                    == Some(name.clone())
+            })
+            .count() as u32
+    }
+
+    mod oracle {
+        use super::*;
+
+        /// Oracle returning a fixed price with 7 decimals
+        #[contract]
+        pub struct MockOracle;
+
+        #[contractimpl]
+        impl MockOracle {
+            pub fn set_price(env: Env, price: i128, timestamp: u64) {
+                env.storage()
+                    .instance()
+                    .set(&symbol_short!("price"), &PriceData { price, timestamp });
+            }
+
+            pub fn lastprice(env: Env, _asset: Address) -> Option<PriceData> {
+                env.storage().instance().get(&symbol_short!("price"))
+            }
+
+            pub fn decimals(_env: Env) -> u32 {
+                7
+            }
+        }
+    }
+
+    mod decimals_token {
+        use super::*;
+
+        /// Minimal token reporting a configurable number of decimals
+        #[contract]
+        pub struct MockDecimalsToken;
+
+        #[contractimpl]
+        impl MockDecimalsToken {
+            pub fn __constructor(env: Env, decimals: u32) {
+                env.storage()
+                    .instance()
+                    .set(&symbol_short!("decimals"), &decimals);
+            }
+
+            pub fn decimals(env: Env) -> u32 {
+                env.storage()
+                    .instance()
+                    .get(&symbol_short!("decimals"))
+                    .unwrap()
+            }
+        }
+    }
+
+    mod reentrant_token {
+        use super::*;
+
+        /// Token whose `transfer` tries to call back into the savings contract
+        #[contract]
+        pub struct ReentrantToken;
+
+        #[contractimpl]
+        impl ReentrantToken {
+            pub fn __constructor(env: Env, savings: Address) {
+                env.storage()
+                    .instance()
+                    .set(&symbol_short!("savings"), &savings);
+            }
+
+            pub fn decimals(_env: Env) -> u32 {
+                7
+            }
+
+            pub fn balance(_env: Env, _id: Address) -> i128 {
+                i128::MAX
+            }
+
+            pub fn transfer(env: Env, from: Address, _to: Address, _amount: i128) {
+                let savings: Address = env
+                    .storage()
+                    .instance()
+                    .get(&symbol_short!("savings"))
+                    .unwrap();
+                let reentry = TimeLockedSavingsClient::new(&env, &savings).try_create_goal(
+                    &from,
+                    &1000,
+                    &THIRTY_DAYS,
+                    &0,
+                    &None,
+                    &None,
+                    &0,
+                );
+                env.storage()
+                    .instance()
+                    .set(&symbol_short!("blocked"), &reentry.is_err());
+            }
+
+            /// Whether the last reentry attempt was rejected
+            pub fn reentry_blocked(env: Env) -> Option<bool> {
+                env.storage().instance().get(&symbol_short!("blocked"))
+            }
+        }
+    }
+
+    use decimals_token::MockDecimalsToken;
+    use reentrant_token::{ReentrantToken, ReentrantTokenClient};
+    use oracle::{MockOracle, MockOracleClient};
+
+    #[test]
+    fn test_create_and_withdraw_goal() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+        fund_reserve(&env, &client, &admin, 10000);
+
+        // Create goal: 10000 tokens, 30 days lock, 5% interest
+        let goal_id = client.create_goal(&user, &10000, &THIRTY_DAYS, &500, &None, &None, &0);
+
+        // Fast forward time to unlock
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS + 1);
+
+        // Withdraw
+        let amount = client.withdraw(&user, &goal_id);
+        assert!(amount > 10000); // Should have interest
+    }
+
+    #[test]
+    fn test_withdraw_cooldown_boundary() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            token,
+            ..
+        } = setup();
+
+        client.set_withdraw_cooldown(&admin, &3600);
+        let goal_id = client.create_goal(&user, &10000, &THIRTY_DAYS, &0, &None, &None, &0);
+
+        // Direct withdrawals are disabled while a cooldown is configured
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS);
+        assert_eq!(
+            client.try_withdraw(&user, &goal_id),
+            Err(Ok(Error::WithdrawRequestRequired))
+        );
+
+        let claimable_at = client.request_withdraw(&user, &goal_id);
+        assert_eq!(claimable_at, THIRTY_DAYS + 3600);
+        assert_eq!(
+            client.try_request_withdraw(&user, &goal_id),
+            Err(Ok(Error::WithdrawAlreadyRequested))
+        );
+
+        env.ledger().with_mut(|li| li.timestamp = claimable_at - 1);
+        assert_eq!(
+            client.try_claim_withdraw(&user, &goal_id),
+            Err(Ok(Error::CooldownNotElapsed))
+        );
+
+        env.ledger().with_mut(|li| li.timestamp = claimable_at);
+        assert_eq!(client.claim_withdraw(&user, &goal_id), 10000);
+        assert_eq!(token.balance(&user), 10000);
+        assert_eq!(client.get_withdraw_request(&user, &goal_id), None);
+    }
+
+    #[test]
+    fn test_claim_requires_maturity_after_cooldown() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+
+        client.set_withdraw_cooldown(&admin, &3600);
+        let goal_id = client.create_goal(&user, &10000, &THIRTY_DAYS, &0, &None, &None, &0);
+
+        // Request early; the cooldown elapses long before maturity
+        client.request_withdraw(&user, &goal_id);
+        env.ledger().with_mut(|li| li.timestamp = 7200);
+        assert_eq!(
+            client.try_claim_withdraw(&user, &goal_id),
+            Err(Ok(Error::StillLocked))
+        );
+
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS);
+        assert_eq!(client.claim_withdraw(&user, &goal_id), 10000);
+    }
+
+    #[test]
+    fn test_cancel_withdraw_request() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+
+        client.set_withdraw_cooldown(&admin, &3600);
+        let goal_id = client.create_goal(&user, &10000, &THIRTY_DAYS, &0, &None, &None, &0);
+
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS);
+        client.request_withdraw(&user, &goal_id);
+        client.cancel_withdraw(&user, &goal_id);
+        assert_eq!(client.get_withdraw_request(&user, &goal_id), None);
+
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS + 3600);
+        assert_eq!(
+            client.try_claim_withdraw(&user, &goal_id),
+            Err(Ok(Error::WithdrawNotRequested))
+        );
+        assert_eq!(
+            client.try_cancel_withdraw(&user, &goal_id),
+            Err(Ok(Error::WithdrawNotRequested))
+        );
+
+        // A fresh request restarts the cooldown
+        client.request_withdraw(&user, &goal_id);
+        assert_eq!(
+            client.try_claim_withdraw(&user, &goal_id),
+            Err(Ok(Error::CooldownNotElapsed))
+        );
+    }
+
+    #[test]
+    fn test_zero_cooldown_claims_instantly() {
+        let Setup {
+            env, client, user, ..
+        } = setup();
+
+        assert_eq!(client.get_withdraw_cooldown(), 0);
+        let goal_id = client.create_goal(&user, &10000, &THIRTY_DAYS, &0, &None, &None, &0);
+
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS);
+        client.request_withdraw(&user, &goal_id);
+        assert_eq!(client.claim_withdraw(&user, &goal_id), 10000);
+    }
+
+    #[test]
+    fn test_unique_user_count() {
+        let Setup {
+            env,
+            client,
+            user,
+            token,
+            ..
+        } = setup();
+
+        let other = Address::generate(&env);
+        StellarAssetClient::new(&env, &token.address).mint(&other, &10000);
+
+        assert_eq!(client.get_unique_user_count(), 0);
+
+        client.create_goal(&user, &1000, &THIRTY_DAYS, &500, &None, &None, &0);
+        client.create_goal(&user, &1000, &THIRTY_DAYS, &500, &None, &None, &0);
+        assert_eq!(client.get_unique_user_count(), 1);
+
+        client.create_goal(&other, &1000, &THIRTY_DAYS, &500, &None, &None, &0);
+        client.create_goal(&other, &1000, &THIRTY_DAYS, &500, &None, &None, &0);
+        client.create_goal(&user, &1000, &THIRTY_DAYS, &500, &None, &None, &0);
+        assert_eq!(client.get_unique_user_count(), 2);
+        assert_eq!(client.get_user_goal_count(&user), 3);
+        assert_eq!(client.get_user_goal_count(&other), 2);
+    }
+
+    #[test]
+    fn test_unique_user_count_ignores_transfers() {
+        let Setup {
+            env,
+            client,
+            user,
+            token,
+            ..
+        } = setup();
+        let friend = Address::generate(&env);
+        StellarAssetClient::new(&env, &token.address).mint(&friend, &10000);
+
+        // Receiving a goal isn't creating one, and giving the only goal away
+        // doesn't make the sender a first-time saver again
+        let goal_id = client.create_goal(&user, &1000, &THIRTY_DAYS, &500, &None, &None, &0);
+        client.transfer_goal(&user, &friend, &goal_id);
+        assert_eq!(client.get_user_goal_count(&user), 0);
+        assert_eq!(client.get_unique_user_count(), 1);
+        client.create_goal(&user, &1000, &THIRTY_DAYS, &500, &None, &None, &0);
+        assert_eq!(client.get_unique_user_count(), 1);
+
+        client.create_goal(&friend, &1000, &THIRTY_DAYS, &500, &None, &None, &0);
+        assert_eq!(client.get_unique_user_count(), 2);
+    }
+
+    #[test]
+    fn test_linked_asset() {
+        let Setup {
+            env, client, user, ..
+        } = setup();
+
+        let goal_id = client.create_goal(&user, &10000, &THIRTY_DAYS, &500, &None, &None, &0);
+        assert_eq!(
+            client.get_goal(&user, &goal_id).linked_asset,
+            LinkedAsset::None
+        );
+
+        let linked = LinkedAsset::Position(Address::generate(&env), 42);
+        client.set_linked_asset(&user, &goal_id, &linked);
+        assert_eq!(client.get_goal(&user, &goal_id).linked_asset, linked);
+
+        client.set_linked_asset(&user, &goal_id, &LinkedAsset::None);
+        assert_eq!(
+            client.get_goal(&user, &goal_id).linked_asset,
+            LinkedAsset::None
+        );
+    }
+
+    #[test]
+    fn test_guaranteed_minimum_topped_up_from_reserve() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            token,
+        } = setup();
+
+        StellarAssetClient::new(&env, &token.address).mint(&admin, &1000);
+        client.fund_reserve(&admin, &1000);
+        assert_eq!(client.get_reserve_balance(), 1000);
+
+        // 10% guaranteed on a 0% goal over one year
+        client.set_guaranteed_min_rate(&admin, &1000);
+        let one_year = SECONDS_PER_YEAR as u64;
+        let goal_id = client.create_goal(&user, &5000, &one_year, &0, &None, &None, &0);
+
+        env.ledger().with_mut(|li| li.timestamp = one_year);
+        assert_eq!(client.withdraw(&user, &goal_id), 5500);
+        assert_eq!(client.get_reserve_balance(), 500);
+    }
+
+    #[test]
+    fn test_guarantee_strict_blocks_on_empty_reserve() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+
+        client.set_guaranteed_min_rate(&admin, &1000);
+        client.set_guarantee_strict(&admin, &true);
+        let goal_id = client.create_goal(&user, &5000, &THIRTY_DAYS, &0, &None, &None, &0);
+
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS);
+        assert_eq!(
+            client.try_withdraw(&user, &goal_id),
+            Err(Ok(Error::InsufficientReserve))
+        );
+        assert!(client.get_goal(&user, &goal_id).is_active);
+    }
+
+    #[test]
+    fn test_guarantee_best_effort_pays_accrued_on_empty_reserve() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+
+        // Best-effort is the default
+        client.set_guaranteed_min_rate(&admin, &1000);
+        let goal_id = client.create_goal(&user, &5000, &THIRTY_DAYS, &0, &None, &None, &0);
+
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS);
+        assert_eq!(client.withdraw(&user, &goal_id), 5000);
+        assert_eq!(client.get_reserve_balance(), 0);
+    }
+
+    #[test]
+    fn test_health_report() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            token,
+        } = setup();
+
+        // No goals: nothing owed, nothing accruing
+        let report = client.get_health();
+        assert_eq!(report.active_goals, 0);
+        assert_eq!(report.status, HealthStatus::Healthy);
+
+        // A 10% goal with no reserve has no runway
+        client.create_goal(&user, &10000, &THIRTY_DAYS, &1000, &None, &None, &0);
+        let report = client.get_health();
+        assert_eq!(report.active_goals, 1);
+        assert_eq!(report.runway, 0);
+        assert_eq!(report.solvency_bps, 110000); // 110000 held / 10000 owed
+        assert_eq!(report.status, HealthStatus::Critical);
+
+        // 100 tokens of reserve covers 1000 per year of interest for 36.5 days
+        StellarAssetClient::new(&env, &token.address).mint(&admin, &100);
+        client.fund_reserve(&admin, &100);
+        let report = client.get_health();
+        assert_eq!(report.runway, 3153600);
+        assert_eq!(report.status, HealthStatus::Healthy);
+
+        client.set_health_thresholds(
+            &admin,
+            &HealthThresholds {
+                warning_solvency_bps: 10000,
+                critical_solvency_bps: 9000,
+                warning_runway: 2 * 3153600,
+                critical_runway: 3153600,
+            },
+        );
+        assert_eq!(client.get_health().status, HealthStatus::Warning);
+    }
+
+    #[test]
+    fn test_drawdown_installments() {
+        let Setup {
+            env,
+            client,
+            user,
+            token,
+            ..
+        } = setup();
+
+        let goal_id = client.create_goal(&user, &10000, &THIRTY_DAYS, &0, &None, &None, &0);
+        client.setup_drawdown(&user, &goal_id, &4000, &86400);
+        assert_eq!(
+            client.get_drawdown(&user, &goal_id).unwrap().next_installment_time,
+            THIRTY_DAYS
+        );
+
+        // Nothing can be claimed before maturity
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS - 1);
+        assert_eq!(
+            client.try_claim_installment(&user, &goal_id),
+            Err(Ok(Error::StillLocked))
+        );
+
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS);
+        assert_eq!(client.claim_installment(&user, &goal_id), 4000);
+        assert_eq!(
+            client.try_claim_installment(&user, &goal_id),
+            Err(Ok(Error::InstallmentNotDue))
+        );
+
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS + 86400);
+        assert_eq!(client.claim_installment(&user, &goal_id), 4000);
+        assert!(client.get_goal(&user, &goal_id).is_active);
+
+        // The final installment releases only what's left and closes the goal
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS + 2 * 86400);
+        assert_eq!(client.claim_installment(&user, &goal_id), 2000);
+        assert!(!client.get_goal(&user, &goal_id).is_active);
+        assert_eq!(client.get_drawdown(&user, &goal_id), None);
+        assert_eq!(token.balance(&user), 10000);
+    }
+
+    #[test]
+    fn test_sustainable_rate_warning() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            token,
+        } = setup();
+
+        StellarAssetClient::new(&env, &token.address).mint(&admin, &500);
+        client.fund_reserve(&admin, &500);
+
+        // 500 of reserve sustains 10% on 5000 of TVL
+        client.create_goal(&user, &5000, &THIRTY_DAYS, &1000, &None, &None, &0);
+        assert_eq!(count_events(&env, symbol_short!("rate_warn")), 0);
+        assert_eq!(client.get_sustainable_rate_bps(), 1000);
+
+        // Doubling TVL halves the sustainable rate; an aggressive goal is
+        // accepted but flagged
+        let goal_id = client.create_goal(&user, &5000, &THIRTY_DAYS, &2000, &None, &None, &0);
+        let events = env.events().all();
+        assert_eq!(
+            events.slice(events.len() - 1..),
+            vec![
+                &env,
+                (
+                    client.address.clone(),
+                    (
+                        symbol_short!("savings"),
+                        symbol_short!("rate_warn"),
+                        user.clone(),
+                        goal_id
+                    )
+                        .into_val(&env),
+                    (2000u32, 500u32).into_val(&env),
+                )
+            ]
+        );
+        assert_eq!(client.get_sustainable_rate_bps(), 500);
+        assert!(client.get_goal(&user, &goal_id).is_active);
+    }
+
+    #[test]
+    fn test_get_stale_goals() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            token,
+        } = setup();
+        fund_reserve(&env, &client, &admin, 10000);
+
+        let other = Address::generate(&env);
+        StellarAssetClient::new(&env, &token.address).mint(&other, &10000);
+
+        let first = client.create_goal(&user, &1000, &THIRTY_DAYS, &500, &None, &None, &0);
+        let second = client.create_goal(&other, &1000, &THIRTY_DAYS, &500, &None, &None, &0);
+        let third = client.create_goal(&user, &1000, &THIRTY_DAYS, &500, &None, &None, &0);
+
+        // The withdrawn goal is skipped along with the freshly compounded one
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS);
+        client.compound_interest(&user, &first);
+        client.withdraw(&other, &second);
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS + 3600);
+
+        assert_eq!(
+            client.get_stale_goals(&7200, &0, &10),
+            vec![&env, (user.clone(), third)]
+        );
+
+        // Paging covers [start_id, start_id + limit) and stops at the counter
+        assert_eq!(
+            client.get_stale_goals(&0, &0, &1),
+            vec![&env, (user.clone(), first)]
+        );
+        assert_eq!(client.get_stale_goals(&0, &1, &1).len(), 0);
+        assert_eq!(client.get_stale_goals(&0, &3, &10).len(), 0);
+    }
+
+    #[test]
+    fn test_quote_in_unit() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+
+        let goal_id = client.create_goal(&user, &10000, &THIRTY_DAYS, &0, &None, &None, &0);
+        assert_eq!(
+            client.try_quote_in_unit(&user, &goal_id),
+            Err(Ok(Error::OracleNotConfigured))
+        );
+
+        let oracle_id = env.register(MockOracle, ());
+        let oracle = MockOracleClient::new(&env, &oracle_id);
+        client.set_price_oracle(&admin, &oracle_id, &3600);
+        assert_eq!(
+            client.try_quote_in_unit(&user, &goal_id),
+            Err(Ok(Error::PriceUnavailable))
+        );
+
+        // 0.25 units per token
+        env.ledger().with_mut(|li| li.timestamp = 7200);
+        oracle.set_price(&2500000, &7200);
+        assert_eq!(client.quote_in_unit(&user, &goal_id), 2500);
+
+        env.ledger().with_mut(|li| li.timestamp = 7200 + 3601);
+        assert_eq!(
+            client.try_quote_in_unit(&user, &goal_id),
+            Err(Ok(Error::PriceStale))
+        );
+
+        // Quoting never touches the goal
+        assert_eq!(client.get_current_balance(&user, &goal_id), 10000);
+    }
+
+    #[test]
+    fn test_withdraw_principal_only_and_claim_iou() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            token,
+        } = setup();
+
+        let one_year = SECONDS_PER_YEAR as u64;
+        let goal_id = client.create_goal(&user, &10000, &one_year, &500, &None, &None, &0);
+
+        env.ledger().with_mut(|li| li.timestamp = one_year);
+        assert_eq!(client.withdraw_principal_only(&user, &goal_id), 10000);
+        assert_eq!(token.balance(&user), 10000);
+        assert_eq!(client.get_iou(&user), 500);
+        assert!(!client.get_goal(&user, &goal_id).is_active);
+
+        // The IOU can't be redeemed until the reserve covers it
+        assert_eq!(
+            client.try_claim_iou(&user),
+            Err(Ok(Error::InsufficientReserve))
+        );
+
+        StellarAssetClient::new(&env, &token.address).mint(&admin, &500);
+        client.fund_reserve(&admin, &500);
+        assert_eq!(client.claim_iou(&user), 500);
+        assert_eq!(token.balance(&user), 10500);
+        assert_eq!(client.get_iou(&user), 0);
+        assert_eq!(client.get_reserve_balance(), 0);
+        assert_eq!(client.try_claim_iou(&user), Err(Ok(Error::NoIou)));
+    }
+
+    #[test]
+    fn test_withdraw_principal_only_pays_interest_the_reserve_covers() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            token,
+        } = setup();
+        fund_reserve(&env, &client, &admin, 500);
+
+        let one_year = SECONDS_PER_YEAR as u64;
+        let goal_id = client.create_goal(&user, &10000, &one_year, &500, &None, &None, &0);
+
+        env.ledger().with_mut(|li| li.timestamp = one_year);
+        assert_eq!(client.withdraw_principal_only(&user, &goal_id), 10500);
+        assert_eq!(token.balance(&user), 10500);
+        assert_eq!(client.get_iou(&user), 0);
+        assert_eq!(client.get_reserve_balance(), 0);
+        assert!(!client.get_goal(&user, &goal_id).is_active);
+    }
+
+    #[test]
+    fn test_deposit_keeps_unlock_by_default() {
+        let Setup {
+            env, client, user, ..
+        } = setup();
+
+        let goal_id = client.create_goal(&user, &6000, &THIRTY_DAYS, &0, &None, &None, &0);
+
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS / 2);
+        assert_eq!(client.deposit(&user, &goal_id, &4000), 10000);
+
+        let goal = client.get_goal(&user, &goal_id);
+        assert_eq!(goal.principal, 10000);
+        assert_eq!(goal.unlock_time, THIRTY_DAYS);
+        assert_eq!(goal.lock_duration, THIRTY_DAYS);
+    }
+
+    #[test]
+    fn test_deposit_extends_to_blended_maturity() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+
+        client.set_top_up_mode(&admin, &TopUpMode::ExtendBlended);
+        let goal_id = client.create_goal(&user, &6000, &THIRTY_DAYS, &0, &None, &None, &0);
+
+        // Halfway through: 6000 with 15 days left, 4000 for a fresh 30 days
+        let halfway = THIRTY_DAYS / 2;
+        env.ledger().with_mut(|li| li.timestamp = halfway);
+        client.deposit(&user, &goal_id, &4000);
+
+        let blended = (6000 * halfway + 4000 * THIRTY_DAYS) / 10000;
+        let goal = client.get_goal(&user, &goal_id);
+        assert_eq!(goal.unlock_time, halfway + blended);
+        assert_eq!(goal.lock_duration, halfway + blended);
+    }
+
+    #[cfg(feature = "diagnostics")]
+    #[test]
+    fn test_diagnose_goal_reports_failing_step() {
+        let Setup {
+            env, client, user, ..
+        } = setup();
+
+        let goal_id = client.create_goal(&user, &10000, &THIRTY_DAYS, &500, &None, &None, &0);
+        assert_eq!(
+            client.diagnose_goal(&user, &goal_id),
+            ErrorContext::None as u32
+        );
+
+        // Force a balance that overflows once multiplied by the rate
+        let mut goal = client.get_goal(&user, &goal_id);
+        goal.principal = i128::MAX / 100;
+        env.as_contract(&client.address, || {
+            env.storage()
+                .persistent()
+                .set(&StorageKey::Goal(user.clone(), goal_id), &goal);
+        });
+        assert_eq!(
+            client.try_get_current_balance(&user, &goal_id),
+            Err(Ok(Error::Overflow))
+        );
+        assert_eq!(
+            client.diagnose_goal(&user, &goal_id),
+            ErrorContext::InterestRateMul as u32
+        );
+
+        // A smaller balance whose interest only overflows after multiplying
+        // by time, which takes millennia now that time is applied after the
+        // division
+        goal.principal = i128::MAX / 1_000;
+        env.as_contract(&client.address, || {
+            env.storage()
+                .persistent()
+                .set(&StorageKey::Goal(user.clone(), goal_id), &goal);
+        });
+        env.ledger().with_mut(|li| li.timestamp = 1_000_000_000_000);
+        assert_eq!(
+            client.diagnose_goal(&user, &goal_id),
+            ErrorContext::InterestTimeMul as u32
+        );
+    }
+
+    #[test]
+    fn test_renewable_goals_and_renewal() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+        fund_reserve(&env, &client, &admin, 10000);
+
+        let renewing = client.create_goal(&user, &5000, &THIRTY_DAYS, &1000, &None, &None, &0);
+        let plain = client.create_goal(&user, &1000, &THIRTY_DAYS, &1000, &None, &None, &0);
+        let later = client.create_goal(&user, &1000, &(2 * THIRTY_DAYS), &1000, &None, &None, &0);
+        client.set_auto_renew(&user, &renewing, &true);
+        client.set_auto_renew(&user, &later, &true);
+
+        // Nothing is renewable before maturity
+        assert_eq!(client.get_renewable_goals(&0, &10).len(), 0);
+        assert_eq!(
+            client.try_process_renewal(&user, &renewing),
+            Err(Ok(Error::StillLocked))
+        );
+
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS);
+        assert_eq!(
+            client.get_renewable_goals(&0, &10),
+            vec![&env, (user.clone(), renewing)]
+        );
+        assert_eq!(
+            client.try_process_renewal(&user, &plain),
+            Err(Ok(Error::NotRenewable))
+        );
+
+        let balance = client.get_current_balance(&user, &renewing);
+        assert_eq!(client.process_renewal(&user, &renewing), 2 * THIRTY_DAYS);
+
+        let goal = client.get_goal(&user, &renewing);
+        assert_eq!(goal.principal, balance);
+        assert_eq!(goal.accrued_interest, 0);
+        assert_eq!(goal.start_time, THIRTY_DAYS);
+        assert_eq!(client.get_renewable_goals(&0, &10).len(), 0);
+    }
+
+    #[test]
+    fn test_withdraw_after_long_gap_pays_current_balance_exactly() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+        fund_reserve(&env, &client, &admin, 10000);
+
+        let one_year = SECONDS_PER_YEAR as u64;
+        let untouched = client.create_goal(&user, &5000, &one_year, &500, &None, &None, &0);
+        let compounded = client.create_goal(&user, &5000, &one_year, &500, &None, &None, &0);
+
+        env.ledger().with_mut(|li| li.timestamp = one_year);
+        client.compound_interest(&user, &compounded);
+
+        // Three years after creation, payouts equal the quoted balance exactly
+        env.ledger().with_mut(|li| li.timestamp = 3 * one_year);
+
+        let expected = client.get_current_balance(&user, &untouched);
+        assert_eq!(expected, 5750);
+        assert_eq!(client.withdraw(&user, &untouched), expected);
+
+        let expected = client.get_current_balance(&user, &compounded);
+        assert_eq!(expected, 5775);
+        assert_eq!(client.withdraw(&user, &compounded), expected);
+    }
+
+    #[test]
+    fn test_pause_token_isolated_per_token() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            token,
+        } = setup();
+
+        let goal_id = client.create_goal(&user, &5000, &THIRTY_DAYS, &0, &None, &None, &0);
+
+        // Pausing an unrelated token leaves this one working
+        let other_token = env
+            .register_stellar_asset_contract_v2(admin.clone())
+            .address();
+        client.pause_token(&admin, &other_token);
+        assert!(client.is_token_paused(&other_token));
+        client.deposit(&user, &goal_id, &1000);
+
+        client.pause_token(&admin, &token.address);
+        assert_eq!(
+            client.try_create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0),
+            Err(Ok(Error::TokenPaused))
+        );
+        assert_eq!(
+            client.try_deposit(&user, &goal_id, &1000),
+            Err(Ok(Error::TokenPaused))
+        );
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS);
+        assert_eq!(
+            client.try_withdraw(&user, &goal_id),
+            Err(Ok(Error::TokenPaused))
+        );
+
+        client.unpause_token(&admin, &token.address);
+        assert_eq!(client.withdraw(&user, &goal_id), 6000);
+        assert!(client.is_token_paused(&other_token));
+    }
+
+    #[test]
+    fn test_goal_records_creation_ledger() {
+        let Setup {
+            env, client, user, ..
+        } = setup();
+
+        env.ledger().with_mut(|li| li.sequence_number = 42);
+        let goal_id = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+
+        env.ledger().with_mut(|li| li.sequence_number = 50);
+        assert_eq!(client.get_goal(&user, &goal_id).created_ledger, 42);
+        assert_eq!(client.get_goal_created_ledger(&user, &goal_id), 42);
+        assert_eq!(
+            client.try_get_goal_created_ledger(&user, &99),
+            Err(Ok(Error::GoalNotFound))
+        );
+    }
+
+    #[test]
+    fn test_supported_tokens_cache_decimals() {
+        let Setup {
+            env,
+            client,
+            admin,
+            token,
+            ..
+        } = setup();
+
+        // The default token is registered at initialization
+        assert_eq!(client.get_token_decimals(&token.address), 7);
+
+        let eighteen = env.register(MockDecimalsToken, (18u32,));
+        let six = env.register(MockDecimalsToken, (6u32,));
+        assert_eq!(
+            client.try_get_token_decimals(&eighteen),
+            Err(Ok(Error::TokenNotSupported))
+        );
+
+        assert_eq!(client.add_supported_token(&admin, &eighteen), 18);
+        assert_eq!(client.add_supported_token(&admin, &six), 6);
+        assert_eq!(client.get_token_decimals(&eighteen), 18);
+        assert_eq!(client.get_token_decimals(&six), 6);
+
+        client.remove_supported_token(&admin, &six);
+        assert_eq!(
+            client.try_get_token_decimals(&six),
+            Err(Ok(Error::TokenNotSupported))
+        );
+        assert_eq!(
+            client.try_remove_supported_token(&admin, &token.address),
+            Err(Ok(Error::InvalidToken))
+        );
+    }
+
+    #[test]
+    fn test_add_supported_token_rejects_failing_decimals() {
+        let Setup {
+            env, client, admin, ..
+        } = setup();
+
+        // A contract without `decimals()` isn't accepted as a token
+        let not_a_token = env.register(TimeLockedSavings, ());
+        assert_eq!(
+            client.try_add_supported_token(&admin, &not_a_token),
+            Err(Ok(Error::InvalidToken))
+        );
+    }
+
+    #[test]
+    fn test_penalty_free_available() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+        fund_reserve(&env, &client, &admin, 10000);
+
+        let one_year = SECONDS_PER_YEAR as u64;
+        let goal_id = client.create_goal(&user, &10000, &one_year, &500, &None, &None, &0);
+
+        env.ledger().with_mut(|li| li.timestamp = one_year - 1);
+        assert_eq!(client.get_penalty_free_available(&user, &goal_id), 0);
+
+        env.ledger().with_mut(|li| li.timestamp = one_year);
+        assert_eq!(client.get_penalty_free_available(&user, &goal_id), 10500);
+
+        // Nothing is available until a required withdrawal request matures
+        client.set_withdraw_cooldown(&admin, &3600);
+        assert_eq!(client.get_penalty_free_available(&user, &goal_id), 0);
+        client.request_withdraw(&user, &goal_id);
+        env.ledger().with_mut(|li| li.timestamp = one_year + 3600);
+        let available = client.get_penalty_free_available(&user, &goal_id);
+        assert_eq!(available, client.get_current_balance(&user, &goal_id));
+        assert_eq!(client.claim_withdraw(&user, &goal_id), available);
+
+        assert_eq!(client.get_penalty_free_available(&user, &goal_id), 0);
+    }
+
+    #[test]
+    fn test_event_topics_use_configured_namespace() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+
+        assert_eq!(client.get_event_namespace(), symbol_short!("savings"));
+
+        client.set_event_namespace(&admin, &symbol_short!("vault_b"));
+        assert_eq!(client.get_event_namespace(), symbol_short!("vault_b"));
+
+        // No reserve, so any positive rate triggers a warning event
+        let goal_id = client.create_goal(&user, &1000, &THIRTY_DAYS, &500, &None, &None, &0);
+        let events = env.events().all();
+        let (_, topics, _) = events.last().unwrap();
+        assert_eq!(
+            topics,
+            (
+                symbol_short!("vault_b"),
+                symbol_short!("rate_warn"),
+                user.clone(),
+                goal_id
+            )
+                .into_val(&env)
+        );
+        assert_eq!(
+            client.try_set_event_namespace(&user, &symbol_short!("other")),
+            Err(Ok(Error::Unauthorized))
+        );
+    }
+
+    #[test]
+    fn test_rebalance_reserve_recycles_penalties() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            token,
+        } = setup();
+
+        // Recycle 40% of each penalty into the reserve
+        client.set_penalty_reserve_share(&admin, &4000);
+        assert_eq!(
+            client.try_set_penalty_reserve_share(&admin, &10001),
+            Err(Ok(Error::InvalidAmount))
+        );
+        assert_eq!(client.rebalance_reserve(), 0);
+
+        let goal_id = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        client.emergency_withdraw(&user, &goal_id);
+
+        // 10% penalty of 100: 40 held back, 60 paid to admin
+        assert_eq!(token.balance(&admin), 60);
+        assert_eq!(client.get_pending_penalty_revenue(), 40);
+        assert_eq!(client.get_reserve_balance(), 0);
+
+        assert_eq!(client.rebalance_reserve(), 40);
+        let events = env.events().all();
+        assert_eq!(
+            events.slice(events.len() - 1..),
+            vec![
+                &env,
+                (
+                    client.address.clone(),
+                    (symbol_short!("savings"), symbol_short!("rebalance")).into_val(&env),
+                    40i128.into_val(&env)
+                )
+            ]
+        );
+        assert_eq!(client.get_pending_penalty_revenue(), 0);
+        assert_eq!(client.get_reserve_balance(), 40);
+        assert_eq!(client.rebalance_reserve(), 0);
+    }
+
+    #[test]
+    fn test_compound_count_increments_on_credit() {
+        let Setup {
+            env, client, user, ..
+        } = setup();
+
+        let goal_id = client.create_goal(&user, &9000, &THIRTY_DAYS, &500, &None, &None, &0);
+        assert_eq!(client.get_goal(&user, &goal_id).compound_count, 0);
+
+        // Same timestamp: nothing credited, count unchanged
+        client.compound_interest(&user, &goal_id);
+        assert_eq!(client.get_goal(&user, &goal_id).compound_count, 0);
+
+        env.ledger().with_mut(|li| li.timestamp += 86400);
+        client.compound_interest(&user, &goal_id);
+        env.ledger().with_mut(|li| li.timestamp += 86400);
+        client.compound_interest(&user, &goal_id);
+        assert_eq!(client.get_goal(&user, &goal_id).compound_count, 2);
+
+        // Zero-rate goals never credit interest
+        let flat = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        env.ledger().with_mut(|li| li.timestamp += 86400);
+        client.compound_interest(&user, &flat);
+        assert_eq!(client.get_goal(&user, &flat).compound_count, 0);
+    }
+
+    #[test]
+    fn test_initialize_full_ready_for_goals() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let client = TimeLockedSavingsClient::new(&env, &env.register(TimeLockedSavings, ()));
+        let admin = Address::generate(&env);
+        let user = Address::generate(&env);
+        let token_id = env.register_stellar_asset_contract_v2(admin.clone());
+        let extra_id = env.register_stellar_asset_contract_v2(admin.clone());
+        StellarAssetClient::new(&env, &token_id.address()).mint(&user, &10000);
+
+        let mut config = FullConfig {
+            token: token_id.address(),
+            admin: admin.clone(),
+            emergency_penalty: 2000,
+            penalty_reserve_share_bps: 5000,
+            withdraw_cooldown: 3600,
+            guaranteed_min_rate: 100,
+            guarantee_strict: true,
+            health_thresholds: HealthThresholds {
+                warning_solvency_bps: 10000,
+                critical_solvency_bps: 9500,
+                warning_runway: 86400,
+                critical_runway: 3600,
+            },
+            top_up_mode: TopUpMode::ExtendBlended,
+            event_namespace: symbol_short!("fleet_7"),
+            extra_tokens: vec![&env, extra_id.address()],
+            rate_tiers: vec![&env],
+            min_deposit: None,
+            max_deposit: None,
+            treasury: None,
+            max_goals_per_user: None,
+        };
+
+        // An invalid setting reverts the whole call, leaving it uninitialized
+        config.health_thresholds.critical_runway = 100000;
+        assert_eq!(
+            client.try_initialize_full(&config),
+            Err(Ok(Error::InvalidThresholds))
+        );
+        config.health_thresholds.critical_runway = 3600;
+
+        client.initialize_full(&config);
+        assert_eq!(
+            client.try_initialize_full(&config),
+            Err(Ok(Error::AlreadyInitialized))
+        );
+        assert_eq!(client.get_withdraw_cooldown(), 3600);
+        assert_eq!(client.get_event_namespace(), symbol_short!("fleet_7"));
+        assert_eq!(client.get_token_decimals(&extra_id.address()), 7);
+
+        // Ready for goals immediately
+        let goal_id = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        assert_eq!(client.emergency_withdraw(&user, &goal_id), 800);
+        assert_eq!(client.get_pending_penalty_revenue(), 100);
+    }
+
+    #[test]
+    fn test_withdraw_split_even_and_uneven() {
+        let Setup {
+            env,
+            client,
+            user,
+            token,
+            ..
+        } = setup();
+        let alice = Address::generate(&env);
+        let bob = Address::generate(&env);
+        let carol = Address::generate(&env);
+
+        let even = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        let uneven = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+
+        assert_eq!(
+            client.try_withdraw_split(&user, &even, &vec![&env]),
+            Err(Ok(Error::InvalidSplit))
+        );
+        assert_eq!(
+            client.try_withdraw_split(&user, &even, &vec![&env, (alice.clone(), 9999u32)]),
+            Err(Ok(Error::InvalidSplit))
+        );
+        assert_eq!(
+            client.try_withdraw_split(
+                &user,
+                &even,
+                &vec![&env, (alice.clone(), 10000u32), (bob.clone(), 0u32)]
+            ),
+            Err(Ok(Error::InvalidSplit))
+        );
+        assert_eq!(
+            client.try_withdraw_split(&user, &even, &vec![&env, (alice.clone(), 10000u32)]),
+            Err(Ok(Error::StillLocked))
+        );
+
+        env.ledger().with_mut(|li| li.timestamp += THIRTY_DAYS);
+
+        let halves = vec![&env, (alice.clone(), 5000u32), (bob.clone(), 5000u32)];
+        assert_eq!(client.withdraw_split(&user, &even, &halves), 1000);
+        assert_eq!(token.balance(&alice), 500);
+        assert_eq!(token.balance(&bob), 500);
+
+        // 1000 * 3333 / 10000 rounds down to 333 for bob and carol; alice
+        // gets the remaining 334
+        let thirds = vec![
+            &env,
+            (alice.clone(), 3334u32),
+            (bob.clone(), 3333u32),
+            (carol.clone(), 3333u32),
+        ];
+        assert_eq!(client.withdraw_split(&user, &uneven, &thirds), 1000);
+        assert_eq!(token.balance(&alice), 500 + 334);
+        assert_eq!(token.balance(&bob), 500 + 333);
+        assert_eq!(token.balance(&carol), 333);
+        assert_eq!(
+            client.try_withdraw_split(&user, &uneven, &thirds),
+            Err(Ok(Error::GoalInactive))
+        );
+    }
+
+    #[test]
+    fn test_get_ledger_time_matches_unlock_clock() {
+        let Setup {
+            env, client, user, ..
+        } = setup();
+
+        env.ledger().with_mut(|li| li.timestamp = 1_700_000_000);
+        assert_eq!(client.get_ledger_time(), 1_700_000_000);
+
+        let goal_id = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        assert_eq!(
+            client.get_goal(&user, &goal_id).unlock_time,
+            client.get_ledger_time() + THIRTY_DAYS
+        );
+    }
+
+    #[test]
+    fn test_prepaid_goal_reserves_and_pays_interest() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            token,
+        } = setup();
+        let sponsor = Address::generate(&env);
+        StellarAssetClient::new(&env, &token.address).mint(&sponsor, &1000);
+
+        // Strict guarantee with an empty reserve blocks ordinary goals
+        client.set_guaranteed_min_rate(&admin, &100);
+        client.set_guarantee_strict(&admin, &true);
+        let ordinary = client.create_goal(&user, &5000, &THIRTY_DAYS, &0, &None, &None, &0);
+
+        // 1000 bps on 3650 for 30 days prepays 30
+        let prepaid = client.create_goal_prepaid(&user, &sponsor, &3650, &THIRTY_DAYS, &1000);
+        assert_eq!(client.get_goal(&user, &prepaid).prepaid_interest, 30);
+        assert_eq!(token.balance(&sponsor), 970);
+        assert_eq!(client.get_prepaid_reserve(), 30);
+        assert_eq!(client.get_reserve_balance(), 0);
+
+        env.ledger().with_mut(|li| li.timestamp += THIRTY_DAYS);
+        assert_eq!(
+            client.try_withdraw(&user, &ordinary),
+            Err(Ok(Error::InsufficientReserve))
+        );
+
+        let before = token.balance(&user);
+        assert_eq!(client.withdraw(&user, &prepaid), 3680);
+        assert_eq!(token.balance(&user), before + 3680);
+        assert_eq!(client.get_prepaid_reserve(), 0);
+    }
+
+    #[test]
+    fn test_is_goal_funded() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            token,
+        } = setup();
+        StellarAssetClient::new(&env, &token.address).mint(&admin, &1000);
+
+        // 1000 bps on 3650 for 30 days projects 30 of interest
+        let goal_id = client.create_goal(&user, &3650, &THIRTY_DAYS, &1000, &None, &None, &0);
+        assert!(!client.is_goal_funded(&user, &goal_id));
+
+        client.fund_reserve(&admin, &29);
+        assert!(!client.is_goal_funded(&user, &goal_id));
+        client.fund_reserve(&admin, &1);
+        assert!(client.is_goal_funded(&user, &goal_id));
+
+        let prepaid = client.create_goal_prepaid(&user, &user, &3650, &THIRTY_DAYS, &5000);
+        assert!(client.is_goal_funded(&user, &prepaid));
+
+        client.emergency_withdraw(&user, &goal_id);
+        assert!(!client.is_goal_funded(&user, &goal_id));
+    }
+
+    #[test]
+    fn test_decaying_emergency_penalty() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+
+        client.set_penalty_mode(&admin, &PenaltyMode::LinearDecay);
+        let at_start = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        let at_midpoint = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        let near_maturity = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+
+        // Full 10% right after deposit
+        assert_eq!(client.emergency_withdraw(&user, &at_start), 900);
+
+        // Half the lock left: 5%
+        env.ledger().with_mut(|li| li.timestamp += THIRTY_DAYS / 2);
+        assert_eq!(client.emergency_withdraw(&user, &at_midpoint), 950);
+
+        // 1% of the lock left: 10 bps, a penalty of 1
+        env.ledger()
+            .with_mut(|li| li.timestamp += THIRTY_DAYS / 2 - THIRTY_DAYS / 100);
+        assert_eq!(client.emergency_withdraw(&user, &near_maturity), 999);
+
+        // Flat penalty again once disabled
+        client.set_penalty_mode(&admin, &PenaltyMode::Flat);
+        let flat = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        env.ledger().with_mut(|li| li.timestamp += THIRTY_DAYS / 2);
+        assert_eq!(client.emergency_withdraw(&user, &flat), 900);
+    }
+
+    #[test]
+    fn test_average_maturity() {
+        let Setup {
+            env, client, user, ..
+        } = setup();
+
+        assert_eq!(client.get_average_maturity(), 0);
+
+        // 1000 unlocking in 30 days and 3000 in 90 days: (30 + 270) / 4 = 75 days
+        let short = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        client.create_goal(&user, &3000, &(3 * THIRTY_DAYS), &0, &None, &None, &0);
+        assert_eq!(client.get_average_maturity(), 5 * THIRTY_DAYS / 2);
+
+        env.ledger().with_mut(|li| li.timestamp += THIRTY_DAYS);
+        assert_eq!(client.get_average_maturity(), 3 * THIRTY_DAYS / 2);
+
+        // Withdrawing the matured goal leaves only the 60 days remaining
+        client.withdraw(&user, &short);
+        assert_eq!(client.get_average_maturity(), 2 * THIRTY_DAYS);
+    }
+
+    #[test]
+    fn test_relock_matured_goal_in_place() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            token,
+        } = setup();
+        fund_reserve(&env, &client, &admin, 10000);
+
+        // 1000 bps on 3650 for 30 days earns 30
+        let goal_id = client.create_goal(&user, &3650, &THIRTY_DAYS, &1000, &None, &None, &0);
+        assert_eq!(
+            client.try_relock(&user, &goal_id, &THIRTY_DAYS, &500),
+            Err(Ok(Error::StillLocked))
+        );
+
+        env.ledger().with_mut(|li| li.timestamp += THIRTY_DAYS);
+        assert_eq!(
+            client.try_relock(&user, &goal_id, &1, &500),
+            Err(Ok(Error::InvalidDuration))
+        );
+        assert_eq!(
+            client.try_relock(&user, &goal_id, &THIRTY_DAYS, &(MAX_INTEREST_RATE + 1)),
+            Err(Ok(Error::RateTooHigh))
+        );
+
+        let user_balance = token.balance(&user);
+        let contract_balance = token.balance(&client.address);
+        let now = env.ledger().timestamp();
+
+        assert_eq!(
+            client.relock(&user, &goal_id, &(2 * THIRTY_DAYS), &500),
+            now + 2 * THIRTY_DAYS
+        );
+
+        let goal = client.get_goal(&user, &goal_id);
+        assert_eq!(goal.principal, 3680);
+        assert_eq!(goal.accrued_interest, 0);
+        assert_eq!(goal.interest_rate, 500);
+        assert_eq!(goal.start_time, now);
+        assert_eq!(goal.lock_duration, 2 * THIRTY_DAYS);
+        assert_eq!(token.balance(&user), user_balance);
+        assert_eq!(token.balance(&client.address), contract_balance);
+    }
+
+    /// Topics and data of the last event named `name` in the last invocation
+    fn last_event(env: &Env, name: Symbol) -> (Vec<Val>, Val) {
+        let (_, topics, data) = env
+            .events()
+            .all()
+            .iter()
+            .filter(|(_, topics, _)| {
+                topics
+                    .get(1)
+                    .and_then(|topic| Symbol::try_from_val(env, &topic).ok())
+                    == Some(name.clone())
+            })
+            .last()
+            .unwrap();
+        (topics, data)
+    }
+
+    #[test]
+    fn test_lifecycle_events() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+        fund_reserve(&env, &client, &admin, 10000);
+        let ns = symbol_short!("savings");
+
+        let goal_id = client.create_goal(&user, &3650, &THIRTY_DAYS, &1000, &None, &None, &0);
+        let (topics, data) = last_event(&env, symbol_short!("created"));
+        let unlock_time = client.get_goal(&user, &goal_id).unlock_time;
+        assert_eq!(
+            topics,
+            (ns.clone(), symbol_short!("created"), user.clone(), goal_id).into_val(&env)
+        );
+        assert_eq!(
+            <(i128, u32, u64, i128)>::try_from_val(&env, &data).unwrap(),
+            (3650, 1000, unlock_time, 0)
+        );
+
+        env.ledger().with_mut(|li| li.timestamp += THIRTY_DAYS);
+        client.withdraw(&user, &goal_id);
+        let (_, data) = last_event(&env, symbol_short!("compound"));
+        assert_eq!(<(i128, i128)>::try_from_val(&env, &data).unwrap(), (30, 30));
+        let (topics, data) = last_event(&env, symbol_short!("withdrawn"));
+        assert_eq!(
+            topics,
+            (ns.clone(), symbol_short!("withdrawn"), user.clone(), goal_id).into_val(&env)
+        );
+        assert_eq!(
+            <(i128, bool, Address)>::try_from_val(&env, &data).unwrap(),
+            (3680, false, user.clone())
+        );
+
+        let early = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        client.emergency_withdraw(&user, &early);
+        assert_eq!(count_events(&env, symbol_short!("compound")), 0);
+        let (_, data) = last_event(&env, symbol_short!("withdrawn"));
+        assert_eq!(
+            <(i128, bool, Address)>::try_from_val(&env, &data).unwrap(),
+            (900, true, user.clone())
+        );
+    }
+
+    #[test]
+    fn test_withdraw_partial() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            token,
+        } = setup();
+        fund_reserve(&env, &client, &admin, 10000);
+
+        // 1000 bps on 3650 for 30 days earns 30
+        let goal_id = client.create_goal(&user, &3650, &THIRTY_DAYS, &1000, &None, &None, &0);
+        assert_eq!(
+            client.try_withdraw_partial(&user, &goal_id, &100),
+            Err(Ok(Error::StillLocked))
+        );
+
+        env.ledger().with_mut(|li| li.timestamp += THIRTY_DAYS);
+        assert_eq!(
+            client.try_withdraw_partial(&user, &goal_id, &3681),
+            Err(Ok(Error::AmountExceedsBalance))
+        );
+        assert_eq!(
+            client.try_withdraw_partial(&user, &goal_id, &0),
+            Err(Ok(Error::InvalidAmount))
+        );
+
+        // Interest goes first, then principal
+        let before = token.balance(&user);
+        assert_eq!(client.withdraw_partial(&user, &goal_id, &80), 80);
+        assert_eq!(token.balance(&user), before + 80);
+        let goal = client.get_goal(&user, &goal_id);
+        assert_eq!((goal.accrued_interest, goal.principal), (0, 3600));
+        assert!(goal.is_active);
+
+        // The full remaining balance closes the goal like withdraw
+        assert_eq!(client.withdraw_partial(&user, &goal_id, &3600), 3600);
+        assert!(!client.get_goal(&user, &goal_id).is_active);
+        assert_eq!(token.balance(&user), before + 3680);
+    }
+
+    #[test]
+    fn test_deposit_rejects_inactive_and_matured_goals() {
+        let Setup {
+            env, client, user, ..
+        } = setup();
+
+        let matured = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        let closed = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        assert_eq!(client.deposit(&user, &matured, &500), 1500);
+
+        client.emergency_withdraw(&user, &closed);
+        assert_eq!(
+            client.try_deposit(&user, &closed, &500),
+            Err(Ok(Error::GoalInactive))
+        );
+
+        env.ledger().with_mut(|li| li.timestamp += THIRTY_DAYS);
+        assert_eq!(
+            client.try_deposit(&user, &matured, &500),
+            Err(Ok(Error::GoalMatured))
+        );
+
+        // Re-locking makes the goal accept deposits again
+        client.relock(&user, &matured, &THIRTY_DAYS, &0);
+        assert_eq!(client.deposit(&user, &matured, &500), 2000);
+    }
+
+    #[test]
+    fn test_extend_lock() {
+        let Setup {
+            env, client, user, ..
+        } = setup();
+
+        let goal_id = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        let start = client.get_goal(&user, &goal_id).start_time;
+
+        assert_eq!(
+            client.try_extend_lock(&user, &goal_id, &(MIN_LOCK_DURATION - 1)),
+            Err(Ok(Error::InvalidDuration))
+        );
+        assert_eq!(
+            client.try_extend_lock(&user, &goal_id, &MAX_LOCK_DURATION),
+            Err(Ok(Error::InvalidDuration))
+        );
+
+        assert_eq!(
+            client.extend_lock(&user, &goal_id, &THIRTY_DAYS),
+            start + 2 * THIRTY_DAYS
+        );
+        let (topics, data) = last_event(&env, symbol_short!("extended"));
+        assert_eq!(
+            topics,
+            (symbol_short!("savings"), symbol_short!("extended"), user.clone(), goal_id)
+                .into_val(&env)
+        );
+        assert_eq!(u64::try_from_val(&env, &data).unwrap(), start + 2 * THIRTY_DAYS);
+        assert_eq!(client.get_goal(&user, &goal_id).lock_duration, 2 * THIRTY_DAYS);
+
+        // A matured goal is extended from now
+        env.ledger().with_mut(|li| li.timestamp += 3 * THIRTY_DAYS);
+        let now = env.ledger().timestamp();
+        assert_eq!(client.extend_lock(&user, &goal_id, &THIRTY_DAYS), now + THIRTY_DAYS);
+        assert_eq!(
+            client.try_withdraw(&user, &goal_id),
+            Err(Ok(Error::StillLocked))
+        );
+    }
+
+    #[test]
+    fn test_extend_lock_drops_pending_schedules() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+        client.set_withdraw_cooldown(&admin, &3600);
+        let goal_id = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+
+        // Requested and scheduled for the matured term, then extended
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS);
+        client.request_withdraw(&user, &goal_id);
+        client.setup_drawdown(&user, &goal_id, &100, &86400);
+        client.extend_lock(&user, &goal_id, &THIRTY_DAYS);
+
+        assert_eq!(client.get_withdraw_request(&user, &goal_id), None);
+        assert!(client.get_drawdown(&user, &goal_id).is_none());
+
+        // At the new unlock a fresh request must wait out the cooldown
+        env.ledger().with_mut(|li| li.timestamp = 2 * THIRTY_DAYS);
+        assert_eq!(
+            client.try_claim_withdraw(&user, &goal_id),
+            Err(Ok(Error::WithdrawNotRequested))
+        );
+    }
+
+    #[test]
+    fn test_goal_labels() {
+        let Setup {
+            env, client, user, ..
+        } = setup();
+
+        let label = String::from_str(&env, "House deposit");
+        let goal_id =
+            client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &Some(label.clone()), &None, &0);
+        assert_eq!(client.get_goal(&user, &goal_id).label, Some(label));
+
+        let too_long = String::from_str(&env, "a label that is longer than 32 bytes");
+        assert_eq!(
+            client.try_create_goal(
+                &user,
+                &1000,
+                &THIRTY_DAYS,
+                &0,
+                &Some(too_long.clone()),
+                &None,
+                &0,
+            ),
+            Err(Ok(Error::LabelTooLong))
+        );
+        assert_eq!(
+            client.try_set_goal_label(&user, &goal_id, &too_long),
+            Err(Ok(Error::LabelTooLong))
+        );
+
+        let renamed = String::from_str(&env, "Car");
+        client.set_goal_label(&user, &goal_id, &renamed);
+        assert_eq!(client.get_goal(&user, &goal_id).label, Some(renamed));
+
+        client.set_goal_label(&user, &goal_id, &String::from_str(&env, ""));
+        assert_eq!(client.get_goal(&user, &goal_id).label, None);
+    }
+
+    #[test]
+    fn test_periodic_compounding_beats_linear() {
+        let Setup {
+            env, client, user, ..
+        } = setup();
+        let year = 365 * 86400;
+
+        let linear = client.create_goal(&user, &4000, &year, &500, &None, &None, &0);
+        let daily = client.create_goal(&user, &4000, &year, &500, &None, &None, &0);
+        assert_eq!(
+            client.get_goal(&user, &daily).compound_mode,
+            CompoundMode::Linear
+        );
+        client.set_compound_mode(&user, &daily, &CompoundMode::Periodic);
+
+        env.ledger().with_mut(|li| li.timestamp += year);
+        client.compound_interest(&user, &linear);
+        client.compound_interest(&user, &daily);
+
+        // 4000 * 5% = 200 linear; 4000 * ((1 + 0.05/365)^365 - 1) = 205.07 daily
+        assert_eq!(client.get_goal(&user, &linear).accrued_interest, 200);
+        assert_eq!(client.get_goal(&user, &daily).accrued_interest, 205);
+    }
+
+    #[test]
+    fn test_list_user_goals() {
+        let Setup {
+            env,
+            client,
+            user,
+            token,
+            ..
+        } = setup();
+        let other = Address::generate(&env);
+        StellarAssetClient::new(&env, &token.address).mint(&other, &1000);
+
+        let first = client.create_goal(&user, &100, &THIRTY_DAYS, &0, &None, &None, &0);
+        client.create_goal(&other, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        let second = client.create_goal(&user, &200, &THIRTY_DAYS, &0, &None, &None, &0);
+        let third = client.create_goal(&user, &300, &THIRTY_DAYS, &0, &None, &None, &0);
+        client.emergency_withdraw(&user, &second);
+
+        assert_eq!(
+            client.get_user_goal_ids(&user, &0, &10),
+            vec![&env, first, second, third]
+        );
+        assert_eq!(client.get_user_goal_ids(&user, &1, &1), vec![&env, second]);
+        assert_eq!(client.get_user_goal_ids(&user, &5, &10).len(), 0);
+
+        let all = client.list_goals(&user, &0, &10, &true);
+        assert_eq!(all.len(), 3);
+        assert_eq!(all.get_unchecked(1).principal, 200);
+
+        let active = client.list_goals(&user, &0, &10, &false);
+        assert_eq!(active.len(), 2);
+        assert_eq!(active.get_unchecked(1).principal, 300);
+    }
+
+    #[test]
+    fn test_two_step_admin_transfer() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+        let new_admin = Address::generate(&env);
+
+        assert_eq!(
+            client.try_propose_admin(&user, &new_admin),
+            Err(Ok(Error::Unauthorized))
+        );
+        assert_eq!(client.try_accept_admin(&new_admin), Err(Ok(Error::Unauthorized)));
+
+        client.propose_admin(&admin, &new_admin);
+        assert_eq!(client.get_pending_admin(), Some(new_admin.clone()));
+        assert_eq!(client.try_accept_admin(&user), Err(Ok(Error::Unauthorized)));
+
+        // The old admin keeps control until the proposal is accepted
+        client.set_emergency_penalty(&admin, &500);
+
+        client.accept_admin(&new_admin);
+        let (topics, _) = last_event(&env, symbol_short!("admin"));
+        assert_eq!(
+            topics,
+            (symbol_short!("savings"), symbol_short!("admin"), admin.clone(), new_admin.clone())
+                .into_val(&env)
+        );
+        assert_eq!(client.get_pending_admin(), None);
+        assert_eq!(
+            client.try_set_emergency_penalty(&admin, &500),
+            Err(Ok(Error::Unauthorized))
+        );
+        client.set_emergency_penalty(&new_admin, &500);
+    }
+
+    #[test]
+    fn test_pause_blocks_deposits_not_withdrawals() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+
+        let matured = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        let locked = client.create_goal(&user, &1000, &(2 * THIRTY_DAYS), &0, &None, &None, &0);
+        env.ledger().with_mut(|li| li.timestamp += THIRTY_DAYS);
+
+        assert_eq!(client.try_pause(&user), Err(Ok(Error::Unauthorized)));
+        client.pause(&admin);
+        assert!(client.is_paused());
+
+        assert_eq!(
+            client.try_create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0),
+            Err(Ok(Error::Paused))
+        );
+        assert_eq!(
+            client.try_deposit(&user, &locked, &100),
+            Err(Ok(Error::Paused))
+        );
+
+        assert_eq!(client.withdraw(&user, &matured), 1000);
+        assert_eq!(client.emergency_withdraw(&user, &locked), 900);
+
+        client.unpause(&admin);
+        assert!(!client.is_paused());
+        client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+    }
+
+    #[test]
+    fn test_beneficiary_claim_after_inactivity_window() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            token,
+        } = setup();
+        fund_reserve(&env, &client, &admin, 10000);
+        let heir = Address::generate(&env);
+        let stranger = Address::generate(&env);
+
+        client.set_beneficiary_window(&admin, &THIRTY_DAYS);
+        let goal_id = client.create_goal(&user, &3650, &THIRTY_DAYS, &1000, &None, &None, &0);
+        client.set_beneficiary(&user, &goal_id, &Some(heir.clone()));
+
+        // Matured but still within the owner's window
+        env.ledger().with_mut(|li| li.timestamp += THIRTY_DAYS);
+        assert_eq!(
+            client.try_claim_as_beneficiary(&heir, &user, &goal_id),
+            Err(Ok(Error::StillLocked))
+        );
+
+        env.ledger().with_mut(|li| li.timestamp += THIRTY_DAYS);
+        assert_eq!(
+            client.try_claim_as_beneficiary(&stranger, &user, &goal_id),
+            Err(Ok(Error::Unauthorized))
+        );
+
+        // Interest keeps accruing past unlock: 60 days at 10% on 3650
+        assert_eq!(client.claim_as_beneficiary(&heir, &user, &goal_id), 3710);
+        assert_eq!(token.balance(&heir), 3710);
+        assert!(!client.get_goal(&user, &goal_id).is_active);
+    }
+
+    #[test]
+    fn test_withdraw_to_recipient() {
+        let Setup {
+            env,
+            client,
+            user,
+            token,
+            ..
+        } = setup();
+        let cold_wallet = Address::generate(&env);
+
+        let goal_id = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        assert_eq!(
+            client.try_withdraw_to(&user, &goal_id, &cold_wallet),
+            Err(Ok(Error::StillLocked))
+        );
+
+        env.ledger().with_mut(|li| li.timestamp += THIRTY_DAYS);
+        let user_balance = token.balance(&user);
+        assert_eq!(client.withdraw_to(&user, &goal_id, &cold_wallet), 1000);
+        let (_, data) = last_event(&env, symbol_short!("withdrawn"));
+        assert_eq!(
+            <(i128, bool, Address)>::try_from_val(&env, &data).unwrap(),
+            (1000, false, cold_wallet.clone())
+        );
+        assert_eq!(token.balance(&cold_wallet), 1000);
+        assert_eq!(token.balance(&user), user_balance);
+        assert!(!client.get_goal(&user, &goal_id).is_active);
+    }
+
+    #[test]
+    fn test_rate_tiers_cap_requested_rate() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+        let year = 365 * 86400;
+        let tier = |min_duration, max_rate| RateTier {
+            min_duration,
+            max_rate,
+        };
+
+        assert_eq!(
+            client.try_set_rate_tiers(&admin, &vec![&env, tier(year, 800), tier(THIRTY_DAYS, 300)]),
+            Err(Ok(Error::InvalidRateTiers))
+        );
+        client.set_rate_tiers(&admin, &vec![&env, tier(THIRTY_DAYS, 300), tier(year, 800)]);
+
+        // 30 days up to a year: 3%
+        client.create_goal(&user, &100, &THIRTY_DAYS, &300, &None, &None, &0);
+        assert_eq!(
+            client.try_create_goal(&user, &100, &(year - 1), &301, &None, &None, &0),
+            Err(Ok(Error::RateTooHigh))
+        );
+
+        // A year or more: 8%
+        client.create_goal(&user, &100, &year, &800, &None, &None, &0);
+        assert_eq!(
+            client.try_create_goal(&user, &100, &year, &801, &None, &None, &0),
+            Err(Ok(Error::RateTooHigh))
+        );
+
+        // Below every tier: no interest allowed
+        client.create_goal(&user, &100, &86400, &0, &None, &None, &0);
+        assert_eq!(
+            client.try_create_goal(&user, &100, &86400, &1, &None, &None, &0),
+            Err(Ok(Error::RateTooHigh))
+        );
+
+        client.set_rate_tiers(&admin, &vec![&env]);
+        client.create_goal(&user, &100, &86400, &1000, &None, &None, &0);
+    }
+
+    #[test]
+    fn test_interest_is_paid_from_reserve() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+
+        // 1000 bps on 3650 for 30 days earns 30 each
+        let first = client.create_goal(&user, &3650, &THIRTY_DAYS, &1000, &None, &None, &0);
+        let second = client.create_goal(&user, &3650, &THIRTY_DAYS, &1000, &None, &None, &0);
+        let early = client.create_goal(&user, &1000, &(2 * THIRTY_DAYS), &1000, &None, &None, &0);
+        env.ledger().with_mut(|li| li.timestamp += THIRTY_DAYS);
+
+        assert_eq!(
+            client.try_withdraw(&user, &first),
+            Err(Ok(Error::InsufficientReserve))
+        );
+
+        fund_reserve(&env, &client, &admin, 35);
+        assert_eq!(client.withdraw(&user, &first), 3680);
+        assert_eq!(client.get_reserve_balance(), 5);
+        assert_eq!(
+            client.try_withdraw(&user, &second),
+            Err(Ok(Error::InsufficientReserve))
+        );
+
+        // Emergency exits pay interest only as far as the reserve allows:
+        // 8 accrued but 5 payable, so 1005 less the 10% penalty
+        assert_eq!(client.emergency_withdraw(&user, &early), 905);
+        assert_eq!(client.get_reserve_balance(), 0);
+    }
+
+    #[test]
+    fn test_time_until_unlock_and_is_matured() {
+        let Setup {
+            env, client, user, ..
+        } = setup();
+
+        let goal_id = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        assert_eq!(client.time_until_unlock(&user, &goal_id), THIRTY_DAYS);
+        assert!(!client.is_matured(&user, &goal_id));
+
+        env.ledger().with_mut(|li| li.timestamp += THIRTY_DAYS - 1);
+        assert_eq!(client.time_until_unlock(&user, &goal_id), 1);
+        assert!(!client.is_matured(&user, &goal_id));
+
+        env.ledger().with_mut(|li| li.timestamp += 1);
+        assert_eq!(client.time_until_unlock(&user, &goal_id), 0);
+        assert!(client.is_matured(&user, &goal_id));
+
+        client.withdraw(&user, &goal_id);
+        assert_eq!(client.time_until_unlock(&user, &goal_id), 0);
+        assert!(!client.is_matured(&user, &goal_id));
+        assert_eq!(
+            client.try_time_until_unlock(&user, &99),
+            Err(Ok(Error::GoalNotFound))
+        );
+    }
+
+    #[test]
+    fn test_decaying_penalty_is_zero_past_unlock() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+        let year = 365 * 86400;
+
+        // Flat by default: the full 10% applies on a near-matured goal
+        let flat = client.create_goal(&user, &1000, &year, &0, &None, &None, &0);
+        let near_matured = client.create_goal(&user, &1000, &year, &0, &None, &None, &0);
+        let past_unlock = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        env.ledger().with_mut(|li| li.timestamp += year - 86400);
+        assert_eq!(client.emergency_withdraw(&user, &flat), 900);
+
+        client.set_penalty_mode(&admin, &PenaltyMode::LinearDecay);
+
+        // Just created: the full penalty
+        let just_created = client.create_goal(&user, &1000, &year, &0, &None, &None, &0);
+        assert_eq!(client.emergency_withdraw(&user, &just_created), 900);
+
+        // One day left of a year: 1000 bps * 1/365 rounds down to 2 bps
+        assert_eq!(client.emergency_withdraw(&user, &near_matured), 1000);
+
+        // Past unlock: no penalty at all
+        assert_eq!(client.emergency_withdraw(&user, &past_unlock), 1000);
+    }
+
+    #[test]
+    fn test_create_goals_batch() {
+        let Setup {
+            env,
+            client,
+            user,
+            token,
+            ..
+        } = setup();
+        let contract_before = token.balance(&client.address);
+
+        let mut goals = Vec::new(&env);
+        goals.push_back((1000_i128, THIRTY_DAYS, 500_u32));
+        goals.push_back((2000_i128, 2 * THIRTY_DAYS, 0_u32));
+        goals.push_back((500_i128, 3 * THIRTY_DAYS, 100_u32));
+        let ids = client.create_goals_batch(&user, &goals, &None);
+
+        assert_eq!(ids.len(), 3);
+        assert_eq!(client.get_goal(&user, &ids.get(1).unwrap()).principal, 2000);
+        assert_eq!(
+            client.get_goal(&user, &ids.get(2).unwrap()).lock_duration,
+            3 * THIRTY_DAYS
+        );
+        assert_eq!(token.balance(&user), 6500);
+        assert_eq!(token.balance(&client.address), contract_before + 3500);
+
+        // One bad entry reverts the whole batch
+        let mut bad = Vec::new(&env);
+        bad.push_back((1000_i128, THIRTY_DAYS, 0_u32));
+        bad.push_back((0_i128, THIRTY_DAYS, 0_u32));
+        assert_eq!(
+            client.try_create_goals_batch(&user, &bad, &None),
+            Err(Ok(Error::InvalidAmount))
+        );
+        assert_eq!(token.balance(&user), 6500);
+        assert_eq!(client.get_user_goal_ids(&user, &0, &10).len(), 3);
+
+        assert_eq!(
+            client.try_create_goals_batch(&user, &Vec::new(&env), &None),
+            Err(Ok(Error::InvalidBatch))
+        );
+    }
+
+    #[test]
+    fn test_cancel_goal_within_grace_period() {
+        let Setup {
+            env,
+            client,
+            user,
+            token,
+            ..
+        } = setup();
+
+        let goal_id = client.create_goal(&user, &1000, &THIRTY_DAYS, &500, &None, &None, &0);
+        env.ledger().with_mut(|li| li.timestamp += 3600);
+        assert_eq!(client.cancel_goal(&user, &goal_id), 1000);
+        assert_eq!(count_events(&env, symbol_short!("cancelled")), 1);
+        assert_eq!(token.balance(&user), 10000);
+        assert!(!client.get_goal(&user, &goal_id).is_active);
+        assert_eq!(
+            client.try_cancel_goal(&user, &goal_id),
+            Err(Ok(Error::GoalInactive))
+        );
+
+        let late = client.create_goal(&user, &1000, &THIRTY_DAYS, &500, &None, &None, &0);
+        env.ledger().with_mut(|li| li.timestamp += 3601);
+        assert_eq!(
+            client.try_cancel_goal(&user, &late),
+            Err(Ok(Error::GracePeriodExpired))
+        );
+    }
+
+    #[test]
+    fn test_cancel_goal_rejects_goals_merged_into() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            token,
+        } = setup();
+        fund_reserve(&env, &client, &admin, 10000);
+
+        // A fresh goal absorbs an older locked one...
+        let locked = client.create_goal(&user, &5000, &THIRTY_DAYS, &500, &None, &None, &0);
+        env.ledger().with_mut(|li| li.timestamp += 10 * 86400);
+        let fresh = client.create_goal(&user, &100, &THIRTY_DAYS, &500, &None, &None, &0);
+        client.merge_goals(&user, &fresh, &locked);
+
+        // ...but can't be cancelled to take the locked funds out penalty-free
+        assert_eq!(
+            client.try_cancel_goal(&user, &fresh),
+            Err(Ok(Error::GracePeriodExpired))
+        );
+        assert_eq!(token.balance(&user), 4900);
+    }
+
+    #[test]
+    fn test_cancel_goal_rejects_relocked_and_renewed_goals() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+        client.set_withdraw_cooldown(&admin, &86400);
+        let relocked = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        let renewed = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        client.set_auto_renew(&user, &renewed, &true);
+
+        // A new term moves start_time, but not the creation time
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS);
+        client.relock(&user, &relocked, &THIRTY_DAYS, &0);
+        client.process_renewal(&user, &renewed);
+        assert_eq!(client.get_goal(&user, &relocked).start_time, THIRTY_DAYS);
+        assert_eq!(client.get_goal(&user, &relocked).created_at, 0);
+
+        // Cancelling must not sidestep the withdraw cooldown
+        assert_eq!(
+            client.try_cancel_goal(&user, &relocked),
+            Err(Ok(Error::GracePeriodExpired))
+        );
+        assert_eq!(
+            client.try_cancel_goal(&user, &renewed),
+            Err(Ok(Error::GracePeriodExpired))
+        );
+    }
+
+    #[test]
+    fn test_total_value_locked_returns_to_zero() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+        fund_reserve(&env, &client, &admin, 10000);
+
+        let matures = client.create_goal(&user, &1000, &THIRTY_DAYS, &500, &None, &None, &0);
+        let broken = client.create_goal(&user, &2000, &THIRTY_DAYS, &500, &None, &None, &0);
+        let cancelled = client.create_goal(&user, &500, &THIRTY_DAYS, &0, &None, &None, &0);
+        client.deposit(&user, &matures, &250);
+        assert_eq!(client.get_total_value_locked(), 3750);
+
+        client.cancel_goal(&user, &cancelled);
+        assert_eq!(client.get_total_value_locked(), 3250);
+
+        env.ledger().with_mut(|li| li.timestamp += THIRTY_DAYS / 2);
+        client.emergency_withdraw(&user, &broken);
+        assert_eq!(client.get_total_value_locked(), 1250);
+
+        // Interest paid at maturity never counts against locked principal
+        env.ledger().with_mut(|li| li.timestamp += THIRTY_DAYS);
+        assert!(client.withdraw(&user, &matures) > 1250);
+        assert_eq!(client.get_total_value_locked(), 0);
+    }
+
+    #[test]
+    fn test_goals_in_a_second_token() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            token,
+        } = setup();
+        let second_id = env.register_stellar_asset_contract_v2(admin.clone());
+        let second = token::Client::new(&env, &second_id.address());
+        let second_admin = StellarAssetClient::new(&env, &second_id.address());
+        second_admin.mint(&user, &5000);
+        second_admin.mint(&admin, &1000);
+
+        let token_arg = Some(second.address.clone());
+        assert_eq!(
+            client.try_create_goal(&user, &1000, &THIRTY_DAYS, &500, &None, &token_arg, &0),
+            Err(Ok(Error::TokenNotSupported))
+        );
+
+        client.add_supported_token(&admin, &second.address);
+        let goal_id = client.create_goal(&user, &1000, &THIRTY_DAYS, &500, &None, &token_arg, &0);
+        let default_goal = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        assert_eq!(client.get_goal(&user, &goal_id).token, second.address);
+        assert_eq!(client.get_goal(&user, &default_goal).token, token.address);
+        assert_eq!(second.balance(&user), 4000);
+        assert_eq!(token.balance(&user), 9000);
+
+        // Interest on the second token's goal comes from that token's reserve
+        client.fund_token_reserve(&admin, &second.address, &1000);
+        env.ledger().with_mut(|li| li.timestamp += THIRTY_DAYS);
+        let paid = client.withdraw(&user, &goal_id);
+        assert!(paid > 1000);
+        assert_eq!(second.balance(&user), 4000 + paid);
+        assert_eq!(client.get_token_reserve(&second.address), 2000 - paid);
+        assert_eq!(client.get_reserve_balance(), 0);
+
+        assert_eq!(client.withdraw(&user, &default_goal), 1000);
+        assert_eq!(token.balance(&user), 10000);
+    }
+
+    #[test]
+    fn test_deposit_limits() {
+        let Setup {
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+        let goal_id = client.create_goal(&user, &1, &THIRTY_DAYS, &0, &None, &None, &0);
+
+        client.set_deposit_limits(&admin, &Some(100), &Some(1000));
+        assert_eq!(
+            client.try_create_goal(&user, &99, &THIRTY_DAYS, &0, &None, &None, &0),
+            Err(Ok(Error::DepositBelowMinimum))
+        );
+        assert_eq!(
+            client.try_create_goal(&user, &1001, &THIRTY_DAYS, &0, &None, &None, &0),
+            Err(Ok(Error::DepositAboveMaximum))
+        );
+        client.create_goal(&user, &100, &THIRTY_DAYS, &0, &None, &None, &0);
+        client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+
+        assert_eq!(
+            client.try_deposit(&user, &goal_id, &99),
+            Err(Ok(Error::DepositBelowMinimum))
+        );
+        assert_eq!(
+            client.try_deposit(&user, &goal_id, &1001),
+            Err(Ok(Error::DepositAboveMaximum))
+        );
+        assert_eq!(client.deposit(&user, &goal_id, &1000), 1001);
+
+        assert_eq!(
+            client.try_set_deposit_limits(&admin, &Some(500), &Some(100)),
+            Err(Ok(Error::InvalidAmount))
+        );
+
+        // Clearing the limits restores the plain positive-amount check
+        client.set_deposit_limits(&admin, &None, &None);
+        assert_eq!(client.deposit(&user, &goal_id, &1), 1002);
+        assert_eq!(
+            client.try_deposit(&user, &goal_id, &0),
+            Err(Ok(Error::InvalidAmount))
+        );
+    }
+
+    #[test]
+    fn test_upgrade_requires_admin() {
+        let Setup {
+            env,
+            client,
+            user,
+            ..
+        } = setup();
+
+        assert_eq!(
+            client.try_upgrade(&user, &BytesN::from_array(&env, &[0; 32])),
+            Err(Ok(Error::Unauthorized))
+        );
+    }
+
+    #[test]
+    fn test_goal_storage_outlives_default_ttl() {
+        let Setup {
+            env,
+            client,
+            user,
+            ..
+        } = setup();
+        let year = 365 * 86400;
+
+        let goal_id = client.create_goal(&user, &1000, &year, &0, &None, &None, &0);
+
+        // Far beyond the default minimum persistent TTL
+        env.ledger().with_mut(|li| {
+            li.sequence_number += 1_000_000;
+            li.timestamp += 86400;
+        });
+        let goal = client.get_goal(&user, &goal_id);
+        assert_eq!(goal.principal, 1000);
+
+        let ttl = env.as_contract(&client.address, || {
+            env.storage()
+                .persistent()
+                .get_ttl(&StorageKey::Goal(user.clone(), goal_id))
+        });
+        assert!(ttl > 0);
+    }
+
+    #[test]
+    fn test_transfer_goal() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            token,
+        } = setup();
+        fund_reserve(&env, &client, &admin, 10000);
+        let friend = Address::generate(&env);
+
+        let goal_id = client.create_goal(&user, &1000, &THIRTY_DAYS, &500, &None, &None, &0);
+        client.transfer_goal(&user, &friend, &goal_id);
+        assert_eq!(count_events(&env, symbol_short!("transfer")), 1);
+
+        assert!(client.try_get_goal(&user, &goal_id).is_err());
+        let goal = client.get_goal(&friend, &goal_id);
+        assert_eq!(goal.owner, friend);
+        assert_eq!(goal.unlock_time, THIRTY_DAYS);
+        assert_eq!(client.get_user_goal_count(&user), 0);
+        assert_eq!(client.get_user_goal_count(&friend), 1);
+        assert_eq!(client.get_user_goal_ids(&user, &0, &10).len(), 0);
+        assert_eq!(client.get_user_goal_ids(&friend, &0, &10), vec![&env, goal_id]);
+        assert_eq!(
+            client.try_transfer_goal(&friend, &friend, &goal_id),
+            Err(Ok(Error::GoalExists))
+        );
+
+        // Still locked for the new owner; only they can withdraw at maturity
+        assert_eq!(
+            client.try_withdraw(&friend, &goal_id),
+            Err(Ok(Error::StillLocked))
+        );
+        env.ledger().with_mut(|li| li.timestamp += THIRTY_DAYS);
+        assert_eq!(client.try_withdraw(&user, &goal_id), Err(Ok(Error::GoalNotFound)));
+        let paid = client.withdraw(&friend, &goal_id);
+        assert_eq!(token.balance(&friend), paid);
+    }
+
+    #[test]
+    fn test_balance_breakdown_reconciles() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+        fund_reserve(&env, &client, &admin, 10000);
+
+        let goal_id = client.create_goal(&user, &5000, &(4 * THIRTY_DAYS), &1000, &None, &None, &0);
+        assert_eq!(client.get_balance_breakdown(&user, &goal_id), (5000, 0, 0));
+
+        env.ledger().with_mut(|li| li.timestamp += THIRTY_DAYS);
+        client.compound_interest(&user, &goal_id);
+        env.ledger().with_mut(|li| li.timestamp += THIRTY_DAYS);
+
+        let (principal, accrued, pending) = client.get_balance_breakdown(&user, &goal_id);
+        assert_eq!(principal, 5000);
+        assert!(accrued > 0 && pending > 0);
+        assert_eq!(
+            principal + accrued + pending,
+            client.get_current_balance(&user, &goal_id)
+        );
+
+        client.emergency_withdraw(&user, &goal_id);
+        assert_eq!(client.get_balance_breakdown(&user, &goal_id), (0, 0, 0));
+        assert_eq!(
+            client.try_get_balance_breakdown(&user, &99),
+            Err(Ok(Error::GoalNotFound))
+        );
+    }
+
+    #[test]
+    fn test_penalties_go_to_treasury() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            token,
+        } = setup();
+        assert_eq!(client.get_treasury(), admin);
+
+        let first = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        client.emergency_withdraw(&user, &first);
+        assert_eq!(token.balance(&admin), 100);
+
+        let treasury = Address::generate(&env);
+        client.set_treasury(&admin, &treasury);
+        assert_eq!(client.get_treasury(), treasury);
+
+        let second = client.create_goal(&user, &2000, &THIRTY_DAYS, &0, &None, &None, &0);
+        client.emergency_withdraw(&user, &second);
+        assert_eq!(token.balance(&treasury), 200);
+        assert_eq!(token.balance(&admin), 100);
+    }
+
+    #[test]
+    fn test_recurring_contribution_schedule() {
+        let Setup {
+            env,
+            client,
+            user,
+            ..
+        } = setup();
+        let week = 7 * 86400;
+
+        let goal_id = client.create_goal(&user, &1000, &(4 * THIRTY_DAYS), &0, &None, &None, &0);
+        assert_eq!(client.next_contribution_due(&user, &goal_id), 0);
+        assert_eq!(
+            client.try_set_recurring_plan(&user, &goal_id, &100, &0),
+            Err(Ok(Error::InvalidAmount))
+        );
+
+        client.set_recurring_plan(&user, &goal_id, &100, &week);
+        assert_eq!(client.next_contribution_due(&user, &goal_id), week);
+        assert!(!client.is_contribution_overdue(&user, &goal_id));
+
+        env.ledger().with_mut(|li| li.timestamp += week + 1);
+        assert!(client.is_contribution_overdue(&user, &goal_id));
+
+        client.deposit(&user, &goal_id, &100);
+        assert_eq!(client.next_contribution_due(&user, &goal_id), 2 * week);
+        assert!(!client.is_contribution_overdue(&user, &goal_id));
+
+        client.set_recurring_plan(&user, &goal_id, &0, &0);
+        assert_eq!(client.next_contribution_due(&user, &goal_id), 0);
+    }
+
+    #[test]
+    fn test_transfer_failures_are_typed_errors() {
+        let Setup {
+            client,
+            user,
+            token,
+            ..
+        } = setup();
+
+        assert_eq!(
+            client.try_create_goal(&user, &10001, &THIRTY_DAYS, &0, &None, &None, &0),
+            Err(Ok(Error::InsufficientBalance))
+        );
+
+        let goal_id = client.create_goal(&user, &4000, &THIRTY_DAYS, &0, &None, &None, &0);
+        assert_eq!(
+            client.try_deposit(&user, &goal_id, &6001),
+            Err(Ok(Error::InsufficientBalance))
+        );
+        assert_eq!(client.get_goal(&user, &goal_id).principal, 4000);
+        assert_eq!(token.balance(&user), 6000);
+    }
+
+    #[test]
+    fn test_max_goals_per_user() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+        client.set_max_goals_per_user(&admin, &Some(2));
+
+        let first = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        assert_eq!(client.get_active_goal_count(&user), 2);
+        assert_eq!(
+            client.try_create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0),
+            Err(Ok(Error::GoalLimitReached))
+        );
+
+        // Withdrawing frees a slot
+        env.ledger().with_mut(|li| li.timestamp += THIRTY_DAYS);
+        client.withdraw(&user, &first);
+        assert_eq!(client.get_active_goal_count(&user), 1);
+        client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        assert_eq!(client.get_user_goal_count(&user), 3);
+
+        client.set_max_goals_per_user(&admin, &None);
+        client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        assert_eq!(client.get_active_goal_count(&user), 3);
+    }
+
+    #[test]
+    fn test_create_goal_until_timestamp() {
+        let Setup {
+            env,
+            client,
+            user,
+            ..
+        } = setup();
+        env.ledger().with_mut(|li| li.timestamp = 1_000_000);
+
+        let unlock = 1_000_000 + THIRTY_DAYS + 123;
+        let goal_id = client.create_goal_until(&user, &1000, &unlock, &0);
+        let goal = client.get_goal(&user, &goal_id);
+        assert_eq!(goal.unlock_time, unlock);
+        assert_eq!(goal.lock_duration, THIRTY_DAYS + 123);
+
+        assert_eq!(
+            client.try_create_goal_until(&user, &1000, &1_000_000, &0),
+            Err(Ok(Error::UnlockInPast))
+        );
+        assert_eq!(
+            client.try_create_goal_until(&user, &1000, &(1_000_000 + 3600), &0),
+            Err(Ok(Error::InvalidDuration))
+        );
+    }
+
+    #[test]
+    fn test_goal_history_is_bounded() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+        fund_reserve(&env, &client, &admin, 10000);
+
+        let goal_id = client.create_goal(&user, &1000, &THIRTY_DAYS, &1000, &None, &None, &0);
+        env.ledger().with_mut(|li| li.timestamp += 10 * 86400);
+        client.deposit(&user, &goal_id, &50);
+
+        let history = client.get_goal_history(&user, &goal_id);
+        assert_eq!(history.len(), 3);
+        assert_eq!(history.get(0).unwrap().action, GoalAction::Created);
+        assert_eq!(history.get(0).unwrap().amount, 1000);
+        assert_eq!(history.get(1).unwrap().action, GoalAction::Compound);
+        let deposit = history.get(2).unwrap();
+        assert_eq!(
+            (deposit.action, deposit.amount, deposit.timestamp),
+            (GoalAction::Deposit, 50, 10 * 86400)
+        );
+
+        for _ in 0..MAX_HISTORY_ENTRIES {
+            client.deposit(&user, &goal_id, &10);
+        }
+        env.ledger().with_mut(|li| li.timestamp += THIRTY_DAYS);
+        let paid = client.withdraw(&user, &goal_id);
+
+        let history = client.get_goal_history(&user, &goal_id);
+        assert_eq!(history.len(), MAX_HISTORY_ENTRIES);
+        let last = history.last().unwrap();
+        assert_eq!((last.action, last.amount), (GoalAction::Withdrawal, paid));
+        assert_eq!(
+            client.try_get_goal_history(&user, &99),
+            Err(Ok(Error::GoalNotFound))
+        );
+    }
+
+    #[test]
+    fn test_emergency_withdraw_cooldown() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+        let day = 86400;
+        client.set_withdrawal_cooldown(&admin, &day);
+
+        let goal_id = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        client.emergency_withdraw(&user, &goal_id);
+        assert_eq!(client.get_remaining_cooldown(&user), day);
+        assert_eq!(
+            client.try_create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0),
+            Err(Ok(Error::CooldownActive))
+        );
+
+        env.ledger().with_mut(|li| li.timestamp += day - 1);
+        assert_eq!(client.get_remaining_cooldown(&user), 1);
+        env.ledger().with_mut(|li| li.timestamp += 1);
+        assert_eq!(client.get_remaining_cooldown(&user), 0);
+        client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+    }
+
+    #[test]
+    fn test_reentrancy_guard() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+
+        // A malicious token calling back mid-transfer can't open a second goal
+        let evil_id = env.register(ReentrantToken, (client.address.clone(),));
+        let evil = ReentrantTokenClient::new(&env, &evil_id);
+        client.add_supported_token(&admin, &evil_id);
+        let goal_id =
+            client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &Some(evil_id.clone()), &0);
+        assert_eq!(evil.reentry_blocked(), Some(true));
+        assert_eq!(client.get_user_goal_ids(&user, &0, &10), vec![&env, goal_id]);
+
+        // The lock is released after each call, and rejects entry while held
+        let locked = || {
+            env.as_contract(&client.address, || {
+                env.storage().instance().has(&StorageKey::ReentrancyLock)
+            })
+        };
+        assert!(!locked());
+        env.as_contract(&client.address, || {
+            env.storage()
+                .instance()
+                .set(&StorageKey::ReentrancyLock, &true);
+        });
+        assert_eq!(
+            client.try_emergency_withdraw(&user, &goal_id),
+            Err(Ok(Error::Reentrancy))
+        );
+        assert_eq!(client.try_withdraw(&user, &goal_id), Err(Ok(Error::Reentrancy)));
+        assert_eq!(
+            client.try_create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0),
+            Err(Ok(Error::Reentrancy))
+        );
+    }
+
+    #[test]
+    fn test_lifetime_interest_counts_normal_withdrawals_only() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+        fund_reserve(&env, &client, &admin, 10000);
+
+        let matured = client.create_goal(&user, &5000, &THIRTY_DAYS, &500, &None, &None, &0);
+        let early = client.create_goal(&user, &5000, &THIRTY_DAYS, &500, &None, &None, &0);
+        assert_eq!(client.get_lifetime_interest(&user), 0);
+
+        // Interest paid on an emergency exit isn't counted
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS / 2);
+        client.emergency_withdraw(&user, &early);
+        assert_eq!(client.get_lifetime_interest(&user), 0);
+
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS + 1);
+        let amount = client.withdraw(&user, &matured);
+        assert!(amount > 5000);
+        assert_eq!(client.get_lifetime_interest(&user), amount - 5000);
+    }
+
+    #[test]
+    fn test_can_withdraw() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+        fund_reserve(&env, &client, &admin, 10000);
+
+        assert!(!client.can_withdraw(&user, &0));
+
+        let goal_id = client.create_goal(&user, &10000, &THIRTY_DAYS, &500, &None, &None, &0);
+        assert!(!client.can_withdraw(&user, &goal_id));
+
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS);
+        assert!(client.can_withdraw(&user, &goal_id));
+
+        client.withdraw(&user, &goal_id);
+        assert!(!client.can_withdraw(&user, &goal_id));
+    }
+
+    #[test]
+    fn test_create_goal_for_another_owner() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            token,
+            ..
+        } = setup();
+        fund_reserve(&env, &client, &admin, 10000);
+        let child = Address::generate(&env);
+
+        let goal_id = client.create_goal_for(&user, &child, &5000, &THIRTY_DAYS, &500);
+        assert_eq!(count_events(&env, symbol_short!("gifted")), 1);
+        assert_eq!(client.get_goal(&child, &goal_id).owner, child);
+        assert_eq!(client.get_user_goal_count(&child), 1);
+        assert_eq!(client.get_user_goal_count(&user), 0);
+        assert_eq!(token.balance(&child), 0);
+
+        // Only the owner can take the matured funds
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS + 1);
+        assert_eq!(client.try_withdraw(&user, &goal_id), Err(Ok(Error::GoalNotFound)));
+        let amount = client.withdraw(&child, &goal_id);
+        assert_eq!(token.balance(&child), amount);
+    }
+
+    #[test]
+    fn test_admin_close_dust_goal() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            token,
+            ..
+        } = setup();
+        let dust = client.create_goal(&user, &100, &THIRTY_DAYS, &0, &None, &None, &0);
+        let large = client.create_goal(&user, &5000, &THIRTY_DAYS, &0, &None, &None, &0);
+        client.set_dust_threshold(&admin, &1000);
+        let outsider = Address::generate(&env);
+        assert_eq!(
+            client.try_admin_close_dust_goal(&outsider, &user, &dust),
+            Err(Ok(Error::Unauthorized))
+        );
+
+        // Dust goals must be long past unlock, and large goals never qualify
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS + 1);
+        assert_eq!(
+            client.try_admin_close_dust_goal(&admin, &user, &dust),
+            Err(Ok(Error::StillLocked))
+        );
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS + DUST_GRACE_PERIOD);
+        assert_eq!(
+            client.try_admin_close_dust_goal(&admin, &user, &large),
+            Err(Ok(Error::AmountExceedsBalance))
+        );
+
+        let balance_before = token.balance(&user);
+        let admin_before = token.balance(&admin);
+        assert_eq!(client.admin_close_dust_goal(&admin, &user, &dust), 100);
+        assert_eq!(token.balance(&user), balance_before + 100);
+        assert_eq!(token.balance(&admin), admin_before);
+        assert!(!client.get_goal(&user, &dust).is_active);
+
+        // Already-closed goals are eligible and return nothing
+        client.withdraw(&user, &large);
+        assert_eq!(client.admin_close_dust_goal(&admin, &user, &large), 0);
+    }
+
+    #[test]
+    fn test_admin_close_dust_goal_keeps_inactive_goal_records() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+        let closed = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS);
+        client.withdraw(&user, &closed);
+        let history = client.get_goal_history(&user, &closed);
+
+        // A stale schedule left behind on the closed goal
+        env.as_contract(&client.address, || {
+            env.storage()
+                .persistent()
+                .set(&StorageKey::WithdrawRequest(user.clone(), closed), &0u64);
+        });
+
+        assert_eq!(client.admin_close_dust_goal(&admin, &user, &closed), 0);
+        assert_eq!(client.get_withdraw_request(&user, &closed), None);
+        assert!(!client.get_goal(&user, &closed).is_active);
+        assert_eq!(client.get_goal_history(&user, &closed), history);
+        assert_eq!(client.get_user_goal_ids(&user, &0, &10), vec![&env, closed]);
+    }
+
+    #[test]
+    fn test_accrue_after_unlock_setting() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+        fund_reserve(&env, &client, &admin, 10000);
+
+        let capped = client.create_goal(&user, &5000, &THIRTY_DAYS, &500, &None, &None, &0);
+        let open = client.create_goal(&user, &5000, &THIRTY_DAYS, &500, &None, &None, &0);
+        client.set_accrue_after_unlock(&admin, &false);
+        client.set_goal_accrue_after_unlock(&admin, &user, &open, &Some(true));
+
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS);
+        let at_maturity = client.get_current_balance(&user, &capped);
+        assert!(at_maturity > 5000);
+        assert_eq!(client.get_current_balance(&user, &open), at_maturity);
+
+        // Left unclaimed for a year, only the goal that keeps accruing grows
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS + SECONDS_PER_YEAR as u64);
+        assert_eq!(client.get_current_balance(&user, &capped), at_maturity);
+        assert!(client.get_current_balance(&user, &open) > at_maturity);
+        assert_eq!(client.withdraw(&user, &capped), at_maturity);
+    }
+
+    #[test]
+    fn test_projected_maturity_balance() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+        fund_reserve(&env, &client, &admin, 10000);
+
+        let goal_id = client.create_goal(&user, &10000, &THIRTY_DAYS, &500, &None, &None, &0);
+        let projected = client.projected_maturity_balance(&user, &goal_id);
+        assert!(projected > 10000);
+
+        // Compounding along the way earns interest on interest from then on
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS / 3);
+        client.compound_interest(&user, &goal_id);
+        let projected = client.projected_maturity_balance(&user, &goal_id);
+
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS);
+        assert_eq!(client.get_current_balance(&user, &goal_id), projected);
+        assert_eq!(client.projected_maturity_balance(&user, &goal_id), projected);
+    }
+
+    #[test]
+    fn test_interest_rate_fixed_after_creation() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+        fund_reserve(&env, &client, &admin, 10000);
+        let goal_id = client.create_goal(&user, &5000, &THIRTY_DAYS, &500, &None, &None, &0);
+        let rate = || client.get_goal(&user, &goal_id).interest_rate;
+
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS / 2);
+        client.compound_interest(&user, &goal_id);
+        client.deposit(&user, &goal_id, &1000);
+        client.extend_lock(&user, &goal_id, &THIRTY_DAYS);
+        client.set_compound_mode(&user, &goal_id, &CompoundMode::Periodic);
+        client.set_auto_renew(&user, &goal_id, &true);
+        assert_eq!(rate(), 500);
+
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS * 3);
+        client.withdraw_partial(&user, &goal_id, &100);
+        assert_eq!(rate(), 500);
+
+        // Only a consented, re-validated new term changes it
+        assert_eq!(
+            client.try_relock(&user, &goal_id, &THIRTY_DAYS, &(MAX_INTEREST_RATE + 1)),
+            Err(Ok(Error::RateTooHigh))
+        );
+        client.relock(&user, &goal_id, &THIRTY_DAYS, &300);
+        assert_eq!(rate(), 300);
+    }
+
+    #[test]
+    #[should_panic(expected = "interest rate changed outside renegotiate_rate")]
+    fn test_untracked_rate_change_panics() {
+        let Setup { env, client, user, .. } = setup();
+        let goal_id = client.create_goal(&user, &5000, &THIRTY_DAYS, &500, &None, &None, &0);
+        let before = client.get_goal(&user, &goal_id);
+        let mut after = before.clone();
+        after.interest_rate = 600;
+
+        env.as_contract(&client.address, || {
+            let _ = track_goal_change(&env, Some(&before), &after);
+        });
+    }
+
+    #[test]
+    fn test_configure_rates() {
+        let Setup {
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+        assert_eq!(
+            client.try_configure_rates(&admin, &(MAX_INTEREST_RATE + 1), &0),
+            Err(Ok(Error::RateTooHigh))
+        );
+        assert_eq!(client.try_configure_rates(&admin, &800, &900), Err(Ok(Error::RateTooHigh)));
+        client.configure_rates(&admin, &800, &400);
+
+        assert_eq!(
+            client.try_create_goal(&user, &1000, &THIRTY_DAYS, &801, &None, &None, &0),
+            Err(Ok(Error::RateTooHigh))
+        );
+        let explicit = client.create_goal(&user, &1000, &THIRTY_DAYS, &800, &None, &None, &0);
+        assert_eq!(client.get_goal(&user, &explicit).interest_rate, 800);
+        let defaulted = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        assert_eq!(client.get_goal(&user, &defaulted).interest_rate, 400);
+    }
+
+    #[test]
+    fn test_quote_emergency_withdraw_matches_result() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            token,
+            ..
+        } = setup();
+        fund_reserve(&env, &client, &admin, 10000);
+        assert_eq!(
+            client.try_quote_emergency_withdraw(&user, &0),
+            Err(Ok(Error::GoalNotFound))
+        );
+
+        let goal_id = client.create_goal(&user, &10000, &THIRTY_DAYS, &500, &None, &None, &0);
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS / 2);
+        let (amount, penalty) = client.quote_emergency_withdraw(&user, &goal_id);
+        assert!(penalty > 0);
+
+        // Quoting changes nothing
+        assert_eq!(client.get_goal(&user, &goal_id).accrued_interest, 0);
+
+        let treasury_before = token.balance(&admin);
+        assert_eq!(client.emergency_withdraw(&user, &goal_id), amount);
+        assert_eq!(token.balance(&admin) - treasury_before, penalty);
+        assert_eq!(
+            client.try_quote_emergency_withdraw(&user, &goal_id),
+            Err(Ok(Error::AlreadyWithdrawn))
+        );
+    }
+
+    #[test]
+    fn test_goal_target_amount() {
+        let Setup { env, client, user, .. } = setup();
+
+        let untargeted = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        assert!(!client.is_goal_reached(&user, &untargeted));
+
+        let goal_id = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &3000);
+        assert_eq!(client.get_goal(&user, &goal_id).target_amount, 3000);
+        assert!(!client.is_goal_reached(&user, &goal_id));
+
+        client.deposit(&user, &goal_id, &1000);
+        assert_eq!(count_events(&env, symbol_short!("target")), 0);
+
+        // Only the top-up that crosses the target announces it
+        client.deposit(&user, &goal_id, &1000);
+        assert_eq!(count_events(&env, symbol_short!("target")), 1);
+        assert!(client.is_goal_reached(&user, &goal_id));
+        client.deposit(&user, &goal_id, &1000);
+        assert_eq!(count_events(&env, symbol_short!("target")), 0);
+    }
+
+    #[test]
+    fn test_update_config() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+        let update = ConfigUpdate {
+            emergency_penalty: Some(2000),
+            min_deposit: Some(100),
+            max_deposit: Some(5000),
+            max_rate: Some(800),
+        };
+        let penalty = || -> Option<u32> {
+            env.as_contract(&client.address, || {
+                env.storage().instance().get(&StorageKey::EmergencyPenalty)
+            })
+        };
+        let outsider = Address::generate(&env);
+        assert_eq!(
+            client.try_update_config(&outsider, &update),
+            Err(Ok(Error::Unauthorized))
+        );
+
+        // One invalid field reverts the rest
+        let invalid = ConfigUpdate {
+            max_rate: Some(MAX_INTEREST_RATE + 1),
+            ..update.clone()
+        };
+        assert_eq!(client.try_update_config(&admin, &invalid), Err(Ok(Error::RateTooHigh)));
+        assert_eq!(penalty(), Some(1000));
+
+        client.update_config(&admin, &update);
+        assert_eq!(penalty(), Some(2000));
+        assert_eq!(
+            client.try_create_goal(&user, &50, &THIRTY_DAYS, &0, &None, &None, &0),
+            Err(Ok(Error::DepositBelowMinimum))
+        );
+        assert_eq!(
+            client.try_create_goal(&user, &1000, &THIRTY_DAYS, &900, &None, &None, &0),
+            Err(Ok(Error::RateTooHigh))
+        );
+
+        // Omitted fields keep their values and still bound the provided ones
+        let min_only = ConfigUpdate {
+            emergency_penalty: None,
+            min_deposit: Some(6000),
+            max_deposit: None,
+            max_rate: None,
+        };
+        assert_eq!(client.try_update_config(&admin, &min_only), Err(Ok(Error::InvalidAmount)));
+    }
+
+    #[test]
+    fn test_create_goal_rejects_reused_id() {
+        let Setup {
+            env,
+            client,
+            user,
+            token,
+            ..
+        } = setup();
+        let other = Address::generate(&env);
+        StellarAssetClient::new(&env, &token.address).mint(&other, &1000);
+
+        let goal_id = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+
+        // Roll the counter back as a faulty migration might; no owner's goal
+        // may be overwritten under the reused ID
+        env.as_contract(&client.address, || {
+            env.storage()
+                .instance()
+                .set(&StorageKey::GoalCounter, &goal_id);
+        });
+        assert_eq!(
+            client.try_create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0),
+            Err(Ok(Error::GoalExists))
+        );
+        assert_eq!(
+            client.try_create_goal(&other, &1000, &THIRTY_DAYS, &0, &None, &None, &0),
+            Err(Ok(Error::GoalExists))
+        );
+        assert_eq!(client.get_goal(&user, &goal_id).principal, 1000);
+    }
+
+    #[test]
+    fn test_global_cap() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+        let outsider = Address::generate(&env);
+        assert_eq!(
+            client.try_set_global_cap(&outsider, &Some(3000)),
+            Err(Ok(Error::Unauthorized))
+        );
+        client.set_global_cap(&admin, &Some(3000));
+
+        let goal_id = client.create_goal(&user, &2000, &THIRTY_DAYS, &0, &None, &None, &0);
+        client.deposit(&user, &goal_id, &1000);
+        assert_eq!(client.get_total_value_locked(), 3000);
+
+        assert_eq!(
+            client.try_deposit(&user, &goal_id, &1),
+            Err(Ok(Error::DepositAboveMaximum))
+        );
+        assert_eq!(
+            client.try_create_goal(&user, &1, &THIRTY_DAYS, &0, &None, &None, &0),
+            Err(Ok(Error::DepositAboveMaximum))
+        );
+
+        client.set_global_cap(&admin, &None);
+        client.deposit(&user, &goal_id, &1);
+    }
+
+    #[test]
+    fn test_compound_all() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+        fund_reserve(&env, &client, &admin, 10000);
+        let first = client.create_goal(&user, &1000, &THIRTY_DAYS, &500, &None, &None, &0);
+        let closed = client.create_goal(&user, &1000, &THIRTY_DAYS, &500, &None, &None, &0);
+        let last = client.create_goal(&user, &1000, &THIRTY_DAYS, &500, &None, &None, &0);
+        client.cancel_goal(&user, &closed);
+
+        // Nothing has elapsed yet
+        assert_eq!(client.compound_all(&user), 0);
+
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS / 2);
+        assert_eq!(client.compound_all(&user), 2);
+        for goal_id in [first, last] {
+            let goal = client.get_goal(&user, &goal_id);
+            assert_eq!(goal.last_compound_time, THIRTY_DAYS / 2);
+            assert!(goal.accrued_interest > 0);
+        }
+        assert_eq!(client.compound_all(&user), 0);
+    }
+
+    #[test]
+    fn test_interest_truncation_characterized() {
+        const HOUR: u64 = 3600;
+        const DAY: u64 = 86400;
 
-        let mut goal: SavingsGoal = env
-            .storage()
-            .persistent()
-            .get(&StorageKey::Goal(owner.clone(), goal_id))
-            .ok_or(Error::GoalNotFound)?;
+        // 10000 at 5% earns ~1.37 units a day and ~0.057 an hour, truncated
+        assert_eq!(calculate_interest(10000, 500, DAY), Ok(1));
+        assert_eq!(calculate_interest(10000, 500, HOUR), Ok(0));
+        // Small balances earn nothing until a whole unit has accrued
+        assert_eq!(calculate_interest(100, 500, 30 * DAY), Ok(0));
+        assert_eq!(calculate_interest(100, 500, 73 * DAY), Ok(1));
 
-        // Security: Check if goal is active
-        if !goal.is_active {
-            return Err(Error::AlreadyWithdrawn);
+        // Compounding hourly for two days forfeits all of it
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+        fund_reserve(&env, &client, &admin, 10000);
+        let hourly = client.create_goal(&user, &5000, &THIRTY_DAYS, &500, &None, &None, &0);
+        let once = client.create_goal(&user, &5000, &THIRTY_DAYS, &500, &None, &None, &0);
+        for hour in 1..=48 {
+            env.ledger().with_mut(|li| li.timestamp = hour * HOUR);
+            client.compound_interest(&user, &hourly);
         }
+        client.compound_interest(&user, &once);
+        assert_eq!(client.get_goal(&user, &hourly).accrued_interest, 0);
+        assert_eq!(client.get_goal(&user, &once).accrued_interest, 1);
+    }
 
-        // Calculate total balance
-        let total_balance = goal
-            .principal
-            .checked_add(goal.accrued_interest)
-            .ok_or(Error::Overflow)?;
+    #[test]
+    fn test_poke_announces_maturity_once() {
+        let Setup { env, client, user, .. } = setup();
+        let goal_id = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
 
-        // Get penalty rate
-        let penalty_rate: u32 = env
-            .storage()
-            .instance()
-            .get(&StorageKey::EmergencyPenalty)
-            .unwrap_or(1000); // Default 10%
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS - 1);
+        assert!(!client.poke(&user, &goal_id));
+        assert_eq!(count_events(&env, symbol_short!("matured")), 0);
 
-        // Calculate penalty amount
-        let penalty = total_balance
-            .checked_mul(penalty_rate as i128)
-            .ok_or(Error::Overflow)?
-            .checked_div(BASIS_POINTS)
-            .ok_or(Error::DivisionError)?;
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS);
+        assert!(client.poke(&user, &goal_id));
+        assert_eq!(count_events(&env, symbol_short!("matured")), 1);
+        assert!(!client.poke(&user, &goal_id));
+        assert_eq!(count_events(&env, symbol_short!("matured")), 0);
 
-        let withdrawal_amount = total_balance
-            .checked_sub(penalty)
-            .ok_or(Error::Underflow)?;
+        // A new term can be announced again
+        client.extend_lock(&user, &goal_id, &THIRTY_DAYS);
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS * 2);
+        assert!(client.poke(&user, &goal_id));
+    }
 
-        // Security: Mark goal as inactive before transfers
-        goal.is_active = false;
-        env.storage()
-            .persistent()
-            .set(&StorageKey::Goal(owner.clone(), goal_id), &goal);
+    #[test]
+    fn test_creation_fee() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            token,
+            ..
+        } = setup();
+        assert_eq!(
+            client.try_set_creation_fee(&admin, &(MAX_CREATION_FEE_BPS + 1)),
+            Err(Ok(Error::InvalidAmount))
+        );
+        let treasury = Address::generate(&env);
+        client.set_treasury(&admin, &treasury);
+        client.set_creation_fee(&admin, &200);
 
-        // Transfer tokens
-        let token_address: Address = env
-            .storage()
-            .instance()
-            .get(&StorageKey::Token)
-            .ok_or(Error::NotInitialized)?;
-        let token = token::Client::new(&env, &token_address);
+        let user_before = token.balance(&user);
+        let goal_id = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        let (_, data) = last_event(&env, symbol_short!("created"));
+        assert_eq!(<(i128, u32, u64, i128)>::try_from_val(&env, &data).unwrap().3, 20);
+        assert_eq!(client.get_goal(&user, &goal_id).principal, 980);
+        assert_eq!(client.get_total_value_locked(), 980);
+        assert_eq!(token.balance(&treasury), 20);
+        assert_eq!(token.balance(&user), user_before - 1000);
 
-        // Transfer withdrawal amount to owner
-        token.transfer(&env.current_contract_address(), &owner, &withdrawal_amount);
+        // Fees round down, so tiny deposits may pay none
+        let small = client.create_goal(&user, &49, &THIRTY_DAYS, &0, &None, &None, &0);
+        assert_eq!(client.get_goal(&user, &small).principal, 49);
+    }
 
-        // Transfer penalty to admin
-        let admin: Address = env
-            .storage()
-            .instance()
-            .get(&StorageKey::Admin)
-            .ok_or(Error::NotInitialized)?;
-        token.transfer(&env.current_contract_address(), &admin, &penalty);
+    #[test]
+    fn test_interest_paid_in_reward_token() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            token,
+        } = setup();
+        let reward_id = env.register_stellar_asset_contract_v2(admin.clone());
+        let reward = token::Client::new(&env, &reward_id.address());
+        StellarAssetClient::new(&env, &reward_id.address()).mint(&admin, &1000);
 
-        Ok(withdrawal_amount)
+        assert_eq!(
+            client.try_set_reward_token(&admin, &Some(reward.address.clone())),
+            Err(Ok(Error::TokenNotSupported))
+        );
+        client.add_supported_token(&admin, &reward.address);
+        client.set_reward_token(&admin, &Some(reward.address.clone()));
+        client.fund_token_reserve(&admin, &reward.address, &1000);
+
+        let goal_id = client.create_goal(&user, &1000, &THIRTY_DAYS, &500, &None, &None, &0);
+        let stranded = client.create_goal(&user, &1000, &THIRTY_DAYS, &500, &None, &None, &0);
+        assert!(client.get_goal(&user, &goal_id).interest_in_reward);
+        env.ledger().with_mut(|li| li.timestamp += THIRTY_DAYS);
+
+        // Principal comes back in the deposit token, interest in the reward token
+        let paid = client.withdraw(&user, &goal_id);
+        let interest = paid - 1000;
+        assert!(interest > 0);
+        assert_eq!(token.balance(&user), 9000);
+        assert_eq!(reward.balance(&user), interest);
+        assert_eq!(client.get_token_reserve(&reward.address), 1000 - interest);
+
+        client.set_reward_token(&admin, &None);
+        assert_eq!(
+            client.try_withdraw(&user, &stranded),
+            Err(Ok(Error::NotInitialized))
+        );
     }
 
-    /// Get details of a specific savings goal
-    /// 
-    /// # Security:
-    /// - Read-only function, no state changes
-    /// - Anyone can view goal details (transparency)
-    pub fn get_goal(env: Env, owner: Address, goal_id: u64) -> Result<SavingsGoal, Error> {
-        env.storage()
-            .persistent()
-            .get(&StorageKey::Goal(owner, goal_id))
-            .ok_or(Error::GoalNotFound)
+    #[test]
+    fn test_whitelist_gates_goal_creation() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+        let early = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+
+        client.set_whitelist_enabled(&admin, &true);
+        assert_eq!(
+            client.try_create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0),
+            Err(Ok(Error::Unauthorized))
+        );
+
+        client.add_to_whitelist(&admin, &user);
+        client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+
+        // Removal blocks new goals but never existing exits
+        client.remove_from_whitelist(&admin, &user);
+        assert_eq!(
+            client.try_create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0),
+            Err(Ok(Error::Unauthorized))
+        );
+        env.ledger().with_mut(|li| li.timestamp += THIRTY_DAYS);
+        assert_eq!(client.withdraw(&user, &early), 1000);
+
+        client.set_whitelist_enabled(&admin, &false);
+        client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
     }
 
-    /// Get the total number of goals for a user
-    /// 
-    /// # Security:
-    /// - Read-only function
-    pub fn get_user_goal_count(env: Env, owner: Address) -> u64 {
-        env.storage()
-            .persistent()
-            .get(&StorageKey::UserGoalCount(owner))
-            .unwrap_or(0)
+    #[test]
+    fn test_whitelist_applies_to_the_funder_of_a_gift() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+        let owner = Address::generate(&env);
+        client.set_whitelist_enabled(&admin, &true);
+        client.add_to_whitelist(&admin, &owner);
+
+        // An approved owner doesn't let an unapproved caller in
+        assert_eq!(
+            client.try_create_goal_for(&user, &owner, &1000, &THIRTY_DAYS, &0),
+            Err(Ok(Error::Unauthorized))
+        );
+
+        // Nor does an approved caller let an unapproved owner in
+        client.add_to_whitelist(&admin, &user);
+        client.remove_from_whitelist(&admin, &owner);
+        assert_eq!(
+            client.try_create_goal_for(&user, &owner, &1000, &THIRTY_DAYS, &0),
+            Err(Ok(Error::Unauthorized))
+        );
+
+        client.add_to_whitelist(&admin, &owner);
+        let goal_id = client.create_goal_for(&user, &owner, &1000, &THIRTY_DAYS, &0);
+        assert_eq!(client.get_goal(&owner, &goal_id).principal, 1000);
     }
 
-    /// Calculate current total balance (principal + interest) for a goal
-    /// 
-    /// # Security:
-    /// - Read-only function, doesn't modify state
-    /// - Calculates up-to-date interest without changing storage
-    pub fn get_current_balance(env: Env, owner: Address, goal_id: u64) -> Result<i128, Error> {
-        let goal: SavingsGoal = env
-            .storage()
-            .persistent()
-            .get(&StorageKey::Goal(owner, goal_id))
-            .ok_or(Error::GoalNotFound)?;
+    #[test]
+    fn test_effective_apy() {
+        let Setup { client, user, .. } = setup();
+        let year = 365 * 86400;
 
-        if !goal.is_active {
-            return Ok(0);
-        }
+        let linear = client.create_goal(&user, &1000, &year, &500, &None, &None, &0);
+        assert_eq!(client.effective_apy(&user, &linear), 500);
 
-        let current_time = env.ledger().timestamp();
-        let time_elapsed = current_time
-            .checked_sub(goal.last_compound_time)
-            .ok_or(Error::TimeError)?;
+        // (1 + r/365)^365 - 1: 5% -> 5.1267%, 10% -> 10.5156%, 0% -> 0%
+        client.set_compound_mode(&user, &linear, &CompoundMode::Periodic);
+        assert_eq!(client.effective_apy(&user, &linear), 512);
+        let ten = client.create_goal(&user, &1000, &year, &1000, &None, &None, &0);
+        client.set_compound_mode(&user, &ten, &CompoundMode::Periodic);
+        assert_eq!(client.effective_apy(&user, &ten), 1051);
+        let zero = client.create_goal(&user, &1000, &year, &0, &None, &None, &0);
+        client.set_compound_mode(&user, &zero, &CompoundMode::Periodic);
+        assert_eq!(client.effective_apy(&user, &zero), 0);
+    }
 
-        // Calculate pending interest
-        let total_balance = goal
-            .principal
-            .checked_add(goal.accrued_interest)
-            .ok_or(Error::Overflow)?;
+    #[test]
+    fn test_frozen_goal_accrues_no_interest() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+        fund_reserve(&env, &client, &admin, 1000);
+        let year = 365 * 86400;
+        let goal_id = client.create_goal(&user, &4000, &THIRTY_DAYS, &1000, &None, &None, &0);
 
-        let pending_interest = total_balance
-            .checked_mul(goal.interest_rate as i128)
-            .ok_or(Error::Overflow)?
-            .checked_mul(time_elapsed as i128)
-            .ok_or(Error::Overflow)?
-            .checked_div(SECONDS_PER_YEAR * BASIS_POINTS)
-            .ok_or(Error::DivisionError)?;
+        client.freeze_goal(&user, &goal_id);
+        assert!(client.get_goal(&user, &goal_id).frozen);
+        env.ledger().with_mut(|li| li.timestamp += year);
+        assert_eq!(client.get_current_balance(&user, &goal_id), 4000);
+        client.compound_interest(&user, &goal_id);
+        let goal = client.get_goal(&user, &goal_id);
+        assert_eq!(goal.accrued_interest, 0);
+        assert_eq!(goal.last_compound_time, env.ledger().timestamp());
 
-        total_balance
-            .checked_add(pending_interest)
-            .ok_or(Error::Overflow)
+        // Unfreezing doesn't pay for the frozen year, only time after it
+        client.unfreeze_goal(&user, &goal_id);
+        assert_eq!(client.get_goal(&user, &goal_id).accrued_interest, 0);
+        env.ledger().with_mut(|li| li.timestamp += year);
+        client.compound_interest(&user, &goal_id);
+        assert_eq!(client.get_goal(&user, &goal_id).accrued_interest, 400);
+
+        // Frozen goals can still be withdrawn once unlocked
+        client.freeze_goal(&user, &goal_id);
+        assert_eq!(client.withdraw(&user, &goal_id), 4400);
     }
 
-    /// Admin function to update emergency penalty rate
-    /// 
-    /// # Security:
-    /// - Only admin can call this
-    /// - Validates new penalty rate
-    pub fn set_emergency_penalty(env: Env, admin: Address, new_penalty: u32) -> Result<(), Error> {
-        admin.require_auth();
+    #[test]
+    fn test_get_config_matches_initialize() {
+        let Setup {
+            env,
+            client,
+            admin,
+            token,
+            ..
+        } = setup();
 
-        let stored_admin: Address = env
-            .storage()
-            .instance()
-            .get(&StorageKey::Admin)
-            .ok_or(Error::NotInitialized)?;
+        assert_eq!(
+            client.get_config(),
+            Config {
+                token: token.address.clone(),
+                admin: admin.clone(),
+                treasury: admin.clone(),
+                emergency_penalty: 1000,
+                min_deposit: None,
+                max_deposit: None,
+                max_rate: MAX_INTEREST_RATE,
+                default_rate: 0,
+                global_cap: None,
+                creation_fee_bps: 0,
+                max_goals_per_user: None,
+                withdraw_cooldown: 0,
+                paused: false,
+            }
+        );
 
-        if admin != stored_admin {
-            return Err(Error::Unauthorized);
+        let treasury = Address::generate(&env);
+        client.set_treasury(&admin, &treasury);
+        client.set_deposit_limits(&admin, &Some(100), &None);
+        let config = client.get_config();
+        assert_eq!(config.treasury, treasury);
+        assert_eq!(config.min_deposit, Some(100));
+
+        let fresh = env.register(TimeLockedSavings, ());
+        let uninitialized = TimeLockedSavingsClient::new(&env, &fresh);
+        assert_eq!(uninitialized.try_get_config(), Err(Ok(Error::NotInitialized)));
+    }
+
+    #[test]
+    fn test_zero_rate_goal_is_a_pure_time_lock() {
+        let Setup {
+            env, client, user, ..
+        } = setup();
+        let goal_id = client.create_goal(&user, &7777, &THIRTY_DAYS, &0, &None, &None, &0);
+        client.set_compound_mode(&user, &goal_id, &CompoundMode::Periodic);
+
+        for _ in 0..30 {
+            env.ledger().with_mut(|li| li.timestamp += 86400);
+            client.compound_interest(&user, &goal_id);
+            assert_eq!(client.get_current_balance(&user, &goal_id), 7777);
         }
+        let goal = client.get_goal(&user, &goal_id);
+        assert_eq!(goal.accrued_interest, 0);
+        assert_eq!(goal.compound_count, 0);
+        assert_eq!(goal.last_compound_time, env.ledger().timestamp());
 
-        if new_penalty > 5000 {
-            return Err(Error::PenaltyTooHigh);
+        assert_eq!(client.withdraw(&user, &goal_id), 7777);
+    }
+
+    #[test]
+    fn test_whale_goals_over_max_lock_do_not_overflow() {
+        let Setup {
+            env,
+            client,
+            user,
+            token,
+            ..
+        } = setup();
+        let whale: i128 = 1_000_000_000_000_000_000_000_000_000;
+        StellarAssetClient::new(&env, &token.address).mint(&user, &(2 * whale));
+        assert_eq!(
+            calculate_interest(whale, MAX_INTEREST_RATE, MAX_LOCK_DURATION),
+            Ok(5 * whale)
+        );
+
+        // The full triple product would exceed i128::MAX
+        let linear = client.create_goal(
+            &user,
+            &whale,
+            &MAX_LOCK_DURATION,
+            &MAX_INTEREST_RATE,
+            &None,
+            &None,
+            &0,
+        );
+        // Daily compounding scales the balance by COMPOUND_SCALE first, and
+        // previously overflowed on each day's step at far smaller balances
+        let daily = client.create_goal(
+            &user,
+            &(whale / 10),
+            &MAX_LOCK_DURATION,
+            &MAX_INTEREST_RATE,
+            &None,
+            &None,
+            &0,
+        );
+        client.set_compound_mode(&user, &daily, &CompoundMode::Periodic);
+        assert!(whale
+            .checked_mul(MAX_INTEREST_RATE as i128)
+            .and_then(|v| v.checked_mul(MAX_LOCK_DURATION as i128))
+            .is_none());
+
+        env.ledger().with_mut(|li| li.timestamp += MAX_LOCK_DURATION);
+        client.compound_interest(&user, &linear);
+        client.compound_interest(&user, &daily);
+        assert_eq!(client.get_goal(&user, &linear).accrued_interest, 5 * whale);
+        assert!(client.get_goal(&user, &daily).accrued_interest > 5 * whale / 10);
+        assert_eq!(client.get_current_balance(&user, &linear), 6 * whale);
+    }
+
+    #[test]
+    fn test_split_goal_keeps_terms() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+        fund_reserve(&env, &client, &admin, 1000);
+        let year = 365 * 86400;
+        let goal_id = client.create_goal(&user, &4000, &(2 * year), &1000, &None, &None, &0);
+        env.ledger().with_mut(|li| li.timestamp += year);
+
+        assert_eq!(
+            client.try_split_goal(&user, &goal_id, &4000),
+            Err(Ok(Error::InvalidSplit))
+        );
+        let child_id = client.split_goal(&user, &goal_id, &1000);
+        let parent = client.get_goal(&user, &goal_id);
+        let child = client.get_goal(&user, &child_id);
+        assert_eq!((parent.principal, parent.accrued_interest), (3000, 300));
+        assert_eq!((child.principal, child.accrued_interest), (1000, 100));
+        assert_eq!(child.start_time, parent.start_time);
+        assert_eq!(child.unlock_time, parent.unlock_time);
+        assert_eq!(child.interest_rate, parent.interest_rate);
+        assert_eq!(client.get_total_value_locked(), 4000);
+        assert_eq!(client.get_user_goal_count(&user), 2);
+
+        // Both halves still unlock together and keep compounding their share
+        assert_eq!(
+            client.try_withdraw(&user, &child_id),
+            Err(Ok(Error::StillLocked))
+        );
+        env.ledger().with_mut(|li| li.timestamp += year);
+        assert_eq!(client.withdraw(&user, &child_id), 1210);
+        assert_eq!(client.withdraw(&user, &goal_id), 3630);
+    }
+
+    #[test]
+    fn test_merge_goals() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+        fund_reserve(&env, &client, &admin, 1000);
+        let year = 365 * 86400;
+        let goal_a = client.create_goal(&user, &2000, &year, &1000, &None, &None, &0);
+        let goal_b = client.create_goal(&user, &1000, &(2 * year), &1000, &None, &None, &0);
+        let other_rate = client.create_goal(&user, &1000, &year, &500, &None, &None, &0);
+
+        assert_eq!(
+            client.try_merge_goals(&user, &goal_a, &other_rate),
+            Err(Ok(Error::InvalidSplit))
+        );
+        assert_eq!(
+            client.try_merge_goals(&user, &goal_a, &goal_a),
+            Err(Ok(Error::InvalidSplit))
+        );
+
+        env.ledger().with_mut(|li| li.timestamp += year / 2);
+        client.merge_goals(&user, &goal_a, &goal_b);
+        let merged = client.get_goal(&user, &goal_a);
+        assert_eq!((merged.principal, merged.accrued_interest), (3000, 150));
+        assert_eq!(merged.unlock_time, 2 * year);
+        assert!(!client.get_goal(&user, &goal_b).is_active);
+        assert_eq!(client.get_total_value_locked(), 4000);
+
+        // The merged goal keeps the later unlock
+        env.ledger().with_mut(|li| li.timestamp += year);
+        assert_eq!(
+            client.try_withdraw(&user, &goal_a),
+            Err(Ok(Error::StillLocked))
+        );
+        assert_eq!(
+            client.try_withdraw(&user, &goal_b),
+            Err(Ok(Error::GoalInactive))
+        );
+    }
+
+    #[test]
+    fn test_solvency_check() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            token,
+        } = setup();
+        fund_reserve(&env, &client, &admin, 1000);
+        let year = 365 * 86400;
+        let goal_id = client.create_goal(&user, &4000, &year, &1000, &None, &None, &0);
+        env.ledger().with_mut(|li| li.timestamp += year);
+        client.compound_interest(&user, &goal_id);
+
+        // Setup pre-funds the contract, so it is comfortably solvent
+        assert_eq!(client.solvency_check(), (100000 + 1000 + 4000, 4400));
+
+        // Drain the contract's holdings below what it owes
+        let drain = Address::generate(&env);
+        env.as_contract(&client.address, || {
+            token.transfer(&client.address, &drain, &101000);
+        });
+        let (balance, liabilities) = client.solvency_check();
+        assert_eq!((balance, liabilities), (4000, 4400));
+        assert!(balance < liabilities);
+    }
+
+    #[test]
+    fn test_creation_rate_limit_per_ledger() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            token,
+        } = setup();
+        client.set_creation_rate_limit(&admin, &Some(2));
+
+        client.create_goal(&user, &100, &THIRTY_DAYS, &0, &None, &None, &0);
+        client.create_goal(&user, &100, &THIRTY_DAYS, &0, &None, &None, &0);
+        assert_eq!(
+            client.try_create_goal(&user, &100, &THIRTY_DAYS, &0, &None, &None, &0),
+            Err(Ok(Error::CooldownActive))
+        );
+
+        // Other addresses and later ledgers have their own allowance
+        let other = Address::generate(&env);
+        StellarAssetClient::new(&env, &token.address).mint(&other, &100);
+        client.create_goal(&other, &100, &THIRTY_DAYS, &0, &None, &None, &0);
+        env.ledger().with_mut(|li| li.sequence_number += 1);
+        client.create_goal(&user, &100, &THIRTY_DAYS, &0, &None, &None, &0);
+
+        client.set_creation_rate_limit(&admin, &None);
+        for _ in 0..3 {
+            client.create_goal(&user, &100, &THIRTY_DAYS, &0, &None, &None, &0);
         }
+    }
 
-        env.storage()
-            .instance()
-            .set(&StorageKey::EmergencyPenalty, &new_penalty);
+    #[test]
+    fn test_list_goals_by_status() {
+        let Setup {
+            env, client, user, ..
+        } = setup();
+        let withdrawn = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        client.create_goal(&user, &2000, &THIRTY_DAYS, &0, &None, &None, &0);
+        client.create_goal(&user, &3000, &(2 * THIRTY_DAYS), &0, &None, &None, &0);
+        env.ledger().with_mut(|li| li.timestamp += THIRTY_DAYS);
+        client.withdraw(&user, &withdrawn);
 
-        Ok(())
+        let count = |status: GoalStatus, start: u32, limit: u32| {
+            client
+                .list_goals_by_status(&user, &status, &start, &limit)
+                .len()
+        };
+        assert_eq!(count(GoalStatus::Locked, 0, 10), 1);
+        assert_eq!(count(GoalStatus::Matured, 0, 10), 1);
+        assert_eq!(count(GoalStatus::Withdrawn, 0, 10), 1);
+
+        // Pages scan the goal list, so a later page finds the locked goal
+        assert_eq!(count(GoalStatus::Locked, 0, 2), 0);
+        assert_eq!(count(GoalStatus::Locked, 2, 2), 1);
+
+        let page = client.list_goals_by_status(&user, &GoalStatus::Matured, &0, &10);
+        assert_eq!(page.get(0).unwrap().principal, 2000);
+        let page = client.list_goals_by_status(&user, &GoalStatus::Locked, &0, &10);
+        assert_eq!(page.get(0).unwrap().principal, 3000);
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use soroban_sdk::{testutils::{Address as _, Ledger}, token};
+    #[test]
+    fn test_accrual_start_offset_cliff() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+        let year = 365 * 86400;
+        let goal_id = client.create_goal(&user, &4000, &(2 * year), &1000, &None, &None, &0);
+        assert_eq!(
+            client.try_set_accrual_start_offset(&admin, &user, &goal_id, &(3 * year)),
+            Err(Ok(Error::InvalidDuration))
+        );
+        client.set_accrual_start_offset(&admin, &user, &goal_id, &year);
+
+        // Nothing accrues during the cliff, even when compounded midway
+        env.ledger().with_mut(|li| li.timestamp += year / 2);
+        client.compound_interest(&user, &goal_id);
+        env.ledger().with_mut(|li| li.timestamp += year / 2);
+        assert_eq!(client.get_current_balance(&user, &goal_id), 4000);
+
+        // A compound spanning the cliff only counts time after it
+        env.ledger().with_mut(|li| li.timestamp += year / 2);
+        assert_eq!(client.get_current_balance(&user, &goal_id), 4200);
+        client.compound_interest(&user, &goal_id);
+        assert_eq!(client.get_goal(&user, &goal_id).accrued_interest, 200);
+    }
 
     #[test]
-    fn test_create_and_withdraw_goal() {
+    fn test_penalty_destination_routing() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            token,
+        } = setup();
+
+        // Default: the penalty goes to the treasury (the admin here)
+        let goal_id = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        assert_eq!(client.emergency_withdraw(&user, &goal_id), 900);
+        assert_eq!(token.balance(&admin), 100);
+
+        client.set_penalty_destination(&admin, &PenaltyDestination::Reserve, &None);
+        let goal_id = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        client.emergency_withdraw(&user, &goal_id);
+        assert_eq!(client.get_reserve_balance(), 100);
+        assert_eq!(token.balance(&admin), 100);
+
+        let burn = Address::generate(&env);
+        client.set_penalty_destination(&admin, &PenaltyDestination::Burn, &Some(burn.clone()));
+        let goal_id = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        client.emergency_withdraw(&user, &goal_id);
+        assert_eq!(token.balance(&burn), 100);
+        assert_eq!(client.get_reserve_balance(), 100);
+    }
+
+    #[test]
+    fn test_rejects_contract_as_token_admin_or_treasury() {
         let env = Env::default();
         env.mock_all_auths();
-
-        let contract_id = env.register_contract(None, TimeLockedSavings);
+        let contract_id = env.register(TimeLockedSavings, ());
         let client = TimeLockedSavingsClient::new(&env, &contract_id);
-
         let admin = Address::generate(&env);
-        let user = Address::generate(&env);
         let token_id = env.register_stellar_asset_contract_v2(admin.clone());
-        let token = token::Client::new(&env, &token_id.address());
 
-        // Initialize contract
+        assert_eq!(
+            client.try_initialize(&contract_id, &admin, &1000),
+            Err(Ok(Error::InvalidToken))
+        );
+        assert_eq!(
+            client.try_initialize(&token_id.address(), &contract_id, &1000),
+            Err(Ok(Error::Unauthorized))
+        );
         client.initialize(&token_id.address(), &admin, &1000);
 
-        // Mint tokens to user
-        token.mint(&user, &10000);
+        assert_eq!(
+            client.try_add_supported_token(&admin, &contract_id),
+            Err(Ok(Error::InvalidToken))
+        );
+        assert_eq!(
+            client.try_set_treasury(&admin, &contract_id),
+            Err(Ok(Error::Unauthorized))
+        );
+        assert_eq!(
+            client.try_propose_admin(&admin, &contract_id),
+            Err(Ok(Error::Unauthorized))
+        );
+    }
 
-        // Create goal: 10000 tokens, 30 days lock, 5% interest
-        let goal_id = client.create_goal(&user, &10000, &2592000, &500);
+    #[test]
+    fn test_goals_maturing_within_window() {
+        let Setup {
+            env, client, user, ..
+        } = setup();
+        let day = 86400;
+        let inside = client.create_goal(&user, &1000, &(2 * day), &0, &None, &None, &0);
+        client.create_goal(&user, &1000, &(2 * day + 1), &0, &None, &None, &0);
+        let matured = client.create_goal(&user, &1000, &day, &0, &None, &None, &0);
+        env.ledger().with_mut(|li| li.timestamp += day);
 
-        // Fast forward time to unlock
-        env.ledger().with_mut(|li| li.timestamp = 2592001);
+        // Exactly at the window's end is inside, one second past is not
+        let ids = client.goals_maturing_within(&user, &day, &0, &10);
+        assert_eq!(ids, Vec::from_array(&env, [inside, matured]));
 
-        // Withdraw
-        let amount = client.withdraw(&user, &goal_id);
-        assert!(amount > 10000); // Should have interest
+        // Once matured a goal drops out; withdrawn goals never appear
+        env.ledger().with_mut(|li| li.timestamp += 1);
+        client.withdraw(&user, &matured);
+        let ids = client.goals_maturing_within(&user, &day, &0, &10);
+        assert_eq!(ids.len(), 2);
+        assert!(!ids.contains(matured));
+        assert_eq!(client.goals_maturing_within(&user, &day, &0, &1).len(), 1);
+    }
+
+    #[test]
+    fn test_emergency_withdraw_partial_then_full() {
+        let Setup {
+            client,
+            admin,
+            user,
+            token,
+            ..
+        } = setup();
+        let goal_id = client.create_goal(&user, &4000, &THIRTY_DAYS, &0, &None, &None, &0);
+        assert_eq!(
+            client.try_emergency_withdraw_partial(&user, &goal_id, &4001),
+            Err(Ok(Error::AmountExceedsBalance))
+        );
+
+        // 10% of the 1000 taken, the rest of the goal stays locked and active
+        assert_eq!(client.emergency_withdraw_partial(&user, &goal_id, &1000), 900);
+        let goal = client.get_goal(&user, &goal_id);
+        assert!(goal.is_active);
+        assert_eq!(goal.principal, 3000);
+        assert_eq!(token.balance(&user), 6000 + 900);
+        assert_eq!(token.balance(&admin), 100);
+        assert_eq!(client.get_total_value_locked(), 3000);
+
+        // Taking the remainder closes the goal like a full emergency exit
+        assert_eq!(client.emergency_withdraw_partial(&user, &goal_id, &3000), 2700);
+        assert!(!client.get_goal(&user, &goal_id).is_active);
+        assert_eq!(token.balance(&user), 9600);
+        assert_eq!(token.balance(&admin), 400);
+        assert_eq!(client.get_total_value_locked(), 0);
+    }
+
+    #[test]
+    fn test_min_return_clamps_emergency_penalty() {
+        let Setup {
+            client,
+            admin,
+            user,
+            token,
+            ..
+        } = setup();
+        client.set_emergency_penalty(&admin, &5000);
+        assert_eq!(
+            client.try_set_min_return_bps(&admin, &10001),
+            Err(Ok(Error::InvalidAmount))
+        );
+
+        // A 50% penalty is clamped so the owner keeps 80%
+        client.set_min_return_bps(&admin, &8000);
+        let goal_id = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        assert_eq!(client.quote_emergency_withdraw(&user, &goal_id), (800, 200));
+        assert_eq!(client.emergency_withdraw(&user, &goal_id), 800);
+        assert_eq!(token.balance(&admin), 200);
+
+        // A floor below the penalty's own return changes nothing
+        client.set_min_return_bps(&admin, &4000);
+        let goal_id = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        assert_eq!(client.emergency_withdraw(&user, &goal_id), 500);
+    }
+
+    #[test]
+    fn test_get_goals_batch() {
+        let Setup {
+            env, client, user, ..
+        } = setup();
+        let first = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        let second = client.create_goal(&user, &2000, &THIRTY_DAYS, &0, &None, &None, &0);
+
+        let ids = Vec::from_array(&env, [second, 99, first]);
+        let goals = client.get_goals(&user, &ids);
+        assert_eq!(goals.len(), 3);
+        assert_eq!(goals.get(0).unwrap().unwrap().principal, 2000);
+        assert!(goals.get(1).unwrap().is_none());
+        assert_eq!(goals.get(2).unwrap().unwrap().principal, 1000);
+
+        // Another owner's IDs resolve to None
+        let other = Address::generate(&env);
+        let goals = client.get_goals(&other, &ids);
+        assert!(goals.iter().all(|goal| goal.is_none()));
+
+        let mut too_many = Vec::new(&env);
+        for goal_id in 0..=MAX_PAGE_LIMIT as u64 {
+            too_many.push_back(goal_id);
+        }
+        assert!(matches!(
+            client.try_get_goals(&user, &too_many),
+            Err(Ok(Error::InvalidBatch))
+        ));
+    }
+
+    #[test]
+    fn test_admin_reduce_rate_only_lowers() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+        let year = 365 * 86400;
+        let goal_id = client.create_goal(&user, &4000, &(2 * year), &1000, &None, &None, &0);
+
+        assert_eq!(
+            client.try_admin_reduce_rate(&admin, &user, &goal_id, &1000),
+            Err(Ok(Error::RateTooHigh))
+        );
+        assert_eq!(
+            client.try_admin_reduce_rate(&admin, &user, &goal_id, &1500),
+            Err(Ok(Error::RateTooHigh))
+        );
+
+        // The year at 10% is kept; the next year earns 5% on the new balance
+        env.ledger().with_mut(|li| li.timestamp += year);
+        client.admin_reduce_rate(&admin, &user, &goal_id, &500);
+        let goal = client.get_goal(&user, &goal_id);
+        assert_eq!((goal.interest_rate, goal.accrued_interest), (500, 400));
+        env.ledger().with_mut(|li| li.timestamp += year);
+        assert_eq!(client.get_current_balance(&user, &goal_id), 4400 + 220);
+    }
+
+    #[test]
+    fn test_withdrawn_at_records_close_time() {
+        let Setup {
+            env, client, user, ..
+        } = setup();
+        let matured = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        let early = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        assert_eq!(client.get_goal(&user, &matured).withdrawn_at, 0);
+
+        env.ledger().with_mut(|li| li.timestamp = 12345);
+        client.emergency_withdraw(&user, &early);
+        assert_eq!(client.get_goal(&user, &early).withdrawn_at, 12345);
+        assert_eq!(client.get_goal(&user, &matured).withdrawn_at, 0);
+
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS + 7);
+        client.withdraw(&user, &matured);
+        assert_eq!(client.get_goal(&user, &matured).withdrawn_at, THIRTY_DAYS + 7);
+    }
+
+    #[test]
+    fn test_version() {
+        let Setup { client, .. } = setup();
+        assert_eq!(client.version(), CONTRACT_VERSION);
+        assert_eq!(client.version(), 1);
+    }
+
+    #[test]
+    fn test_claim_installment_respects_withdraw_cooldown() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+        client.set_withdraw_cooldown(&admin, &3600);
+        let goal_id = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS);
+
+        // One huge installment can't skip the request/cooldown flow
+        client.setup_drawdown(&user, &goal_id, &1000, &86400);
+        assert_eq!(
+            client.try_claim_installment(&user, &goal_id),
+            Err(Ok(Error::WithdrawRequestRequired))
+        );
+        client.request_withdraw(&user, &goal_id);
+        assert_eq!(
+            client.try_claim_installment(&user, &goal_id),
+            Err(Ok(Error::CooldownNotElapsed))
+        );
+
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS + 3600);
+        assert_eq!(client.claim_installment(&user, &goal_id), 1000);
+    }
+
+    #[test]
+    fn test_renewal_drops_a_pending_withdraw_request() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+        fund_reserve(&env, &client, &admin, 10000);
+        client.set_withdraw_cooldown(&admin, &3600);
+
+        let goal_id = client.create_goal(&user, &1000, &THIRTY_DAYS, &1000, &None, &None, &0);
+        client.set_auto_renew(&user, &goal_id, &true);
+
+        // Requested during the first term, claimable right after it
+        client.request_withdraw(&user, &goal_id);
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS);
+        client.process_renewal(&user, &goal_id);
+
+        // The request belonged to the old term and cannot be claimed in the new one
+        assert_eq!(client.get_withdraw_request(&user, &goal_id), None);
+        env.ledger().with_mut(|li| li.timestamp = 2 * THIRTY_DAYS);
+        assert_eq!(
+            client.try_claim_withdraw(&user, &goal_id),
+            Err(Ok(Error::WithdrawNotRequested))
+        );
+
+        // A fresh request is needed and waits out the cooldown again
+        client.request_withdraw(&user, &goal_id);
+        assert_eq!(
+            client.try_claim_withdraw(&user, &goal_id),
+            Err(Ok(Error::CooldownNotElapsed))
+        );
+        env.ledger().with_mut(|li| li.timestamp += 3600);
+        assert!(client.claim_withdraw(&user, &goal_id) > 1000);
+    }
+
+    #[test]
+    fn test_transfer_goal_respects_recipient_limits() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+        let friend = Address::generate(&env);
+        let goal_id = client.create_goal(&user, &1000, &THIRTY_DAYS, &500, &None, &None, &0);
+
+        // The recipient is already at the per-user cap
+        let held = client.create_goal(&user, &1000, &THIRTY_DAYS, &500, &None, &None, &0);
+        client.transfer_goal(&user, &friend, &held);
+        client.set_max_goals_per_user(&admin, &Some(1));
+        assert_eq!(
+            client.try_transfer_goal(&user, &friend, &goal_id),
+            Err(Ok(Error::GoalLimitReached))
+        );
+        client.set_max_goals_per_user(&admin, &None);
+
+        // During early access the recipient must be approved
+        let stranger = Address::generate(&env);
+        client.set_whitelist_enabled(&admin, &true);
+        client.add_to_whitelist(&admin, &friend);
+        assert_eq!(
+            client.try_transfer_goal(&user, &stranger, &goal_id),
+            Err(Ok(Error::Unauthorized))
+        );
+        client.transfer_goal(&user, &friend, &goal_id);
+        assert_eq!(client.get_goal(&friend, &goal_id).owner, friend);
+    }
+
+    #[test]
+    fn test_reentrancy_guard_covers_token_moving_entry_points() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            token,
+        } = setup();
+        let goal_id = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        let other = Address::generate(&env);
+        env.as_contract(&client.address, || {
+            env.storage()
+                .instance()
+                .set(&StorageKey::ReentrancyLock, &true);
+        });
+
+        let blocked = Err(Ok(Error::Reentrancy));
+        assert_eq!(client.try_withdraw_to(&user, &goal_id, &other), blocked);
+        assert_eq!(client.try_withdraw_partial(&user, &goal_id, &100), blocked);
+        let recipients = vec![&env, (other.clone(), 10000u32)];
+        assert_eq!(client.try_withdraw_split(&user, &goal_id, &recipients), blocked);
+        assert_eq!(client.try_withdraw_principal_only(&user, &goal_id), blocked);
+        assert_eq!(client.try_cancel_goal(&user, &goal_id), blocked);
+        assert_eq!(client.try_claim_installment(&user, &goal_id), blocked);
+        assert_eq!(client.try_claim_as_beneficiary(&other, &user, &goal_id), blocked);
+        assert_eq!(client.try_claim_withdraw(&user, &goal_id), blocked);
+        assert_eq!(client.try_deposit(&user, &goal_id, &100), blocked);
+        assert_eq!(
+            client.try_create_goal_until(&user, &1000, &THIRTY_DAYS, &0),
+            Err(Ok(Error::Reentrancy))
+        );
+        let batch = vec![&env, (1000i128, THIRTY_DAYS, 0u32)];
+        assert_eq!(
+            client.try_create_goals_batch(&user, &batch, &None),
+            Err(Ok(Error::Reentrancy))
+        );
+        assert_eq!(
+            client.try_create_goal_prepaid(&user, &user, &1000, &THIRTY_DAYS, &500),
+            Err(Ok(Error::Reentrancy))
+        );
+        assert_eq!(client.try_claim_iou(&user), blocked);
+        assert_eq!(client.try_fund_reserve(&admin, &100), blocked);
+        assert_eq!(client.try_fund_token_reserve(&admin, &token.address, &100), blocked);
+        assert_eq!(client.try_admin_close_dust_goal(&admin, &user, &goal_id), blocked);
+    }
+
+    #[test]
+    fn test_beneficiary_claim_respects_withdraw_cooldown() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            token,
+        } = setup();
+        let heir = Address::generate(&env);
+        client.set_beneficiary_window(&admin, &THIRTY_DAYS);
+        client.set_withdraw_cooldown(&admin, &3600);
+        let goal_id = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        client.set_beneficiary(&user, &goal_id, &Some(heir.clone()));
+
+        env.ledger().with_mut(|li| li.timestamp = 2 * THIRTY_DAYS);
+        assert_eq!(
+            client.try_claim_as_beneficiary(&heir, &user, &goal_id),
+            Err(Ok(Error::WithdrawRequestRequired))
+        );
+
+        client.request_withdraw(&user, &goal_id);
+        assert_eq!(
+            client.try_claim_as_beneficiary(&heir, &user, &goal_id),
+            Err(Ok(Error::CooldownNotElapsed))
+        );
+
+        env.ledger().with_mut(|li| li.timestamp += 3600);
+        assert_eq!(client.claim_as_beneficiary(&heir, &user, &goal_id), 1000);
+        assert_eq!(token.balance(&heir), 1000);
+    }
+
+    #[test]
+    fn test_beneficiary_request_and_owner_cancellation() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            token,
+        } = setup();
+        let heir = Address::generate(&env);
+        let stranger = Address::generate(&env);
+        client.set_beneficiary_window(&admin, &THIRTY_DAYS);
+        client.set_withdraw_cooldown(&admin, &3600);
+        let goal_id = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+        client.set_beneficiary(&user, &goal_id, &Some(heir.clone()));
+
+        // Only the beneficiary, and only once the window has passed
+        env.ledger().with_mut(|li| li.timestamp = THIRTY_DAYS);
+        assert_eq!(
+            client.try_request_beneficiary_claim(&heir, &user, &goal_id),
+            Err(Ok(Error::StillLocked))
+        );
+        env.ledger().with_mut(|li| li.timestamp = 2 * THIRTY_DAYS);
+        assert_eq!(
+            client.try_request_beneficiary_claim(&stranger, &user, &goal_id),
+            Err(Ok(Error::Unauthorized))
+        );
+        assert_eq!(
+            client.request_beneficiary_claim(&heir, &user, &goal_id),
+            2 * THIRTY_DAYS + 3600
+        );
+        assert_eq!(
+            client.try_request_beneficiary_claim(&heir, &user, &goal_id),
+            Err(Ok(Error::WithdrawAlreadyRequested))
+        );
+        assert_eq!(client.get_withdraw_request(&user, &goal_id), Some(2 * THIRTY_DAYS));
+
+        // An owner who is still around voids the request during the cooldown
+        client.cancel_withdraw(&user, &goal_id);
+        env.ledger().with_mut(|li| li.timestamp += 3600);
+        assert_eq!(
+            client.try_claim_as_beneficiary(&heir, &user, &goal_id),
+            Err(Ok(Error::WithdrawRequestRequired))
+        );
+
+        // A fresh request restarts the cooldown
+        assert_eq!(
+            client.request_beneficiary_claim(&heir, &user, &goal_id),
+            2 * THIRTY_DAYS + 7200
+        );
+        assert_eq!(
+            client.try_claim_as_beneficiary(&heir, &user, &goal_id),
+            Err(Ok(Error::CooldownNotElapsed))
+        );
+        env.ledger().with_mut(|li| li.timestamp += 3600);
+        assert_eq!(client.claim_as_beneficiary(&heir, &user, &goal_id), 1000);
+        assert_eq!(token.balance(&heir), 1000);
+        assert_eq!(
+            client.try_request_beneficiary_claim(&heir, &user, &goal_id),
+            Err(Ok(Error::AlreadyWithdrawn))
+        );
+    }
+
+    #[test]
+    fn test_merge_keeps_the_earlier_creation_time() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+        fund_reserve(&env, &client, &admin, 10000);
+
+        let old = client.create_goal(&user, &5000, &THIRTY_DAYS, &500, &None, &None, &0);
+        env.ledger().with_mut(|li| li.timestamp = 600);
+        let fresh = client.create_goal(&user, &100, &THIRTY_DAYS, &500, &None, &None, &0);
+        let twin_a = client.create_goal(&user, &100, &THIRTY_DAYS, &500, &None, &None, &0);
+        let twin_b = client.create_goal(&user, &100, &THIRTY_DAYS, &500, &None, &None, &0);
+
+        client.merge_goals(&user, &fresh, &old);
+        assert_eq!(client.get_goal(&user, &fresh).created_at, 0);
+        assert_eq!(
+            client.try_cancel_goal(&user, &fresh),
+            Err(Ok(Error::GracePeriodExpired))
+        );
+
+        // Even two goals both inside the grace window can't be cancelled once merged
+        client.merge_goals(&user, &twin_a, &twin_b);
+        assert_eq!(client.get_goal(&user, &twin_a).created_at, 600);
+        assert_eq!(
+            client.try_cancel_goal(&user, &twin_a),
+            Err(Ok(Error::GracePeriodExpired))
+        );
+    }
+
+    #[test]
+    fn test_split_goal_respects_anti_spam_limits() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+        let goal_id = client.create_goal(&user, &1000, &THIRTY_DAYS, &0, &None, &None, &0);
+
+        // Neither half may fall below the minimum deposit
+        client.set_deposit_limits(&admin, &Some(300), &None);
+        assert_eq!(
+            client.try_split_goal(&user, &goal_id, &1),
+            Err(Ok(Error::DepositBelowMinimum))
+        );
+        assert_eq!(
+            client.try_split_goal(&user, &goal_id, &800),
+            Err(Ok(Error::DepositBelowMinimum))
+        );
+
+        // A split is a creation for the per-ledger rate limit
+        client.set_creation_rate_limit(&admin, &Some(1));
+        client.split_goal(&user, &goal_id, &300);
+        assert_eq!(
+            client.try_split_goal(&user, &goal_id, &300),
+            Err(Ok(Error::CooldownActive))
+        );
+        env.ledger().with_mut(|li| li.sequence_number += 1);
+        client.split_goal(&user, &goal_id, &300);
+        assert_eq!(client.get_goal(&user, &goal_id).principal, 400);
+    }
+
+    #[test]
+    fn test_blended_top_up_cannot_exceed_max_lock() {
+        let Setup {
+            env,
+            client,
+            admin,
+            user,
+            ..
+        } = setup();
+        client.set_top_up_mode(&admin, &TopUpMode::ExtendBlended);
+        let goal_id = client.create_goal(&user, &1000, &MAX_LOCK_DURATION, &0, &None, &None, &0);
+
+        // Halfway through, an equal top-up would blend to a 1.25x total lock
+        env.ledger().with_mut(|li| li.timestamp = MAX_LOCK_DURATION / 2);
+        assert_eq!(
+            client.try_deposit(&user, &goal_id, &1000),
+            Err(Ok(Error::InvalidDuration))
+        );
+        assert_eq!(client.get_goal(&user, &goal_id).unlock_time, MAX_LOCK_DURATION);
     }
 }